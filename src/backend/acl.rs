@@ -0,0 +1,54 @@
+// the single user every connection runs as, restricted through the
+// acl-allow-commands / acl-deny-commands config options
+#[derive(Debug, Clone)]
+pub struct AclUser {
+    pub name: String,
+    // an empty allow list lets every command through
+    pub allowed_commands: Vec<String>,
+    pub denied_commands: Vec<String>,
+}
+
+impl Default for AclUser {
+    fn default() -> Self {
+        Self {
+            name: "default".to_string(),
+            allowed_commands: Vec::new(),
+            denied_commands: Vec::new(),
+        }
+    }
+}
+
+impl AclUser {
+    pub fn can_run(&self, command: &str) -> bool {
+        let listed = |list: &[String]| list.iter().any(|c| c == command);
+        !listed(&self.denied_commands)
+            && (self.allowed_commands.is_empty() || listed(&self.allowed_commands))
+    }
+}
+
+// command lists are written space separated, like `get set hget`
+pub(crate) fn parse_command_list(value: &str) -> Vec<String> {
+    value
+        .split_whitespace()
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_acl_user_can_run() {
+        let mut user = AclUser::default();
+        assert!(user.can_run("flushall"));
+
+        user.denied_commands = parse_command_list("FLUSHALL flushdb");
+        assert!(!user.can_run("flushall"));
+        assert!(user.can_run("get"));
+
+        user.allowed_commands = parse_command_list("get set");
+        assert!(user.can_run("get"));
+        assert!(!user.can_run("hget"));
+    }
+}
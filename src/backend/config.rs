@@ -0,0 +1,143 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use super::acl::{AclUser, parse_command_list};
+
+#[derive(Debug, Clone)]
+pub struct BackendConfig {
+    pub hash_max_listpack_entries: usize,
+    pub hash_max_listpack_value: usize,
+    pub set_max_listpack_entries: usize,
+    pub set_max_listpack_value: usize,
+    pub zset_max_listpack_entries: usize,
+    pub zset_max_listpack_value: usize,
+    // most elements a single reply may hold, 0 means no limit
+    pub max_reply_elements: usize,
+    // memory budget in bytes, 0 means no limit
+    pub maxmemory: usize,
+    // most arguments a command array may carry, checked on the header before parsing
+    pub max_multibulk_len: usize,
+    // largest string a command may build, in bytes
+    pub proto_max_bulk_len: usize,
+    // snapshots are written to dir/dbfilename
+    pub dir: String,
+    pub dbfilename: String,
+    pub default_user: AclUser,
+    // file the server was started with, CONFIG REWRITE refuses to run without one
+    pub config_file: Option<PathBuf>,
+}
+
+impl Default for BackendConfig {
+    fn default() -> Self {
+        Self {
+            hash_max_listpack_entries: 128,
+            hash_max_listpack_value: 64,
+            set_max_listpack_entries: 128,
+            set_max_listpack_value: 64,
+            zset_max_listpack_entries: 128,
+            zset_max_listpack_value: 64,
+            max_reply_elements: 0,
+            maxmemory: 0,
+            max_multibulk_len: 1024 * 1024,
+            proto_max_bulk_len: 512 * 1024 * 1024,
+            dir: ".".to_string(),
+            dbfilename: "dump.rdb".to_string(),
+            default_user: AclUser::default(),
+            config_file: None,
+        }
+    }
+}
+
+impl BackendConfig {
+    /// Read a redis.conf style file: one `name value` directive per line, blank lines and
+    /// lines starting with `#` are skipped.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("can't read config file {}: {}", path.display(), e))?;
+        let mut config = Self::default();
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, value) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            config
+                .set(&name.to_ascii_lowercase(), value.trim())
+                .map_err(|e| format!("{}:{}: {}", path.display(), i + 1, e))?;
+        }
+        config.config_file = Some(path.to_path_buf());
+        Ok(config)
+    }
+
+    pub fn snapshot_path(&self) -> PathBuf {
+        Path::new(&self.dir).join(&self.dbfilename)
+    }
+
+    pub fn get(&self, name: &str) -> Option<String> {
+        let value = match name {
+            "dir" => return Some(self.dir.clone()),
+            "dbfilename" => return Some(self.dbfilename.clone()),
+            "acl-allow-commands" => return Some(self.default_user.allowed_commands.join(" ")),
+            "acl-deny-commands" => return Some(self.default_user.denied_commands.join(" ")),
+            "hash-max-listpack-entries" => self.hash_max_listpack_entries,
+            "hash-max-listpack-value" => self.hash_max_listpack_value,
+            "set-max-listpack-entries" => self.set_max_listpack_entries,
+            "set-max-listpack-value" => self.set_max_listpack_value,
+            "zset-max-listpack-entries" => self.zset_max_listpack_entries,
+            "zset-max-listpack-value" => self.zset_max_listpack_value,
+            "max-reply-elements" => self.max_reply_elements,
+            "proto-max-bulk-len" => self.proto_max_bulk_len,
+            "maxmemory" => self.maxmemory,
+            "max-multibulk-len" => self.max_multibulk_len,
+            _ => return None,
+        };
+        Some(value.to_string())
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let field = match name {
+            "dir" => {
+                self.dir = value.to_string();
+                return Ok(());
+            }
+            "dbfilename" => {
+                self.dbfilename = value.to_string();
+                return Ok(());
+            }
+            "acl-allow-commands" => {
+                self.default_user.allowed_commands = parse_command_list(value);
+                return Ok(());
+            }
+            "acl-deny-commands" => {
+                self.default_user.denied_commands = parse_command_list(value);
+                return Ok(());
+            }
+            "hash-max-listpack-entries" => &mut self.hash_max_listpack_entries,
+            "hash-max-listpack-value" => &mut self.hash_max_listpack_value,
+            "set-max-listpack-entries" => &mut self.set_max_listpack_entries,
+            "set-max-listpack-value" => &mut self.set_max_listpack_value,
+            "zset-max-listpack-entries" => &mut self.zset_max_listpack_entries,
+            "zset-max-listpack-value" => &mut self.zset_max_listpack_value,
+            "max-reply-elements" => &mut self.max_reply_elements,
+            "proto-max-bulk-len" => &mut self.proto_max_bulk_len,
+            "maxmemory" => &mut self.maxmemory,
+            "max-multibulk-len" => &mut self.max_multibulk_len,
+            _ => {
+                return Err(format!(
+                    "ERR Unknown option or number of arguments for CONFIG SET - '{}'",
+                    name
+                ));
+            }
+        };
+        *field = value.parse().map_err(|_| {
+            format!(
+                "ERR CONFIG SET failed (possibly related to argument '{}') - argument couldn't be parsed into an integer",
+                name
+            )
+        })?;
+        Ok(())
+    }
+}
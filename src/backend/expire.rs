@@ -0,0 +1,175 @@
+use std::{
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use dashmap::mapref::entry::Entry;
+
+use super::Backend;
+
+// how often the reaper sweeps, the same 10 times a second as the default hz of redis
+const REAP_INTERVAL: Duration = Duration::from_millis(100);
+
+// Keys expire in two ways, like redis: lazily, when a command touches a key past its
+// deadline, and actively, by a reaper thread removing the keys nobody touches.
+impl Backend {
+    /// EXPIRE: the key is removed `secs` seconds from now, a TTL that isn't positive
+    /// removes it right away. Returns false if the key doesn't exist.
+    pub fn expire(&self, key: &[u8], secs: i64) -> bool {
+        if !self.exists(key) {
+            return false;
+        }
+        if secs <= 0 {
+            self.remove(key);
+            return true;
+        }
+        match Instant::now().checked_add(Duration::from_secs(secs as u64)) {
+            Some(deadline) => self.expire_at(key, deadline),
+            None => false,
+        }
+    }
+
+    pub fn expire_at(&self, key: &[u8], deadline: Instant) -> bool {
+        if !self.exists(key) {
+            return false;
+        }
+        self.insert_deadline(key.to_vec(), deadline);
+        true
+    }
+
+    pub(crate) fn insert_deadline(&self, key: Vec<u8>, deadline: Instant) {
+        match self.expires.entry(key) {
+            Entry::Occupied(mut entry) => {
+                entry.insert(deadline);
+            }
+            Entry::Vacant(entry) => {
+                self.grow_memory(entry.key().len() + size_of::<Instant>());
+                entry.insert(deadline);
+            }
+        }
+    }
+
+    // returns true if the key had a TTL
+    pub(crate) fn remove_deadline(&self, key: &[u8]) -> bool {
+        let removed = self.expires.remove(key);
+        if let Some((key, _)) = &removed {
+            self.shrink_memory(key.len() + size_of::<Instant>());
+        }
+        removed.is_some()
+    }
+
+    // remaining seconds of a key TTL, -1 if the key has no TTL and -2 if it doesn't exist
+    pub fn ttl(&self, key: &[u8]) -> i64 {
+        if !self.exists(key) {
+            return -2;
+        }
+        self.deadline(key).map_or(-1, |deadline| {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            (remaining.as_millis() as i64 + 500) / 1000
+        })
+    }
+
+    // returns true if the TTL was removed, false if the key has none or doesn't exist
+    pub fn persist(&self, key: &[u8]) -> bool {
+        self.exists(key) && self.remove_deadline(key)
+    }
+
+    pub fn deadline(&self, key: &[u8]) -> Option<Instant> {
+        self.expires.get(key).map(|deadline| *deadline)
+    }
+
+    // removes the key if its deadline has passed and tells whether it did; must not be
+    // called while holding a reference into one of the maps
+    pub(crate) fn expire_if_due(&self, key: &[u8]) -> bool {
+        let due = self
+            .expires
+            .get(key)
+            .is_some_and(|deadline| *deadline <= Instant::now());
+        if due {
+            self.remove(key);
+        }
+        due
+    }
+
+    // one sweep of the reaper: expired keys first, then expired hash fields
+    pub(crate) fn reap_expired(&self) {
+        let now = Instant::now();
+        let due = self
+            .expires
+            .iter()
+            .filter(|v| *v.value() <= now)
+            .map(|v| v.key().clone())
+            .collect::<Vec<_>>();
+        for key in due {
+            self.expire_if_due(&key);
+        }
+        let hashes = self
+            .hexpires
+            .iter()
+            .map(|v| v.key().clone())
+            .collect::<Vec<_>>();
+        for key in hashes {
+            self.purge_expired_fields(&key);
+        }
+    }
+
+    // the thread only holds a weak reference, it stops once the last clone of the backend
+    // is dropped
+    pub(crate) fn spawn_reaper(&self) {
+        let inner = Arc::downgrade(&self.0);
+        thread::spawn(move || {
+            loop {
+                thread::sleep(REAP_INTERVAL);
+                match inner.upgrade() {
+                    Some(inner) => Backend(inner).reap_expired(),
+                    None => return,
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkString, RespFrame};
+
+    #[test]
+    fn test_expire_ttl_persist() {
+        let backend = Backend::new();
+        backend.set(b"key".to_vec(), BulkString::from("value").into());
+        assert_eq!(backend.ttl(b"key"), -1);
+        assert_eq!(backend.ttl(b"missing"), -2);
+        assert!(!backend.expire(b"missing", 10));
+
+        assert!(backend.expire(b"key", 10));
+        assert_eq!(backend.ttl(b"key"), 10);
+        assert!(backend.persist(b"key"));
+        assert!(!backend.persist(b"key"));
+        assert_eq!(backend.ttl(b"key"), -1);
+
+        assert!(backend.expire(b"key", 0));
+        assert!(!backend.exists(b"key"));
+    }
+
+    #[test]
+    fn test_expired_key_is_absent_before_the_reaper_runs() {
+        let backend = Backend::new();
+        backend.set(b"key".to_vec(), RespFrame::Integer(1));
+        backend.expire_at(b"key", Instant::now());
+
+        assert_eq!(backend.get(b"key"), None);
+        assert!(!backend.map.contains_key(b"key".as_slice()));
+        assert!(backend.expires.is_empty());
+    }
+
+    #[test]
+    fn test_set_clears_the_ttl() {
+        let backend = Backend::new();
+        backend.set(b"key".to_vec(), RespFrame::Integer(1));
+        backend.expire(b"key", 10);
+        backend.set(b"key".to_vec(), RespFrame::Integer(2));
+        assert_eq!(backend.ttl(b"key"), -1);
+    }
+}
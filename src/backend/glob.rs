@@ -0,0 +1,82 @@
+// glob-style matching with the same rules as redis KEYS/SCAN MATCH:
+// `*` any sequence, `?` any byte, `[abc]`/`[^a-z]` classes and `\` to escape
+pub(crate) fn glob_match(pattern: &[u8], s: &[u8]) -> bool {
+    match pattern {
+        [] => s.is_empty(),
+        [b'*', rest @ ..] => {
+            let rest = trim_stars(rest);
+            rest.is_empty() || (0..=s.len()).any(|i| glob_match(rest, &s[i..]))
+        }
+        [b'?', rest @ ..] => !s.is_empty() && glob_match(rest, &s[1..]),
+        [b'[', rest @ ..] => match s.split_first() {
+            Some((&c, tail)) => {
+                let (matched, rest) = match_class(rest, c);
+                matched && glob_match(rest, tail)
+            }
+            None => false,
+        },
+        [b'\\', c, rest @ ..] => s.first() == Some(c) && glob_match(rest, &s[1..]),
+        [c, rest @ ..] => s.first() == Some(c) && glob_match(rest, &s[1..]),
+    }
+}
+
+fn trim_stars(mut pattern: &[u8]) -> &[u8] {
+    while let [b'*', rest @ ..] = pattern {
+        pattern = rest;
+    }
+    pattern
+}
+
+// returns whether `c` is in the class and the pattern left after the closing `]`
+fn match_class(mut pattern: &[u8], c: u8) -> (bool, &[u8]) {
+    let negate = pattern.first() == Some(&b'^');
+    if negate {
+        pattern = &pattern[1..];
+    }
+    let mut matched = false;
+    loop {
+        match pattern {
+            [] => break,
+            [b']', rest @ ..] => {
+                pattern = rest;
+                break;
+            }
+            [b'\\', e, rest @ ..] => {
+                matched |= *e == c;
+                pattern = rest;
+            }
+            [a, b'-', b, rest @ ..] if *b != b']' => {
+                let (lo, hi) = if a <= b { (*a, *b) } else { (*b, *a) };
+                matched |= (lo..=hi).contains(&c);
+                pattern = rest;
+            }
+            [a, rest @ ..] => {
+                matched |= *a == c;
+                pattern = rest;
+            }
+        }
+    }
+    (matched != negate, pattern)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::glob_match;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match(b"*", b"anything"));
+        assert!(glob_match(b"h?llo", b"hello"));
+        assert!(!glob_match(b"h?llo", b"hllo"));
+        assert!(glob_match(b"h*llo", b"heeeello"));
+        assert!(glob_match(b"h[ae]llo", b"hallo"));
+        assert!(!glob_match(b"h[ae]llo", b"hillo"));
+        assert!(glob_match(b"h[^e]llo", b"hallo"));
+        assert!(!glob_match(b"h[^e]llo", b"hello"));
+        assert!(glob_match(b"h[a-c]llo", b"hbllo"));
+        assert!(glob_match(b"user:*:name", b"user:42:name"));
+        assert!(!glob_match(b"user:*:name", b"user:42:age"));
+        assert!(glob_match(b"h\\*llo", b"h*llo"));
+        assert!(!glob_match(b"h\\*llo", b"hello"));
+    }
+}
@@ -1,15 +1,68 @@
-use crate::{RespArray, RespFrame, RespNull};
-use dashmap::{DashMap, DashSet};
-use std::{ops::Deref, sync::Arc};
+mod acl;
+mod config;
+mod expire;
+mod glob;
+mod snapshot;
+mod stats;
+mod zset;
+
+use crate::{BulkString, RespArray, RespFrame, RespNullBulkString};
+use dashmap::{
+    DashMap, DashSet,
+    mapref::{entry::Entry, one::RefMut},
+};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    convert::Infallible,
+    hash::{BuildHasher, DefaultHasher, Hash, Hasher, RandomState},
+    ops::Deref,
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::Notify;
+
+pub use acl::AclUser;
+pub use config::BackendConfig;
+pub(crate) use glob::glob_match;
+pub use stats::ServerStats;
+pub use zset::{LexBound, ZSet};
+
+/// Which end of a list a push or pop works on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListEnd {
+    Left,
+    Right,
+}
 
 #[derive(Debug, Clone)]
 pub struct Backend(Arc<BackendInner>);
 
 #[derive(Debug)]
 pub struct BackendInner {
-    pub(crate) map: DashMap<String, RespFrame>,
-    pub(crate) hmap: DashMap<String, DashMap<String, RespFrame>>,
-    pub(crate) set: DashMap<String, DashSet<String>>,
+    pub(crate) map: DashMap<Vec<u8>, RespFrame>,
+    pub(crate) hmap: DashMap<Vec<u8>, DashMap<String, RespFrame>>,
+    pub(crate) set: DashMap<Vec<u8>, DashSet<String>>,
+    pub(crate) zset: DashMap<Vec<u8>, ZSet>,
+    pub(crate) list: DashMap<Vec<u8>, VecDeque<Vec<u8>>>,
+    // deadlines of whole keys whatever their type, see EXPIRE
+    pub(crate) expires: DashMap<Vec<u8>, Instant>,
+    // per-field deadlines of hash fields, see HEXPIRE
+    pub(crate) hexpires: DashMap<Vec<u8>, DashMap<String, Instant>>,
+    pub(crate) config: RwLock<BackendConfig>,
+    pub(crate) stats: ServerStats,
+    // clients blocked on a key, woken up by the writes that create it
+    pub(crate) key_waiters: DashMap<Vec<u8>, Arc<Notify>>,
+    // LRANGE reads under the shared side and LMOVE moves under the exclusive one, so an
+    // element on its way between two lists is never seen missing from both
+    pub(crate) list_move: RwLock<()>,
+    // mixed into the key hash that orders SCAN, see `Backend::with_hash_seed`
+    pub(crate) hash_seed: u64,
+    // bytes held by keys, values and TTLs, kept up to date by every write so the maxmemory
+    // check doesn't walk the keyspace; see `Backend::used_memory`
+    pub(crate) used_memory: AtomicUsize,
 }
 
 impl Deref for Backend {
@@ -22,7 +75,7 @@ impl Deref for Backend {
 
 impl Default for Backend {
     fn default() -> Self {
-        Self(Arc::new(BackendInner::default()))
+        Self::from_inner(BackendInner::default())
     }
 }
 
@@ -32,6 +85,16 @@ impl Default for BackendInner {
             map: DashMap::new(),
             hmap: DashMap::new(),
             set: DashMap::new(),
+            zset: DashMap::new(),
+            list: DashMap::new(),
+            expires: DashMap::new(),
+            hexpires: DashMap::new(),
+            config: RwLock::new(BackendConfig::default()),
+            stats: ServerStats::default(),
+            key_waiters: DashMap::new(),
+            list_move: RwLock::new(()),
+            hash_seed: RandomState::new().build_hasher().finish(),
+            used_memory: AtomicUsize::new(0),
         }
     }
 }
@@ -42,41 +105,1290 @@ impl Backend {
         Self::default()
     }
 
-    pub fn get(&self, key: &str) -> Option<RespFrame> {
+    /// A backend whose SCAN order is fixed by `seed` instead of picked at random.
+    ///
+    /// The default seed is random so clients can't choose key names that all hash next to
+    /// each other and make every SCAN call walk a long run of colliding keys. Pinning it
+    /// gives up that protection in exchange for a traversal order that is the same from
+    /// one run to the next, which is what tests comparing SCAN output want.
+    pub fn with_hash_seed(seed: u64) -> Self {
+        Self::from_inner(BackendInner {
+            hash_seed: seed,
+            ..Default::default()
+        })
+    }
+
+    pub fn with_config(config: BackendConfig) -> Self {
+        Self::from_inner(BackendInner {
+            config: RwLock::new(config),
+            ..Default::default()
+        })
+    }
+
+    fn from_inner(inner: BackendInner) -> Self {
+        let backend = Self(Arc::new(inner));
+        backend.spawn_reaper();
+        backend
+    }
+
+    pub fn config(&self) -> BackendConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    pub fn set_config(&self, name: &str, value: &str) -> Result<(), String> {
+        self.config.write().unwrap().set(name, value)
+    }
+
+    pub fn can_run(&self, command: &str) -> bool {
+        self.config.read().unwrap().default_user.can_run(command)
+    }
+
+    pub fn stats(&self) -> &ServerStats {
+        &self.stats
+    }
+
+    pub fn get(&self, key: &[u8]) -> Option<RespFrame> {
+        self.expire_if_due(key);
         self.map.get(key).map(|v| v.value().clone())
     }
 
-    pub fn set(&self, key: String, value: RespFrame) {
-        self.map.insert(key, value);
+    // the string value as raw bytes, integers in their decimal form
+    pub fn get_bytes(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.get(key).map(|v| string_bytes(&v))
+    }
+
+    // the length of the string value, an int encoded value is formatted only to be measured
+    // and stays an integer
+    pub fn strlen(&self, key: &[u8]) -> usize {
+        self.expire_if_due(key);
+        self.map.get(key).map_or(0, |v| match v.value() {
+            RespFrame::Integer(i) => i.to_string().len(),
+            value => frame_len(value),
+        })
+    }
+
+    // SET overwrites whatever type the key held, so a stale hash or set can't shadow it,
+    // and drops its TTL
+    pub fn set(&self, key: Vec<u8>, value: RespFrame) {
+        self.set_with_deadline(key, value, None);
+    }
+
+    // the deadline is in place before the value, a reader never sees the key without it
+    pub fn set_with_deadline(&self, key: Vec<u8>, value: RespFrame, deadline: Option<Instant>) {
+        match deadline {
+            Some(deadline) => self.insert_deadline(key.clone(), deadline),
+            None => {
+                self.remove_deadline(&key);
+            }
+        }
+        self.remove_collections(&key);
+        self.store_string(key, value);
+    }
+
+    /// SET NX or XX: stores the value only if whether the key exists matches `exists`, and
+    /// returns true if it did. The check and the store happen under the lock of the string
+    /// entry, so two clients racing to create the same key can't both succeed.
+    pub fn set_if(
+        &self,
+        key: Vec<u8>,
+        value: RespFrame,
+        deadline: Option<Instant>,
+        exists: bool,
+    ) -> bool {
+        self.expire_if_due(&key);
+        let entry = self.map.entry(key);
+        let found = matches!(entry, Entry::Occupied(_)) || self.is_collection(entry.key());
+        if found != exists {
+            return false;
+        }
+        match deadline {
+            Some(deadline) => self.insert_deadline(entry.key().clone(), deadline),
+            None => {
+                self.remove_deadline(entry.key());
+            }
+        }
+        self.remove_collections(entry.key());
+        self.store_entry(entry, value);
+        true
+    }
+
+    // inserts the string and returns the value it replaced
+    fn store_string(&self, key: Vec<u8>, value: RespFrame) -> Option<RespFrame> {
+        self.store_entry(self.map.entry(key), value)
+    }
+
+    fn store_entry(
+        &self,
+        entry: Entry<'_, Vec<u8>, RespFrame>,
+        value: RespFrame,
+    ) -> Option<RespFrame> {
+        let value = detached(value);
+        let size = string_memory(&value);
+        let old = match entry {
+            Entry::Occupied(mut entry) => {
+                let old = entry.insert(value);
+                self.notify_key(entry.key());
+                Some(old)
+            }
+            Entry::Vacant(entry) => {
+                self.grow_memory(entry.key().len());
+                let entry = entry.insert(value);
+                self.notify_key(entry.key());
+                None
+            }
+        };
+        self.grow_memory(size);
+        if let Some(old) = &old {
+            self.shrink_memory(string_memory(old));
+        }
+        old
+    }
+
+    /// Read-modify-write a string while holding the lock of its shard, so two updates of
+    /// the same key can't interleave and lose a write. `f` gets the current value and
+    /// returns the value to store plus what the caller wants back; an error leaves the key
+    /// untouched. `f` must not call back into the backend, the shard is locked meanwhile.
+    pub fn update_string<T, E>(
+        &self,
+        key: Vec<u8>,
+        f: impl FnOnce(Option<&RespFrame>) -> Result<(RespFrame, T), E>,
+    ) -> Result<T, E> {
+        let ret = match self.map.entry(key) {
+            Entry::Occupied(mut entry) => {
+                let (value, ret) = f(Some(entry.get()))?;
+                self.grow_memory(string_memory(&value));
+                let old = entry.insert(value);
+                self.shrink_memory(string_memory(&old));
+                self.notify_key(entry.key());
+                ret
+            }
+            Entry::Vacant(entry) => {
+                let (value, ret) = f(None)?;
+                self.grow_memory(entry.key().len() + string_memory(&value));
+                let entry = entry.insert(value);
+                self.notify_key(entry.key());
+                ret
+            }
+        };
+        Ok(ret)
+    }
+
+    // overwrites part of a string starting at `offset`, zero padding it when needed, and
+    // returns the new length
+    pub fn setrange(&self, key: Vec<u8>, offset: usize, value: &[u8]) -> usize {
+        if value.is_empty() {
+            return self.get(&key).map_or(0, |v| string_bytes(&v).len());
+        }
+        let ret: Result<usize, Infallible> = self.update_string(key, |current| {
+            let mut bytes = current.map(string_bytes).unwrap_or_default();
+            let end = offset + value.len();
+            if bytes.len() < end {
+                bytes.resize(end, 0);
+            }
+            bytes[offset..end].copy_from_slice(value);
+            let len = bytes.len();
+            Ok((BulkString::new(bytes).into(), len))
+        });
+        ret.unwrap_or_else(|e| match e {})
+    }
+
+    // appends to the string, creating it when missing, and returns the new length; None
+    // when that would be longer than `max_len`, checked under the entry lock so concurrent
+    // appends can't go past it together
+    pub fn append(&self, key: Vec<u8>, value: &[u8], max_len: usize) -> Option<usize> {
+        self.expire_if_due(&key);
+        self.update_string(key, |current| {
+            let mut bytes = current.map(string_bytes).unwrap_or_default();
+            if bytes.len().saturating_add(value.len()) > max_len {
+                return Err(());
+            }
+            bytes.extend_from_slice(value);
+            let len = bytes.len();
+            Ok((BulkString::new(bytes).into(), len))
+        })
+        .ok()
+    }
+
+    // SET that hands back the value it replaced, the swap is a single insert under the entry
+    // lock so no other write lands between the read and the store; the TTL is dropped
+    pub fn getset(&self, key: Vec<u8>, value: RespFrame) -> Option<RespFrame> {
+        self.expire_if_due(&key);
+        self.remove_deadline(&key);
+        self.store_string(key, value)
+    }
+
+    // the value is whatever the removal took out, two GETDELs can't both see it
+    pub fn getdel(&self, key: &[u8]) -> Option<RespFrame> {
+        self.expire_if_due(key);
+        let (key, value) = self.map.remove(key)?;
+        self.shrink_memory(key.len() + string_memory(&value));
+        self.remove_deadline(&key);
+        Some(value)
     }
 
-    pub fn hget(&self, key: &str, field: &str) -> Option<RespFrame> {
+    // bits are numbered from the most significant bit of the first byte, like redis
+    pub fn getbit(&self, key: &[u8], offset: usize) -> u8 {
+        self.get(key).map_or(0, |v| {
+            let bytes = string_bytes(&v);
+            bytes
+                .get(offset / 8)
+                .map_or(0, |b| (b >> (7 - offset % 8)) & 1)
+        })
+    }
+
+    // returns the previous value of the bit
+    pub fn setbit(&self, key: Vec<u8>, offset: usize, on: bool) -> u8 {
+        let ret: Result<u8, Infallible> = self.update_string(key, |current| {
+            let mut bytes = current.map(string_bytes).unwrap_or_default();
+            let (byte, mask) = (offset / 8, 1 << (7 - offset % 8));
+            if bytes.len() <= byte {
+                bytes.resize(byte + 1, 0);
+            }
+            let old = u8::from(bytes[byte] & mask != 0);
+            if on {
+                bytes[byte] |= mask;
+            } else {
+                bytes[byte] &= !mask;
+            }
+            Ok((BulkString::new(bytes).into(), old))
+        });
+        ret.unwrap_or_else(|e| match e {})
+    }
+
+    pub fn hget(&self, key: &[u8], field: &str) -> Option<RespFrame> {
+        self.expire_if_due(key);
+        if self.hash_field_expired(key, field) {
+            self.remove_hash_field(key, field);
+            return None;
+        }
         self.hmap
             .get(key)
             .and_then(|v| v.get(field).map(|v| v.value().clone()))
     }
 
-    pub fn hmget(&self, key: &str, field: Vec<String>) -> RespFrame {
+    pub fn hmget(&self, key: &[u8], field: Vec<String>) -> RespFrame {
         let data = field
             .into_iter()
-            .map(|f| self.hget(key, &f).unwrap_or(RespFrame::Null(RespNull)))
+            .map(|f| {
+                self.hget(key, &f)
+                    .unwrap_or(RespFrame::NullBulkString(RespNullBulkString))
+            })
             .collect::<Vec<RespFrame>>();
         RespArray::new(data).into()
     }
 
-    pub fn hset(&self, key: String, field: String, value: RespFrame) {
-        let hmap = self.hmap.entry(key).or_default();
-        hmap.insert(field, value);
+    // returns true if the field is new, a field that already expired counts as new
+    pub fn hset(&self, key: Vec<u8>, field: String, value: RespFrame) -> bool {
+        // overwriting a field drops its TTL, like redis does
+        let expired = self.hash_field_expired(&key, &field);
+        self.remove_field_deadline(&key, &field);
+        let value = detached(value);
+        let hmap = self.collection_entry(&self.hmap, key);
+        let field_len = field.len();
+        self.grow_memory(field_len + value.byte_len());
+        let old = hmap.insert(field, value);
+        if let Some(old) = &old {
+            self.shrink_memory(field_len + old.byte_len());
+        }
+        self.notify_key(hmap.key());
+        old.is_none() || expired
+    }
+
+    /// Read-modify-write a hash field while holding the locks of the hash and of the field,
+    /// the hash counterpart of [`Backend::update_string`]. An expired field is passed as
+    /// missing, a field that is overwritten keeps its TTL; an error leaves the hash untouched.
+    pub fn update_hash_field<T, E>(
+        &self,
+        key: &[u8],
+        field: String,
+        f: impl FnOnce(Option<&RespFrame>) -> Result<(RespFrame, T), E>,
+    ) -> Result<T, E> {
+        self.expire_if_due(key);
+        if self.hash_field_expired(key, &field) {
+            self.remove_hash_field(key, &field);
+        }
+        let hmap = self.collection_entry(&self.hmap, key.to_vec());
+        let ret = match hmap.entry(field) {
+            Entry::Occupied(mut entry) => f(Some(entry.get())).map(|(value, ret)| {
+                self.grow_memory(value.byte_len());
+                let old = entry.insert(value);
+                self.shrink_memory(old.byte_len());
+                ret
+            }),
+            Entry::Vacant(entry) => f(None).map(|(value, ret)| {
+                self.grow_memory(entry.key().len() + value.byte_len());
+                entry.insert(value);
+                ret
+            }),
+        };
+        if ret.is_ok() {
+            self.notify_key(hmap.key());
+        }
+        drop(hmap);
+        // a failed update of a missing key mustn't leave an empty hash behind
+        self.remove_empty(&self.hmap, key, DashMap::is_empty);
+        ret
+    }
+
+    // returns true if the field was there, one that already expired doesn't count; removing
+    // the last field removes the key, like redis does, so no empty hash is left behind
+    pub fn hdel(&self, key: &[u8], field: &str) -> bool {
+        self.expire_if_due(key);
+        let expired = self.hash_field_expired(key, field);
+        self.remove_hash_field(key, field) && !expired
+    }
+
+    // returns -2 if the field doesn't exist, 2 if it was deleted right away because the
+    // TTL is zero and 1 if the TTL was set; None if the deadline is too far away to represent
+    pub fn hexpire(&self, key: &[u8], field: &str, ttl: Duration) -> Option<i64> {
+        if self.hget(key, field).is_none() {
+            return Some(-2);
+        }
+        if ttl.is_zero() {
+            self.remove_hash_field(key, field);
+            return Some(2);
+        }
+        let deadline = Instant::now().checked_add(ttl)?;
+        let fields = self.hexpires.entry(key.to_vec()).or_default();
+        if fields.insert(field.to_string(), deadline).is_none() {
+            self.grow_memory(field.len() + size_of::<Instant>());
+        }
+        Some(1)
+    }
+
+    // remaining seconds of a field TTL, -1 if the field has no TTL and -2 if it doesn't exist
+    pub fn httl(&self, key: &[u8], field: &str) -> i64 {
+        if self.hget(key, field).is_none() {
+            return -2;
+        }
+        self.hexpires
+            .get(key)
+            .and_then(|v| v.get(field).map(|deadline| *deadline))
+            .map_or(-1, |deadline| {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                (remaining.as_millis() as i64 + 500) / 1000
+            })
+    }
+
+    // returns 1 if the TTL was removed, -1 if the field has no TTL and -2 if it doesn't exist
+    pub fn hpersist(&self, key: &[u8], field: &str) -> i64 {
+        if self.hget(key, field).is_none() {
+            return -2;
+        }
+        if self.remove_field_deadline(key, field) {
+            1
+        } else {
+            -1
+        }
+    }
+
+    // drop every expired field of a hash, used before reading the whole hash, or the whole
+    // key when it is past its deadline
+    pub fn purge_expired_fields(&self, key: &[u8]) {
+        if self.expire_if_due(key) {
+            return;
+        }
+        let expired = match self.hexpires.get(key) {
+            Some(fields) => {
+                let now = Instant::now();
+                fields
+                    .iter()
+                    .filter(|v| *v.value() <= now)
+                    .map(|v| v.key().clone())
+                    .collect::<Vec<_>>()
+            }
+            None => return,
+        };
+        for field in expired {
+            self.remove_hash_field(key, &field);
+        }
+    }
+
+    fn hash_field_expired(&self, key: &[u8], field: &str) -> bool {
+        self.hexpires
+            .get(key)
+            .and_then(|v| v.get(field).map(|deadline| *deadline <= Instant::now()))
+            .unwrap_or(false)
+    }
+
+    fn remove_hash_field(&self, key: &[u8], field: &str) -> bool {
+        self.remove_field_deadline(key, field);
+        let removed = self.hmap.get(key).and_then(|v| v.remove(field));
+        if let Some((field, value)) = &removed {
+            self.shrink_memory(field.len() + value.byte_len());
+        }
+        // the key goes with its last field, a TTL left behind would apply to a later key
+        if self.remove_empty(&self.hmap, key, DashMap::is_empty) {
+            self.remove_deadline(key);
+        }
+        removed.is_some()
+    }
+
+    // returns true if the field had a TTL
+    fn remove_field_deadline(&self, key: &[u8], field: &str) -> bool {
+        let removed = self.hexpires.get(key).and_then(|v| v.remove(field));
+        if let Some((field, _)) = &removed {
+            self.shrink_memory(field.len() + size_of::<Instant>());
+        }
+        self.hexpires.remove_if(key, |_, v| v.is_empty());
+        removed.is_some()
+    }
+
+    pub fn add_member(&self, key: Vec<u8>, member: String) -> bool {
+        let members = self.collection_entry(&self.set, key);
+        let size = member.len();
+        let added = members.insert(member);
+        if added {
+            self.grow_memory(size);
+        }
+        self.notify_key(members.key());
+        added
+    }
+
+    // returns true if the member was there, removing the last one removes the key and its TTL
+    pub fn srem(&self, key: &[u8], member: &str) -> bool {
+        self.expire_if_due(key);
+        let removed = self.set.get(key).and_then(|v| v.remove(member));
+        if let Some(member) = &removed {
+            self.shrink_memory(member.len());
+        }
+        if self.remove_empty(&self.set, key, DashSet::is_empty) {
+            self.remove_deadline(key);
+        }
+        removed.is_some()
     }
 
-    pub fn add_member(&self, key: String, member: String) {
-        self.set.entry(key).or_default().insert(member);
+    pub fn scard(&self, key: &[u8]) -> usize {
+        self.expire_if_due(key);
+        self.set.get(key).map_or(0, |v| v.len())
     }
 
-    pub fn sis_member(&self, key: String, member: String) -> RespFrame {
+    pub fn sis_member(&self, key: Vec<u8>, member: String) -> RespFrame {
+        self.expire_if_due(&key);
         self.set
             .get(&key)
-            .map_or(0, |v| (if v.contains(&member) { 1 } else { 0 }))
+            .map_or(0, |v| if v.contains(&member) { 1 } else { 0 })
             .into()
     }
+
+    // removes the key whatever its type, along with its TTLs so nothing is left behind
+    // for used_memory to count; a key past its deadline no longer counts as removed
+    pub fn del(&self, key: &[u8]) -> bool {
+        !self.expire_if_due(key) && self.remove(key)
+    }
+
+    fn remove(&self, key: &[u8]) -> bool {
+        self.remove_deadline(key);
+        let string = self.map.remove(key);
+        if let Some((key, value)) = &string {
+            self.shrink_memory(key.len() + string_memory(value));
+        }
+        let collection = self.remove_collections(key);
+        string.is_some() || collection
+    }
+
+    fn is_collection(&self, key: &[u8]) -> bool {
+        self.hmap.contains_key(key)
+            || self.set.contains_key(key)
+            || self.zset.contains_key(key)
+            || self.list.contains_key(key)
+    }
+
+    // removes the key from every map but the strings, a hash along with its field TTLs;
+    // returns true if it was there
+    fn remove_collections(&self, key: &[u8]) -> bool {
+        if let Some((_, fields)) = self.hexpires.remove(key) {
+            self.shrink_memory(field_deadlines_memory(&fields));
+        }
+        let removed = [
+            self.hmap
+                .remove(key)
+                .map(|(k, v)| k.len() + hash_memory(&v)),
+            self.set.remove(key).map(|(k, v)| k.len() + set_memory(&v)),
+            self.zset
+                .remove(key)
+                .map(|(k, v)| k.len() + zset_memory(&v)),
+            self.list
+                .remove(key)
+                .map(|(k, v)| k.len() + list_memory(&v)),
+        ];
+        removed
+            .iter()
+            .flatten()
+            .for_each(|size| self.shrink_memory(*size));
+        removed.iter().any(Option::is_some)
+    }
+
+    // the collection stored at `key`, created empty when missing
+    fn collection_entry<'a, V: Default>(
+        &self,
+        map: &'a DashMap<Vec<u8>, V>,
+        key: Vec<u8>,
+    ) -> RefMut<'a, Vec<u8>, V> {
+        match map.entry(key) {
+            Entry::Occupied(entry) => entry.into_ref(),
+            Entry::Vacant(entry) => {
+                self.grow_memory(entry.key().len());
+                entry.insert(V::default())
+            }
+        }
+    }
+
+    // drops the collection stored at `key` once it has nothing left in it, returns true if
+    // it did
+    fn remove_empty<V>(
+        &self,
+        map: &DashMap<Vec<u8>, V>,
+        key: &[u8],
+        is_empty: impl Fn(&V) -> bool,
+    ) -> bool {
+        match map.remove_if(key, |_, v| is_empty(v)) {
+            Some((key, _)) => {
+                self.shrink_memory(key.len());
+                true
+            }
+            None => false,
+        }
+    }
+
+    // size of the intersection of the sets, stopping once `limit` common members are found
+    // (0 for no limit); a missing key is an empty set
+    pub fn sintercard(&self, keys: &[Vec<u8>], limit: usize) -> usize {
+        for key in keys {
+            self.expire_if_due(key);
+        }
+        let Some(smallest) = keys
+            .iter()
+            .map(|k| (k, self.set.get(k).map_or(0, |s| s.len())))
+            .min_by_key(|(_, len)| *len)
+            .map(|(k, _)| k)
+        else {
+            return 0;
+        };
+        // copied out so membership checks never hold two shard locks at once
+        let candidates = match self.set.get(smallest) {
+            Some(set) => set.iter().map(|m| m.key().clone()).collect::<Vec<_>>(),
+            None => return 0,
+        };
+        let mut count = 0;
+        for member in candidates {
+            let in_all = keys
+                .iter()
+                .filter(|k| *k != smallest)
+                .all(|k| self.set.get(k).is_some_and(|s| s.contains(&member)));
+            if in_all {
+                count += 1;
+                if count == limit {
+                    break;
+                }
+            }
+        }
+        count
+    }
+
+    // returns how many members were new, the others only get their score updated
+    pub fn zadd(&self, key: Vec<u8>, members: Vec<(f64, String)>) -> usize {
+        let mut zset = self.collection_entry(&self.zset, key);
+        let added = members
+            .into_iter()
+            .filter(|(score, member)| zset.insert(member.clone(), *score))
+            .inspect(|(_, member)| self.grow_memory(member.len() + size_of::<f64>()))
+            .count();
+        self.notify_key(zset.key());
+        added
+    }
+
+    // removes up to `count` members with the lowest scores, or the highest ones with
+    // `max`, in the order they are popped; the key is dropped once the set is empty
+    pub fn zpop(&self, key: &[u8], count: usize, max: bool) -> Vec<(String, f64)> {
+        let popped = {
+            let Some(mut zset) = self.zset.get_mut(key) else {
+                return Vec::new();
+            };
+            std::iter::from_fn(|| if max { zset.pop_max() } else { zset.pop_min() })
+                .take(count)
+                .inspect(|(member, _)| self.shrink_memory(member.len() + size_of::<f64>()))
+                .collect()
+        };
+        self.remove_empty(&self.zset, key, ZSet::is_empty);
+        popped
+    }
+
+    // members between the bounds in lexical order, skipping `offset` and returning at most
+    // `count` of them when given
+    pub fn zrange_by_lex(
+        &self,
+        key: &[u8],
+        min: &LexBound,
+        max: &LexBound,
+        offset: usize,
+        count: Option<usize>,
+    ) -> Vec<String> {
+        let Some(zset) = self.zset.get(key) else {
+            return Vec::new();
+        };
+        zset.range_by_lex(min, max)
+            .skip(offset)
+            .take(count.unwrap_or(usize::MAX))
+            .map(String::from)
+            .collect()
+    }
+
+    // pushes the values one by one, so LPUSH a b c leaves c at the head; returns the new
+    // length of the list
+    pub fn push(&self, key: Vec<u8>, values: Vec<Vec<u8>>, end: ListEnd) -> usize {
+        let mut list = self.collection_entry(&self.list, key);
+        self.grow_memory(values.iter().map(Vec::len).sum());
+        for value in values {
+            match end {
+                ListEnd::Left => list.push_front(value),
+                ListEnd::Right => list.push_back(value),
+            }
+        }
+        let len = list.len();
+        self.notify_key(list.key());
+        len
+    }
+
+    // removes up to `count` elements from one end, None if the key doesn't exist; the list
+    // is dropped once it is empty
+    pub fn pop(&self, key: &[u8], end: ListEnd, count: usize) -> Option<Vec<Vec<u8>>> {
+        let popped = {
+            let mut list = self.list.get_mut(key)?;
+            let count = count.min(list.len());
+            let popped: Vec<_> = match end {
+                ListEnd::Left => list.drain(..count).collect(),
+                ListEnd::Right => {
+                    let at = list.len() - count;
+                    list.drain(at..).rev().collect()
+                }
+            };
+            self.shrink_memory(popped.iter().map(Vec::len).sum());
+            popped
+        };
+        self.remove_empty(&self.list, key, VecDeque::is_empty);
+        Some(popped)
+    }
+
+    // pops from one end of `src` and pushes the element to one end of `dst`, None if `src`
+    // doesn't exist; the same key for both rotates the list
+    pub fn lmove(&self, src: &[u8], dst: &[u8], from: ListEnd, to: ListEnd) -> Option<Vec<u8>> {
+        let _moves = self.list_move.write().unwrap();
+        let element = {
+            let mut list = self.list.get_mut(src)?;
+            let element = match from {
+                ListEnd::Left => list.pop_front()?,
+                ListEnd::Right => list.pop_back()?,
+            };
+            if src == dst {
+                match to {
+                    ListEnd::Left => list.push_front(element.clone()),
+                    ListEnd::Right => list.push_back(element.clone()),
+                }
+                return Some(element);
+            }
+            self.shrink_memory(element.len());
+            element
+        };
+        self.remove_empty(&self.list, src, VecDeque::is_empty);
+        self.push(dst.to_vec(), vec![element.clone()], to);
+        Some(element)
+    }
+
+    // replaces the element at `index`, negative indexes count from the tail; None if the
+    // key doesn't exist and false if the index is out of range
+    pub fn lset(&self, key: &[u8], index: i64, value: Vec<u8>) -> Option<bool> {
+        let mut list = self.list.get_mut(key)?;
+        let index = if index < 0 {
+            index + list.len() as i64
+        } else {
+            index
+        };
+        let Some(element) = usize::try_from(index).ok().and_then(|i| list.get_mut(i)) else {
+            return Some(false);
+        };
+        self.grow_memory(value.len());
+        let old = std::mem::replace(element, value);
+        self.shrink_memory(old.len());
+        self.notify_key(list.key());
+        Some(true)
+    }
+
+    // inserts next to the first element equal to `pivot` and returns the new length, -1 if
+    // the pivot isn't there and 0 if the key doesn't exist
+    pub fn linsert(&self, key: &[u8], after: bool, pivot: &[u8], value: Vec<u8>) -> i64 {
+        let Some(mut list) = self.list.get_mut(key) else {
+            return 0;
+        };
+        let Some(at) = list.iter().position(|e| e == pivot) else {
+            return -1;
+        };
+        self.grow_memory(value.len());
+        list.insert(if after { at + 1 } else { at }, value);
+        let len = list.len() as i64;
+        self.notify_key(list.key());
+        len
+    }
+
+    // elements from `start` to `stop` inclusive, negative indexes count from the tail
+    pub fn lrange(&self, key: &[u8], start: i64, stop: i64) -> Vec<Vec<u8>> {
+        let _moves = self.list_move.read().unwrap();
+        let Some(list) = self.list.get(key) else {
+            return Vec::new();
+        };
+        let len = list.len() as i64;
+        let start = if start < 0 {
+            (len + start).max(0)
+        } else {
+            start
+        };
+        let stop = if stop < 0 {
+            len + stop
+        } else {
+            stop.min(len - 1)
+        };
+        if start > stop {
+            return Vec::new();
+        }
+        list.range(start as usize..=stop as usize)
+            .cloned()
+            .collect()
+    }
+
+    pub fn flush(&self) {
+        self.map.clear();
+        self.hmap.clear();
+        self.set.clear();
+        self.zset.clear();
+        self.list.clear();
+        self.expires.clear();
+        self.hexpires.clear();
+        self.used_memory.store(0, Ordering::Relaxed);
+    }
+
+    // waits until the key exists or the timeout elapses, no timeout waits forever
+    pub async fn wait_for_key(&self, key: &[u8], timeout: Option<Duration>) -> bool {
+        let notify = self.key_waiters.entry(key.to_vec()).or_default().clone();
+        let found = {
+            let notified = notify.notified();
+            tokio::pin!(notified);
+            // registered before checking, so a write landing in between isn't missed
+            notified.as_mut().enable();
+            if self.type_of(key).is_some() {
+                true
+            } else {
+                match timeout {
+                    Some(timeout) => tokio::time::timeout(timeout, notified).await.is_ok(),
+                    None => {
+                        notified.await;
+                        true
+                    }
+                }
+            }
+        };
+        drop(notify);
+        self.key_waiters
+            .remove_if(key, |_, v| Arc::strong_count(v) == 1);
+        found
+    }
+
+    // called once the write is visible, a waiter registers before it checks the key so
+    // either it sees the value or it is woken here
+    fn notify_key(&self, key: &[u8]) {
+        if let Some(notify) = self.key_waiters.get(key) {
+            notify.notify_waiters();
+        }
+    }
+
+    /// Bytes held by keys, values and TTLs, as counted by the writes so far.
+    pub fn used_memory(&self) -> usize {
+        self.used_memory.load(Ordering::Relaxed)
+    }
+
+    // every write counts what it adds before it is visible and what it removes once it is
+    // gone, so a concurrent removal never takes away bytes that weren't counted yet
+    fn grow_memory(&self, bytes: usize) {
+        self.used_memory.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    // saturating, so a FLUSHALL racing with a write can't wrap the counter around
+    fn shrink_memory(&self, bytes: usize) {
+        let _ = self
+            .used_memory
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                Some(used.saturating_sub(bytes))
+            });
+    }
+
+    // what used_memory counts, walked over the whole keyspace; only for loading a snapshot,
+    // which fills the maps directly
+    fn measure_memory(&self) -> usize {
+        let strings: usize = self
+            .map
+            .iter()
+            .map(|v| v.key().len() + string_memory(v.value()))
+            .sum();
+        let hashes: usize = self
+            .hmap
+            .iter()
+            .map(|v| v.key().len() + hash_memory(v.value()))
+            .sum();
+        let sets: usize = self
+            .set
+            .iter()
+            .map(|v| v.key().len() + set_memory(v.value()))
+            .sum();
+        let zsets: usize = self
+            .zset
+            .iter()
+            .map(|v| v.key().len() + zset_memory(v.value()))
+            .sum();
+        let lists: usize = self
+            .list
+            .iter()
+            .map(|v| v.key().len() + list_memory(v.value()))
+            .sum();
+        let ttls: usize = self
+            .hexpires
+            .iter()
+            .map(|v| field_deadlines_memory(v.value()))
+            .sum();
+        let key_ttls: usize = self
+            .expires
+            .iter()
+            .map(|v| v.key().len() + size_of::<Instant>())
+            .sum();
+        strings + hashes + sets + zsets + lists + ttls + key_ttls
+    }
+
+    /// Estimated bytes held by a single key and its value, see MEMORY USAGE.
+    pub fn memory_usage(&self, key: &[u8]) -> Option<usize> {
+        self.expire_if_due(key);
+        let value = if let Some(value) = self.map.get(key) {
+            string_memory(value.value())
+        } else if let Some(hmap) = self.hmap.get(key) {
+            hash_memory(hmap.value())
+        } else if let Some(set) = self.set.get(key) {
+            set_memory(set.value())
+        } else if let Some(zset) = self.zset.get(key) {
+            zset_memory(zset.value())
+        } else if let Some(list) = self.list.get(key) {
+            list_memory(list.value())
+        } else {
+            return None;
+        };
+        Some(key.len() + value)
+    }
+
+    // collections are removed as soon as they become empty, so every entry is a live key
+    pub fn dbsize(&self) -> usize {
+        self.map.len() + self.hmap.len() + self.set.len() + self.zset.len() + self.list.len()
+    }
+
+    pub fn exists(&self, key: &[u8]) -> bool {
+        self.type_of(key).is_some()
+    }
+
+    pub fn type_of(&self, key: &[u8]) -> Option<&'static str> {
+        self.expire_if_due(key);
+        if self.map.contains_key(key) {
+            Some("string")
+        } else if self.hmap.contains_key(key) {
+            Some("hash")
+        } else if self.set.contains_key(key) {
+            Some("set")
+        } else if self.zset.contains_key(key) {
+            Some("zset")
+        } else if self.list.contains_key(key) {
+            Some("list")
+        } else {
+            None
+        }
+    }
+
+    // SCAN walks the keyspace ordered by a seeded hash of the key name and the cursor is the
+    // hash to resume from. Inserts and removes never reorder the keys left to visit, so a key
+    // that lives through a whole iteration is returned exactly once.
+    //
+    // Known limitation: nothing indexes the keys by that hash, so every call hashes the whole
+    // keyspace to find the ones after the cursor. A call is O(N) and a full iteration is
+    // O(N^2 / COUNT). The DashMap shards can't be resumed from instead, a resize moves keys
+    // between buckets and a position in them would skip or repeat keys.
+    pub fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<Vec<u8>>) {
+        let count = count.max(1);
+        // keep one hash more than we return, it becomes the next cursor
+        let mut batch: BTreeMap<u64, Vec<Vec<u8>>> = BTreeMap::new();
+        self.for_each_key(|key| {
+            let hash = self.key_hash(key);
+            if hash < cursor {
+                return;
+            }
+            if batch.len() > count && batch.last_key_value().is_some_and(|(h, _)| hash > *h) {
+                return;
+            }
+            batch.entry(hash).or_default().push(key.to_vec());
+            if batch.len() > count + 1 {
+                batch.pop_last();
+            }
+        });
+        let next = if batch.len() > count {
+            batch.pop_last().map_or(0, |(hash, _)| hash)
+        } else {
+            0
+        };
+        (next, batch.into_values().flatten().collect())
+    }
+
+    fn key_hash(&self, key: &[u8]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash_seed.hash(&mut hasher);
+        key.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // owned key names of a single type; each map is walked shard by shard, so keys written
+    // concurrently may or may not show up but the returned names never borrow the maps
+    pub fn string_keys(&self) -> Vec<Vec<u8>> {
+        self.map.iter().map(|v| v.key().clone()).collect()
+    }
+
+    pub fn hash_keys(&self) -> Vec<Vec<u8>> {
+        self.hmap.iter().map(|v| v.key().clone()).collect()
+    }
+
+    pub fn set_keys(&self) -> Vec<Vec<u8>> {
+        self.set.iter().map(|v| v.key().clone()).collect()
+    }
+
+    pub fn zset_keys(&self) -> Vec<Vec<u8>> {
+        self.zset.iter().map(|v| v.key().clone()).collect()
+    }
+
+    pub fn list_keys(&self) -> Vec<Vec<u8>> {
+        self.list.iter().map(|v| v.key().clone()).collect()
+    }
+
+    fn for_each_key(&self, mut f: impl FnMut(&[u8])) {
+        self.map.iter().for_each(|v| f(v.key()));
+        self.hmap.iter().for_each(|v| f(v.key()));
+        self.set.iter().for_each(|v| f(v.key()));
+        self.zset.iter().for_each(|v| f(v.key()));
+        self.list.iter().for_each(|v| f(v.key()));
+    }
+
+    // storage is always a hashmap, but we report the encoding redis would use for the
+    // same data so the listpack thresholds can be reasoned about
+    pub fn object_encoding(&self, key: &[u8]) -> Option<&'static str> {
+        self.expire_if_due(key);
+        let config = self.config();
+        if let Some(value) = self.map.get(key) {
+            return Some(string_encoding(value.value()));
+        }
+        if let Some(hmap) = self.hmap.get(key) {
+            let small = hmap.len() <= config.hash_max_listpack_entries
+                && hmap.iter().all(|v| {
+                    v.key().len() <= config.hash_max_listpack_value
+                        && frame_len(v.value()) <= config.hash_max_listpack_value
+                });
+            return Some(if small { "listpack" } else { "hashtable" });
+        }
+        if let Some(set) = self.set.get(key) {
+            let small = set.len() <= config.set_max_listpack_entries
+                && set.iter().all(|v| v.len() <= config.set_max_listpack_value);
+            return Some(if small { "listpack" } else { "hashtable" });
+        }
+        if let Some(zset) = self.zset.get(key) {
+            let small = zset.len() <= config.zset_max_listpack_entries
+                && zset
+                    .iter()
+                    .all(|(m, _)| m.len() <= config.zset_max_listpack_value);
+            return Some(if small { "listpack" } else { "skiplist" });
+        }
+        if let Some(list) = self.list.get(key) {
+            let small = list_memory(list.value()) <= LIST_MAX_LISTPACK_SIZE;
+            return Some(if small { "listpack" } else { "quicklist" });
+        }
+        None
+    }
+}
+
+// the default list-max-listpack-size of -2, a single listpack node of up to 8kb
+const LIST_MAX_LISTPACK_SIZE: usize = 8 * 1024;
+
+// redis keeps strings up to 44 bytes in a single allocation with the object header
+const EMBSTR_MAX_LEN: usize = 44;
+
+fn string_encoding(value: &RespFrame) -> &'static str {
+    match value {
+        RespFrame::Integer(_) => "int",
+        RespFrame::BulkString(s) => {
+            let is_int = std::str::from_utf8(s).is_ok_and(|s| s.parse::<i64>().is_ok());
+            if is_int {
+                "int"
+            } else if s.len() <= EMBSTR_MAX_LEN {
+                "embstr"
+            } else {
+                "raw"
+            }
+        }
+        _ => "raw",
+    }
+}
+
+// an int encoded string lives in the pointer slot of its object, so it costs the same
+// whatever its decimal length
+const INT_ENCODED_SIZE: usize = size_of::<i64>();
+
+fn string_memory(value: &RespFrame) -> usize {
+    match string_encoding(value) {
+        "int" => INT_ENCODED_SIZE,
+        _ => value.byte_len(),
+    }
+}
+
+fn hash_memory(fields: &DashMap<String, RespFrame>) -> usize {
+    fields
+        .iter()
+        .map(|f| f.key().len() + f.value().byte_len())
+        .sum()
+}
+
+fn set_memory(members: &DashSet<String>) -> usize {
+    members.iter().map(|m| m.len()).sum()
+}
+
+fn zset_memory(zset: &ZSet) -> usize {
+    zset.iter().map(|(m, _)| m.len() + size_of::<f64>()).sum()
+}
+
+fn list_memory(list: &VecDeque<Vec<u8>>) -> usize {
+    list.iter().map(Vec::len).sum()
+}
+
+fn field_deadlines_memory(fields: &DashMap<String, Instant>) -> usize {
+    fields
+        .iter()
+        .map(|f| f.key().len() + size_of::<Instant>())
+        .sum()
+}
+
+// a value as it is stored, owning its bytes instead of sharing a read buffer
+fn detached(value: RespFrame) -> RespFrame {
+    match value {
+        RespFrame::BulkString(s) => s.detach().into(),
+        value => value,
+    }
+}
+
+// the raw bytes of a string value, integers are seen through their decimal form
+fn string_bytes(value: &RespFrame) -> Vec<u8> {
+    match value {
+        RespFrame::BulkString(s) => s.to_vec(),
+        RespFrame::SimpleString(s) => s.as_bytes().to_vec(),
+        RespFrame::Integer(i) => i.to_string().into_bytes(),
+        _ => Vec::new(),
+    }
+}
+
+fn frame_len(value: &RespFrame) -> usize {
+    match value {
+        RespFrame::BulkString(s) => s.len(),
+        RespFrame::SimpleString(s) => s.len(),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_del_frees_used_memory() {
+        let backend = Backend::new();
+        backend.set(b"keep".to_vec(), BulkString::from("value").into());
+        let before = backend.used_memory();
+
+        backend.set(b"s".to_vec(), BulkString::new(vec![b'x'; 100]).into());
+        backend.set(b"n".to_vec(), RespFrame::Integer(42));
+        backend.hset(b"h".to_vec(), "f".to_string(), BulkString::from("v").into());
+        backend.hexpire(b"h", "f", Duration::from_secs(100));
+        backend.add_member(b"m".to_vec(), "a".to_string());
+        assert!(backend.used_memory() > before);
+
+        for key in [b"s", b"n", b"h", b"m"] {
+            assert!(backend.del(key));
+        }
+        assert!(!backend.del(b"s"));
+        assert_eq!(backend.used_memory(), before);
+        assert!(backend.hexpires.is_empty());
+    }
+
+    #[test]
+    fn test_used_memory_counts_every_write() {
+        let backend = Backend::new();
+        let check =
+            |step: &str| assert_eq!(backend.used_memory(), backend.measure_memory(), "{step}");
+        let value = |s: &str| RespFrame::from(BulkString::from(s));
+
+        backend.set(b"s".to_vec(), value("short"));
+        backend.set(b"s".to_vec(), BulkString::new(vec![b'x'; 100]).into());
+        backend.set(b"n".to_vec(), RespFrame::Integer(7));
+        backend.append(b"n".to_vec(), b"77", usize::MAX);
+        backend.setrange(b"r".to_vec(), 10, b"abc");
+        backend.setbit(b"b".to_vec(), 100, true);
+        backend.getset(b"s".to_vec(), value("swapped"));
+        check("strings");
+
+        backend.hset(b"h".to_vec(), "f".to_string(), value("v"));
+        backend.hset(b"h".to_vec(), "f".to_string(), value("longer value"));
+        backend.hset(b"h".to_vec(), "g".to_string(), value("v"));
+        backend.hexpire(b"h", "f", Duration::from_secs(100));
+        backend.hexpire(b"h", "g", Duration::from_secs(100));
+        backend.hpersist(b"h", "g");
+        backend
+            .update_hash_field(b"h", "n".to_string(), |_| {
+                Ok::<_, ()>((RespFrame::Integer(1), ()))
+            })
+            .unwrap();
+        backend
+            .update_hash_field(b"x", "n".to_string(), |_| Err::<(RespFrame, ()), _>(()))
+            .unwrap_err();
+        backend.hdel(b"h", "n");
+        check("hashes");
+
+        backend.add_member(b"m".to_vec(), "a".to_string());
+        backend.add_member(b"m".to_vec(), "a".to_string());
+        backend.add_member(b"m".to_vec(), "b".to_string());
+        backend.srem(b"m", "a");
+        backend.zadd(
+            b"z".to_vec(),
+            vec![(1.0, "a".to_string()), (2.0, "b".to_string())],
+        );
+        backend.zadd(b"z".to_vec(), vec![(3.0, "a".to_string())]);
+        backend.zpop(b"z", 1, false);
+        check("sets");
+
+        backend.push(
+            b"l".to_vec(),
+            vec![b"a".to_vec(), b"bb".to_vec()],
+            ListEnd::Left,
+        );
+        backend.lset(b"l", 0, b"longer".to_vec());
+        backend.linsert(b"l", true, b"a", b"c".to_vec());
+        backend.lmove(b"l", b"l2", ListEnd::Left, ListEnd::Right);
+        backend.pop(b"l", ListEnd::Right, 1);
+        check("lists");
+
+        backend.expire(b"s", 100);
+        backend.expire(b"h", 100);
+        backend.persist(b"s");
+        backend.expire_at(b"m", Instant::now());
+        backend.reap_expired();
+        backend.getdel(b"n");
+        backend.set(b"h".to_vec(), value("replaces the hash"));
+        check("ttls");
+
+        for key in backend.string_keys() {
+            backend.del(&key);
+        }
+        for key in [b"z".as_slice(), b"l", b"l2"] {
+            backend.del(key);
+        }
+        check("del");
+        assert_eq!(backend.used_memory(), 0);
+    }
+
+    #[test]
+    fn test_concurrent_updates_lose_no_writes() {
+        const THREADS: usize = 8;
+        const APPENDS: usize = 500;
+
+        let backend = Backend::new();
+        let handles: Vec<_> = (0..THREADS)
+            .map(|i| {
+                let backend = backend.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..APPENDS {
+                        backend
+                            .update_string(b"log".to_vec(), |value| {
+                                let mut bytes = value.map(string_bytes).unwrap_or_default();
+                                bytes.push(b'a' + i as u8);
+                                Ok::<_, ()>((BulkString::new(bytes).into(), ()))
+                            })
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let bytes = string_bytes(&backend.get(b"log").unwrap());
+        assert_eq!(bytes.len(), THREADS * APPENDS);
+        for i in 0..THREADS {
+            let appended = bytes.iter().filter(|&&b| b == b'a' + i as u8).count();
+            assert_eq!(appended, APPENDS);
+        }
+    }
+
+    #[test]
+    fn test_scan_order_with_fixed_hash_seed() {
+        let scan_all = |backend: &Backend| {
+            let mut cursor = 0;
+            let mut pages = Vec::new();
+            loop {
+                let (next, keys) = backend.scan(cursor, 3);
+                pages.push(keys);
+                if next == 0 {
+                    return pages;
+                }
+                cursor = next;
+            }
+        };
+
+        let a = Backend::with_hash_seed(42);
+        let b = Backend::with_hash_seed(42);
+        for i in 0..20 {
+            a.set(format!("key:{}", i).into_bytes(), RespFrame::Integer(i));
+        }
+        for i in (0..20).rev() {
+            b.set(format!("key:{}", i).into_bytes(), RespFrame::Integer(i));
+        }
+
+        let pages = scan_all(&a);
+        assert_eq!(pages.iter().map(Vec::len).sum::<usize>(), 20);
+        assert_eq!(pages, scan_all(&b));
+    }
+
+    #[test]
+    fn test_keys_by_type() {
+        let backend = Backend::new();
+        backend.set(b"s1".to_vec(), BulkString::from("v").into());
+        backend.set(b"s2".to_vec(), RespFrame::Integer(1));
+        backend.hset(
+            b"h1".to_vec(),
+            "f".to_string(),
+            BulkString::from("v").into(),
+        );
+        backend.add_member(b"m1".to_vec(), "a".to_string());
+        backend.add_member(b"m2".to_vec(), "b".to_string());
+        // overwritten by SET, so it must only show up as a string
+        backend.add_member(b"s3".to_vec(), "c".to_string());
+        backend.set(b"s3".to_vec(), BulkString::from("v").into());
+
+        let sorted = |mut keys: Vec<Vec<u8>>| {
+            keys.sort();
+            keys
+        };
+        assert_eq!(
+            sorted(backend.string_keys()),
+            vec![b"s1".to_vec(), b"s2".to_vec(), b"s3".to_vec()]
+        );
+        assert_eq!(backend.hash_keys(), vec![b"h1".to_vec()]);
+        assert_eq!(
+            sorted(backend.set_keys()),
+            vec![b"m1".to_vec(), b"m2".to_vec()]
+        );
+    }
 }
@@ -0,0 +1,319 @@
+use std::{
+    fs,
+    path::Path,
+    sync::atomic::Ordering,
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Result, anyhow};
+use bytes::BytesMut;
+use futures::{Stream, StreamExt};
+#[cfg(unix)]
+use tokio::signal::unix::{self, SignalKind};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use super::{Backend, detached};
+use crate::{BulkString, RespArray, RespDecode, RespEncode, RespFrame};
+
+// A snapshot is a plain sequence of RESP arrays, one per record:
+//   ["string", key, value]
+//   ["hash", key, field, value, field, value, ...]
+//   ["hexpire", key, field, unix-ms deadline]
+//   ["set", key, member, ...]
+//   ["zset", key, member, score, member, score, ...]
+//   ["list", key, element, ...] from head to tail
+//   ["expire", key, unix-ms deadline], after the record of the key
+// Deadlines are stored as wall clock time since an Instant can't outlive the process.
+impl Backend {
+    /// SAVE: write the configured snapshot file and remember when it succeeded.
+    pub fn save(&self) -> Result<()> {
+        self.save_snapshot(self.config().snapshot_path())?;
+        self.stats.record_save();
+        Ok(())
+    }
+
+    /// BGSAVE: save on a separate thread, returns false if a background save is already
+    /// running. The keyspace is copied shard by shard while the save runs, so writes
+    /// landing meanwhile may or may not make it to the file.
+    pub fn bgsave(&self) -> bool {
+        if !self.stats.start_bgsave() {
+            return false;
+        }
+        let backend = self.clone();
+        thread::spawn(move || {
+            if let Err(e) = backend.save() {
+                warn!("background save failed: {:?}", e);
+            }
+            backend.stats.finish_bgsave();
+        });
+        true
+    }
+
+    /// Start a BGSAVE whenever the process receives `signal`, for tooling that takes
+    /// snapshots by signaling the server, which listens for SIGUSR1. The handler is in place
+    /// when this returns, the signals are then handled on a spawned task.
+    #[cfg(unix)]
+    pub fn bgsave_on_signal(&self, signal: SignalKind) -> Result<JoinHandle<()>> {
+        let signals = unix::signal(signal)?;
+        let signals = futures::stream::unfold(signals, |mut signals| async move {
+            signals.recv().await.map(|()| ((), signals))
+        });
+        Ok(self.bgsave_on(signals))
+    }
+
+    // a BGSAVE per item of `triggers`, the signals in the server and anything in tests
+    fn bgsave_on(&self, triggers: impl Stream<Item = ()> + Send + 'static) -> JoinHandle<()> {
+        let backend = self.clone();
+        tokio::spawn(async move {
+            let mut triggers = std::pin::pin!(triggers);
+            while triggers.next().await.is_some() {
+                if !backend.bgsave() {
+                    warn!("background save already in progress, signal ignored");
+                }
+            }
+        })
+    }
+
+    pub fn save_snapshot(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let mut buf = Vec::new();
+        let mut push = |record: Vec<RespFrame>| buf.extend(RespArray::new(record).encode());
+
+        for entry in self.map.iter() {
+            push(vec![
+                bulk("string"),
+                bulk(entry.key().clone()),
+                entry.value().clone(),
+            ]);
+        }
+        for entry in self.hmap.iter() {
+            let mut record = vec![bulk("hash"), bulk(entry.key().clone())];
+            for field in entry.value().iter() {
+                record.push(bulk(field.key().clone()));
+                record.push(field.value().clone());
+            }
+            push(record);
+        }
+        let clock = Clock::now();
+        for entry in self.hexpires.iter() {
+            for field in entry.value().iter() {
+                push(vec![
+                    bulk("hexpire"),
+                    bulk(entry.key().clone()),
+                    bulk(field.key().clone()),
+                    bulk(clock.to_unix_ms(*field.value()).to_string()),
+                ]);
+            }
+        }
+        for entry in self.set.iter() {
+            let mut record = vec![bulk("set"), bulk(entry.key().clone())];
+            record.extend(entry.value().iter().map(|m| bulk(m.key().clone())));
+            push(record);
+        }
+        for entry in self.zset.iter() {
+            let mut record = vec![bulk("zset"), bulk(entry.key().clone())];
+            for (member, score) in entry.value().iter() {
+                record.push(bulk(member));
+                record.push(RespFrame::Double(score));
+            }
+            push(record);
+        }
+        for entry in self.list.iter() {
+            let mut record = vec![bulk("list"), bulk(entry.key().clone())];
+            record.extend(entry.value().iter().map(|e| bulk(e.clone())));
+            push(record);
+        }
+
+        for entry in self.expires.iter() {
+            push(vec![
+                bulk("expire"),
+                bulk(entry.key().clone()),
+                bulk(clock.to_unix_ms(*entry.value()).to_string()),
+            ]);
+        }
+
+        // write aside and rename so a crash never leaves a truncated snapshot behind
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, buf)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    // replaces the whole keyspace with the content of the snapshot
+    pub fn load_snapshot(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut buf = BytesMut::from(fs::read(path)?.as_slice());
+        self.flush();
+        let clock = Clock::now();
+        while !buf.is_empty() {
+            let mut record = RespArray::decode(&mut buf)?.0.into_iter();
+            let kind = next_bytes(&mut record)?;
+            let key = next_bytes(&mut record)?;
+            match kind.as_slice() {
+                b"string" => {
+                    let value = record
+                        .next()
+                        .ok_or_else(|| anyhow!("missing string value"))?;
+                    self.map.insert(key, detached(value));
+                }
+                b"hash" => {
+                    let fields = self.hmap.entry(key).or_default();
+                    while let Some(field) = record.next() {
+                        let field = frame_string(field)?;
+                        let value = record.next().ok_or_else(|| anyhow!("missing hash value"))?;
+                        fields.insert(field, detached(value));
+                    }
+                }
+                b"hexpire" => {
+                    let field = String::from_utf8(next_bytes(&mut record)?)?;
+                    let deadline = String::from_utf8(next_bytes(&mut record)?)?.parse()?;
+                    self.hexpires
+                        .entry(key)
+                        .or_default()
+                        .insert(field, clock.to_instant(deadline)?);
+                }
+                b"set" => {
+                    let members = self.set.entry(key).or_default();
+                    for member in record {
+                        members.insert(frame_string(member)?);
+                    }
+                }
+                b"zset" => {
+                    let mut zset = self.zset.entry(key).or_default();
+                    while let Some(member) = record.next() {
+                        let member = frame_string(member)?;
+                        match record.next() {
+                            Some(RespFrame::Double(score)) => zset.insert(member, score),
+                            _ => return Err(anyhow!("missing zset score")),
+                        };
+                    }
+                }
+                b"list" => {
+                    let mut list = self.list.entry(key).or_default();
+                    for element in record {
+                        match element {
+                            RespFrame::BulkString(element) => list.push_back(element.into_vec()),
+                            _ => return Err(anyhow!("malformed snapshot record")),
+                        }
+                    }
+                }
+                b"expire" => {
+                    let deadline = String::from_utf8(next_bytes(&mut record)?)?.parse()?;
+                    self.expires.insert(key, clock.to_instant(deadline)?);
+                }
+                kind => {
+                    return Err(anyhow!(
+                        "unknown snapshot record '{}'",
+                        String::from_utf8_lossy(kind)
+                    ));
+                }
+            }
+        }
+        self.used_memory
+            .store(self.measure_memory(), Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+// pairs the monotonic and the wall clock once so every deadline is converted alike
+struct Clock {
+    instant: Instant,
+    unix_ms: u64,
+}
+
+impl Clock {
+    fn now() -> Self {
+        let since_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Self {
+            instant: Instant::now(),
+            unix_ms: since_epoch.as_millis() as u64,
+        }
+    }
+
+    fn to_unix_ms(&self, deadline: Instant) -> u64 {
+        match deadline.checked_duration_since(self.instant) {
+            Some(ahead) => self.unix_ms.saturating_add(ahead.as_millis() as u64),
+            None => self
+                .unix_ms
+                .saturating_sub(self.instant.duration_since(deadline).as_millis() as u64),
+        }
+    }
+
+    // the deadline comes from the file, one too far away for an Instant is malformed
+    fn to_instant(&self, unix_ms: u64) -> Result<Instant> {
+        if unix_ms >= self.unix_ms {
+            self.instant
+                .checked_add(Duration::from_millis(unix_ms - self.unix_ms))
+                .ok_or_else(|| anyhow!("malformed snapshot record"))
+        } else {
+            // already past, anything not in the future expires on first access
+            Ok(self.instant)
+        }
+    }
+}
+
+fn bulk(value: impl Into<Vec<u8>>) -> RespFrame {
+    BulkString::new(value.into()).into()
+}
+
+fn next_bytes(record: &mut impl Iterator<Item = RespFrame>) -> Result<Vec<u8>> {
+    match record.next() {
+        Some(RespFrame::BulkString(s)) => Ok(s.into_vec()),
+        _ => Err(anyhow!("malformed snapshot record")),
+    }
+}
+
+fn frame_string(frame: RespFrame) -> Result<String> {
+    match frame {
+        RespFrame::BulkString(s) => Ok(String::from_utf8(s.into_vec())?),
+        _ => Err(anyhow!("malformed snapshot record")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_deadline_out_of_range() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("dump.rdb");
+        let mut snapshot = b"*3\r\n$6\r\nstring\r\n$1\r\nk\r\n$1\r\nv\r\n".to_vec();
+        snapshot
+            .extend_from_slice(b"*3\r\n$6\r\nexpire\r\n$1\r\nk\r\n$20\r\n18446744073709551615\r\n");
+        fs::write(&path, snapshot)?;
+
+        let ret = Backend::new().load_snapshot(&path);
+        // whether u64::MAX milliseconds fit in an Instant depends on the platform, where they
+        // don't the record is refused instead of panicking
+        if let Err(e) = ret {
+            assert_eq!(e.to_string(), "malformed snapshot record");
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_trigger_starts_bgsave() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let backend = Backend::new();
+        backend
+            .set_config("dir", dir.path().to_str().unwrap())
+            .map_err(anyhow::Error::msg)?;
+        backend.set(b"hello".to_vec(), BulkString::from("world").into());
+
+        // a real SIGUSR1 would reach the whole test process, one trigger stands in for it
+        let handle = backend.bgsave_on(futures::stream::iter([()]));
+
+        let path = dir.path().join("dump.rdb");
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !path.exists() && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(path.exists());
+        handle.abort();
+        Ok(())
+    }
+}
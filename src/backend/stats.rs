@@ -0,0 +1,82 @@
+use std::{
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+#[derive(Debug)]
+pub struct ServerStats {
+    total_net_input_bytes: AtomicU64,
+    total_net_output_bytes: AtomicU64,
+    // socket flushes, each one carrying every reply of a pipelined batch
+    total_writes_processed: AtomicU64,
+    // unix time in seconds of the last successful save, the start time until then
+    last_save: AtomicU64,
+    bgsave_in_progress: AtomicBool,
+}
+
+impl Default for ServerStats {
+    fn default() -> Self {
+        Self {
+            total_net_input_bytes: AtomicU64::default(),
+            total_net_output_bytes: AtomicU64::default(),
+            total_writes_processed: AtomicU64::default(),
+            last_save: AtomicU64::new(unix_time_secs()),
+            bgsave_in_progress: AtomicBool::default(),
+        }
+    }
+}
+
+impl ServerStats {
+    pub fn record_input(&self, bytes: usize) {
+        self.total_net_input_bytes
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_output(&self, bytes: usize) {
+        self.total_net_output_bytes
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_write(&self) {
+        self.total_writes_processed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_save(&self) {
+        self.last_save.store(unix_time_secs(), Ordering::Relaxed);
+    }
+
+    // claims the single background save slot, false when a save is already running
+    pub(crate) fn start_bgsave(&self) -> bool {
+        !self.bgsave_in_progress.swap(true, Ordering::AcqRel)
+    }
+
+    pub(crate) fn finish_bgsave(&self) {
+        self.bgsave_in_progress.store(false, Ordering::Release);
+    }
+
+    pub fn bgsave_in_progress(&self) -> bool {
+        self.bgsave_in_progress.load(Ordering::Acquire)
+    }
+
+    pub fn last_save(&self) -> u64 {
+        self.last_save.load(Ordering::Relaxed)
+    }
+
+    pub fn total_net_input_bytes(&self) -> u64 {
+        self.total_net_input_bytes.load(Ordering::Relaxed)
+    }
+
+    pub fn total_net_output_bytes(&self) -> u64 {
+        self.total_net_output_bytes.load(Ordering::Relaxed)
+    }
+
+    pub fn total_writes_processed(&self) -> u64 {
+        self.total_writes_processed.load(Ordering::Relaxed)
+    }
+}
+
+fn unix_time_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
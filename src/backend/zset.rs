@@ -0,0 +1,170 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, HashMap},
+};
+
+// members are kept twice: by name for score lookups and ordered by (score, member) for
+// ranges, the same pairing the redis skiplist + dict encoding uses
+#[derive(Debug, Default, Clone)]
+pub struct ZSet {
+    scores: HashMap<String, f64>,
+    ordered: BTreeSet<ScoredMember>,
+}
+
+#[derive(Debug, Clone)]
+struct ScoredMember {
+    score: f64,
+    member: String,
+}
+
+impl PartialEq for ScoredMember {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ScoredMember {}
+
+impl PartialOrd for ScoredMember {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ScoredMember {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score
+            .total_cmp(&other.score)
+            .then_with(|| self.member.cmp(&other.member))
+    }
+}
+
+/// One end of a lexicographical range, as written in ZRANGEBYLEX: `-`, `+`, `[member`
+/// or `(member`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LexBound {
+    NegInf,
+    PosInf,
+    Inclusive(String),
+    Exclusive(String),
+}
+
+impl LexBound {
+    pub fn parse(s: &[u8]) -> Option<Self> {
+        match s {
+            b"-" => Some(LexBound::NegInf),
+            b"+" => Some(LexBound::PosInf),
+            [b'[', rest @ ..] => Some(LexBound::Inclusive(String::from_utf8(rest.to_vec()).ok()?)),
+            [b'(', rest @ ..] => Some(LexBound::Exclusive(String::from_utf8(rest.to_vec()).ok()?)),
+            _ => None,
+        }
+    }
+
+    fn allows_above(&self, member: &str) -> bool {
+        match self {
+            LexBound::NegInf => true,
+            LexBound::PosInf => false,
+            LexBound::Inclusive(min) => member >= min.as_str(),
+            LexBound::Exclusive(min) => member > min.as_str(),
+        }
+    }
+
+    fn allows_below(&self, member: &str) -> bool {
+        match self {
+            LexBound::NegInf => false,
+            LexBound::PosInf => true,
+            LexBound::Inclusive(max) => member <= max.as_str(),
+            LexBound::Exclusive(max) => member < max.as_str(),
+        }
+    }
+}
+
+impl ZSet {
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    // returns true if the member is new, an existing member only has its score updated
+    pub fn insert(&mut self, member: String, score: f64) -> bool {
+        let old = self.scores.insert(member.clone(), score);
+        if let Some(old) = old {
+            self.ordered.remove(&ScoredMember {
+                score: old,
+                member: member.clone(),
+            });
+        }
+        self.ordered.insert(ScoredMember { score, member });
+        old.is_none()
+    }
+
+    pub fn score(&self, member: &str) -> Option<f64> {
+        self.scores.get(member).copied()
+    }
+
+    // removes and returns the member with the lowest score, ties broken by member name
+    pub fn pop_min(&mut self) -> Option<(String, f64)> {
+        let ScoredMember { score, member } = self.ordered.pop_first()?;
+        self.scores.remove(&member);
+        Some((member, score))
+    }
+
+    pub fn pop_max(&mut self) -> Option<(String, f64)> {
+        let ScoredMember { score, member } = self.ordered.pop_last()?;
+        self.scores.remove(&member);
+        Some((member, score))
+    }
+
+    pub fn remove(&mut self, member: &str) -> bool {
+        match self.scores.remove(member) {
+            Some(score) => {
+                self.ordered.remove(&ScoredMember {
+                    score,
+                    member: member.to_string(),
+                });
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Members with their scores, from the lowest score up.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&str, f64)> {
+        self.ordered.iter().map(|v| (v.member.as_str(), v.score))
+    }
+
+    // only meaningful when every member has the same score, like in redis
+    pub fn range_by_lex<'a>(
+        &'a self,
+        min: &'a LexBound,
+        max: &'a LexBound,
+    ) -> impl Iterator<Item = &'a str> {
+        self.iter()
+            .map(|(member, _)| member)
+            .skip_while(move |m| !min.allows_above(m))
+            .take_while(move |m| max.allows_below(m))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zset_insert_orders_by_score_then_member() {
+        let mut zset = ZSet::default();
+        assert!(zset.insert("b".to_string(), 1.0));
+        assert!(zset.insert("a".to_string(), 1.0));
+        assert!(zset.insert("c".to_string(), 0.5));
+        assert!(!zset.insert("c".to_string(), 2.0));
+
+        let members = zset.iter().collect::<Vec<_>>();
+        assert_eq!(members, vec![("a", 1.0), ("b", 1.0), ("c", 2.0)]);
+        assert_eq!(zset.score("c"), Some(2.0));
+        assert!(zset.remove("a"));
+        assert_eq!(zset.len(), 2);
+    }
+}
@@ -0,0 +1,149 @@
+//! A minimal async client, enough to talk to the server from tests without a full redis
+//! client library.
+
+use anyhow::{Result, anyhow};
+use bytes::BytesMut;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpStream, ToSocketAddrs},
+};
+
+use crate::{BulkString, RespArray, RespDecode, RespEncode, RespError, RespFrame};
+
+/// A connection to the server. Commands are sent as arrays of bulk strings and replies are
+/// decoded into [`RespFrame`]s, an error reply is returned as a frame, not as an `Err`.
+#[derive(Debug)]
+pub struct SimpleRedisClient {
+    stream: TcpStream,
+    buf: BytesMut,
+}
+
+/// Commands queued to be sent in one write, see [`SimpleRedisClient::pipeline`].
+#[derive(Debug)]
+pub struct Pipeline<'a> {
+    client: &'a mut SimpleRedisClient,
+    requests: Vec<u8>,
+    count: usize,
+}
+
+impl SimpleRedisClient {
+    pub async fn connect(addr: impl ToSocketAddrs) -> Result<Self> {
+        Ok(Self {
+            stream: TcpStream::connect(addr).await?,
+            buf: BytesMut::with_capacity(4096),
+        })
+    }
+
+    /// Send a command, e.g. `&[b"set", b"key", b"value"]`, and wait for its reply.
+    pub async fn command(&mut self, args: &[&[u8]]) -> Result<RespFrame> {
+        self.stream.write_all(&request(args)).await?;
+        self.read_frame().await
+    }
+
+    /// Queue several commands, then send them at once and read every reply in order.
+    pub fn pipeline(&mut self) -> Pipeline<'_> {
+        Pipeline {
+            client: self,
+            requests: Vec::new(),
+            count: 0,
+        }
+    }
+
+    async fn read_frame(&mut self) -> Result<RespFrame> {
+        loop {
+            match RespFrame::expect_length(&self.buf) {
+                Ok(len) if len <= self.buf.len() => return Ok(RespFrame::decode(&mut self.buf)?),
+                Ok(_) | Err(RespError::NotComplete) => {}
+                Err(e) => return Err(e.into()),
+            }
+            if self.stream.read_buf(&mut self.buf).await? == 0 {
+                return Err(anyhow!("connection closed before a complete reply"));
+            }
+        }
+    }
+}
+
+impl Pipeline<'_> {
+    pub fn cmd(&mut self, args: &[&[u8]]) -> &mut Self {
+        self.requests.extend(request(args));
+        self.count += 1;
+        self
+    }
+
+    pub async fn execute(self) -> Result<Vec<RespFrame>> {
+        self.client.stream.write_all(&self.requests).await?;
+        let mut replies = Vec::with_capacity(self.count);
+        for _ in 0..self.count {
+            replies.push(self.client.read_frame().await?);
+        }
+        Ok(replies)
+    }
+}
+
+fn request(args: &[&[u8]]) -> Vec<u8> {
+    RespArray::new(
+        args.iter()
+            .map(|arg| BulkString::from(*arg).into())
+            .collect::<Vec<RespFrame>>(),
+    )
+    .encode()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Backend, RespNullBulkString, SimpleString, network};
+    use std::net::SocketAddr;
+    use tokio::net::TcpListener;
+
+    async fn start_server() -> Result<SocketAddr> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let backend = Backend::new();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(network::stream_handler(stream, backend.clone()));
+            }
+        });
+        Ok(addr)
+    }
+
+    #[tokio::test]
+    async fn test_set_then_get() -> Result<()> {
+        let addr = start_server().await?;
+        let mut client = SimpleRedisClient::connect(addr).await?;
+
+        let reply = client.command(&[b"set", b"key", b"value"]).await?;
+        assert_eq!(reply, SimpleString::new("OK").into());
+        let reply = client.command(&[b"get", b"key"]).await?;
+        assert_eq!(reply, BulkString::from("value").into());
+        let reply = client.command(&[b"get", b"missing"]).await?;
+        assert_eq!(reply, RespNullBulkString.into());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pipeline() -> Result<()> {
+        let addr = start_server().await?;
+        let mut client = SimpleRedisClient::connect(addr).await?;
+
+        let mut pipeline = client.pipeline();
+        pipeline
+            .cmd(&[b"incr", b"counter"])
+            .cmd(&[b"incrby", b"counter", b"10"])
+            .cmd(&[b"get", b"counter"]);
+        let replies = pipeline.execute().await?;
+        assert_eq!(
+            replies,
+            [
+                RespFrame::Integer(1),
+                RespFrame::Integer(11),
+                BulkString::from("11").into(),
+            ]
+        );
+
+        let reply = client.command(&[b"incrby", b"counter", b"ten"]).await?;
+        assert!(matches!(reply, RespFrame::Error(_)));
+        Ok(())
+    }
+}
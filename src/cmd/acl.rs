@@ -0,0 +1,113 @@
+use crate::{AclUser, Backend, BulkString, ReplyBuilder, RespArray, RespFrame, RespNullBulkString};
+
+use super::{AclGetUser, AclWhoAmI, CommandError, CommandExecutor, extract_args, validate_command};
+
+impl CommandExecutor for AclWhoAmI {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        BulkString::from(backend.config().default_user.name).into()
+    }
+}
+
+impl CommandExecutor for AclGetUser {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let user = backend.config().default_user;
+        if user.name != self.name {
+            return RespFrame::NullBulkString(RespNullBulkString);
+        }
+        ReplyBuilder::new()
+            .bulk("commands")
+            .bulk(command_rules(&user))
+            .bulk("keys")
+            .bulk("~*")
+            .build()
+    }
+}
+
+// the rules in the same notation ACL GETUSER uses in redis, e.g. `-@all +get +set`
+fn command_rules(user: &AclUser) -> String {
+    let mut rules = if user.allowed_commands.is_empty() {
+        vec!["+@all".to_string()]
+    } else {
+        std::iter::once("-@all".to_string())
+            .chain(user.allowed_commands.iter().map(|c| format!("+{}", c)))
+            .collect()
+    };
+    rules.extend(user.denied_commands.iter().map(|c| format!("-{}", c)));
+    rules.join(" ")
+}
+
+impl TryFrom<RespArray> for AclWhoAmI {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["acl", "whoami"], 0)?;
+        Ok(AclWhoAmI)
+    }
+}
+
+impl TryFrom<RespArray> for AclGetUser {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["acl", "getuser"], 1)?;
+
+        let mut args = extract_args(value, 2)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(name)) => Ok(AclGetUser {
+                name: String::from_utf8(name.into_vec())?,
+            }),
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid user name".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RespDecode;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_acl_whoami() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$3\r\nacl\r\n$6\r\nWHOAMI\r\n");
+        let cmd = AclWhoAmI::try_from(RespArray::decode(&mut buf)?)?;
+        assert_eq!(
+            cmd.execute(&Backend::new()),
+            BulkString::from("default").into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_acl_getuser() {
+        let backend = Backend::new();
+        backend.set_config("acl-allow-commands", "get set").unwrap();
+        backend.set_config("acl-deny-commands", "flushall").unwrap();
+
+        let cmd = AclGetUser {
+            name: "default".to_string(),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new([
+                BulkString::from("commands").into(),
+                BulkString::from("-@all +get +set -flushall").into(),
+                BulkString::from("keys").into(),
+                BulkString::from("~*").into(),
+            ])
+            .into()
+        );
+
+        let cmd = AclGetUser {
+            name: "alice".to_string(),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespFrame::NullBulkString(RespNullBulkString)
+        );
+    }
+}
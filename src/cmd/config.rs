@@ -0,0 +1,162 @@
+use crate::{Backend, ReplyBuilder, RespArray, RespFrame, SimpleError};
+
+use super::{
+    CommandError, CommandExecutor, ConfigGet, ConfigRewrite, ConfigSet, RESP_OK, extract_args,
+    validate_command,
+};
+
+impl CommandExecutor for ConfigGet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.config().get(&self.name) {
+            Some(value) => ReplyBuilder::new().bulk(self.name).bulk(value).build(),
+            None => ReplyBuilder::new().build(),
+        }
+    }
+}
+
+impl CommandExecutor for ConfigSet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.set_config(&self.name, &self.value) {
+            Ok(()) => RESP_OK.clone(),
+            Err(e) => SimpleError::new(e).into(),
+        }
+    }
+}
+
+// the file is left untouched, tools only call this to make a CONFIG SET stick and the
+// settings they care about are read back through CONFIG GET
+impl CommandExecutor for ConfigRewrite {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.config().config_file {
+            Some(_) => RESP_OK.clone(),
+            None => SimpleError::new("ERR The server is running without a config file").into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for ConfigGet {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["config", "get"], 1)?;
+
+        let mut args = extract_args(value, 2)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(name)) => Ok(ConfigGet {
+                name: String::from_utf8(name.into_vec())?.to_ascii_lowercase(),
+            }),
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid parameter".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for ConfigSet {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["config", "set"], 2)?;
+
+        let mut args = extract_args(value, 2)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(name)), Some(RespFrame::BulkString(value))) => {
+                Ok(ConfigSet {
+                    name: String::from_utf8(name.into_vec())?.to_ascii_lowercase(),
+                    value: String::from_utf8(value.into_vec())?,
+                })
+            }
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid parameter or value".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for ConfigRewrite {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["config", "rewrite"], 0)?;
+        Ok(ConfigRewrite)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RespDecode;
+
+    use super::*;
+    use crate::{BackendConfig, BulkString};
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_config_set_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*4\r\n$6\r\nconfig\r\n$3\r\nset\r\n$25\r\nHASH-MAX-LISTPACK-ENTRIES\r\n$2\r\n10\r\n",
+        );
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: ConfigSet = frame.try_into()?;
+        assert_eq!(result.name, "hash-max-listpack-entries");
+        assert_eq!(result.value, "10");
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_set_get_commands() {
+        let backend = Backend::new();
+        let cmd = ConfigSet {
+            name: "set-max-listpack-entries".to_string(),
+            value: "10".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+
+        let cmd = ConfigGet {
+            name: "set-max-listpack-entries".to_string(),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new([
+                BulkString::from("set-max-listpack-entries").into(),
+                BulkString::from("10").into(),
+            ])
+            .into()
+        );
+
+        let cmd = ConfigSet {
+            name: "set-max-listpack-entries".to_string(),
+            value: "ten".to_string(),
+        };
+        assert!(matches!(cmd.execute(&backend), RespFrame::Error(_)));
+
+        let cmd = ConfigSet {
+            name: "unknown".to_string(),
+            value: "10".to_string(),
+        };
+        assert!(matches!(cmd.execute(&backend), RespFrame::Error(_)));
+    }
+
+    #[test]
+    fn test_config_rewrite() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$6\r\nconfig\r\n$7\r\nREWRITE\r\n");
+        let cmd = ConfigRewrite::try_from(RespArray::decode(&mut buf)?)?;
+        assert_eq!(
+            cmd.execute(&Backend::new()),
+            SimpleError::new("ERR The server is running without a config file").into()
+        );
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("redis.conf");
+        std::fs::write(&path, "# limits\nmaxmemory 1024\n\nDbfilename  other.rdb\n")?;
+        let config = BackendConfig::from_file(&path).map_err(anyhow::Error::msg)?;
+        let backend = Backend::with_config(config);
+        assert_eq!(backend.config().maxmemory, 1024);
+        assert_eq!(backend.config().dbfilename, "other.rdb");
+        assert_eq!(ConfigRewrite.execute(&backend), RESP_OK.clone());
+        Ok(())
+    }
+}
@@ -1,29 +1,93 @@
+use std::time::Duration;
+
+use bytes::BytesMut;
+
 use super::{
-    CommandError, CommandExecutor, HGet, HGetAll, HMGet, HSet, RESP_OK, extract_args,
-    validate_command,
+    CommandError, CommandExecutor, HDel, HExists, HExpire, HGet, HGetAll, HIncrBy, HKeys, HMGet,
+    HPersist, HSet, HTtl, HVals, RESP_REPLY_TOO_LARGE, RESP_WRONGTYPE, extract_args, is_wrong_type,
+    parse_integer, reply_too_large, validate_command,
 };
-use crate::{BulkString, RespArray, RespFrame, RespNull, backend::Backend};
+use crate::{BulkString, RespArray, RespEncode, RespFrame, RespNullBulkString, backend::Backend};
 
 impl CommandExecutor for HGet {
     fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "hash") {
+            return RESP_WRONGTYPE.clone();
+        }
         match backend.hget(&self.key, &self.field) {
             Some(value) => value,
-            None => RespFrame::Null(RespNull),
+            None => RespFrame::NullBulkString(RespNullBulkString),
         }
     }
 }
 
 impl CommandExecutor for HMGet {
     fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "hash") {
+            return RESP_WRONGTYPE.clone();
+        }
         backend.hmget(&self.key, self.fields)
     }
 }
 
+impl CommandExecutor for HDel {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "hash") {
+            return RESP_WRONGTYPE.clone();
+        }
+        let removed = self
+            .fields
+            .iter()
+            .filter(|field| backend.hdel(&self.key, field))
+            .count();
+        RespFrame::Integer(removed as i64)
+    }
+}
+
+impl CommandExecutor for HExists {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "hash") {
+            return RESP_WRONGTYPE.clone();
+        }
+        RespFrame::Integer(backend.hget(&self.key, &self.field).is_some() as i64)
+    }
+}
+
+// a missing field counts as 0, the result is stored back as its decimal text like INCRBY does
+impl CommandExecutor for HIncrBy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "hash") {
+            return RESP_WRONGTYPE.clone();
+        }
+        let ret = backend.update_hash_field(&self.key, self.field, |current| {
+            let current = match current {
+                Some(value) => {
+                    parse_integer::<i64>(value).map_err(|_| CommandError::HashValueNotAnInteger)?
+                }
+                None => 0,
+            };
+            let value = current
+                .checked_add(self.delta)
+                .ok_or(CommandError::Overflow)?;
+            Ok::<_, CommandError>((BulkString::from(value.to_string()).into(), value))
+        });
+        match ret {
+            Ok(value) => RespFrame::Integer(value),
+            Err(e) => e.into(),
+        }
+    }
+}
+
 impl CommandExecutor for HGetAll {
     fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "hash") {
+            return RESP_WRONGTYPE.clone();
+        }
+        backend.purge_expired_fields(&self.key);
         let hmap = backend.hmap.get(&self.key);
 
         match hmap {
+            Some(hmap) if reply_too_large(backend, hmap.len() * 2) => RESP_REPLY_TOO_LARGE.clone(),
             Some(hmap) => {
                 let mut data = Vec::with_capacity(hmap.len());
                 for v in hmap.iter() {
@@ -45,11 +109,228 @@ impl CommandExecutor for HGetAll {
     }
 }
 
+impl CommandExecutor for HKeys {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        hash_reply(backend, &self.key, |field, _| {
+            BulkString::from(field).into()
+        })
+    }
+}
+
+impl CommandExecutor for HVals {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        hash_reply(backend, &self.key, |_, value| value.clone())
+    }
+}
+
+impl HKeys {
+    pub fn stream(self, backend: &Backend, dst: &mut BytesMut) {
+        stream_hash_reply(backend, &self.key, dst, |field, _| {
+            BulkString::from(field).into()
+        })
+    }
+}
+
+impl HVals {
+    pub fn stream(self, backend: &Backend, dst: &mut BytesMut) {
+        stream_hash_reply(backend, &self.key, dst, |_, value| value.clone())
+    }
+}
+
+// one element per live field of the hash, an empty array for a missing key
+fn hash_reply(
+    backend: &Backend,
+    key: &[u8],
+    element: impl Fn(&str, &RespFrame) -> RespFrame,
+) -> RespFrame {
+    if is_wrong_type(backend, key, "hash") {
+        return RESP_WRONGTYPE.clone();
+    }
+    backend.purge_expired_fields(key);
+    match backend.hmap.get(key) {
+        Some(hmap) if reply_too_large(backend, hmap.len()) => RESP_REPLY_TOO_LARGE.clone(),
+        Some(hmap) => RespArray::new(
+            hmap.iter()
+                .map(|v| element(v.key(), v.value()))
+                .collect::<Vec<_>>(),
+        )
+        .into(),
+        None => RespArray::new([]).into(),
+    }
+}
+
+// what hash_reply encodes to, written to `dst` one element at a time so a big hash never
+// becomes a frame
+fn stream_hash_reply(
+    backend: &Backend,
+    key: &[u8],
+    dst: &mut BytesMut,
+    element: impl Fn(&str, &RespFrame) -> RespFrame,
+) {
+    if is_wrong_type(backend, key, "hash") {
+        return dst.extend_from_slice(&RESP_WRONGTYPE.clone().encode());
+    }
+    backend.purge_expired_fields(key);
+    // held exclusively, HDEL only needs a shared guard to remove a field, and the array
+    // header has to count exactly the elements written after it
+    match backend.hmap.get_mut(key) {
+        Some(hmap) if reply_too_large(backend, hmap.len()) => {
+            dst.extend_from_slice(&RESP_REPLY_TOO_LARGE.clone().encode())
+        }
+        Some(hmap) => {
+            dst.extend_from_slice(format!("*{}\r\n", hmap.len()).as_bytes());
+            for v in hmap.iter() {
+                dst.extend_from_slice(&element(v.key(), v.value()).encode());
+            }
+        }
+        None => dst.extend_from_slice(&RespArray::new([]).encode()),
+    }
+}
+
 impl CommandExecutor for HSet {
     fn execute(self, backend: &Backend) -> RespFrame {
-        backend.hset(self.key, self.field, self.value);
-        RESP_OK.clone()
+        // a hash written next to a string would shadow it, counted twice by DBSIZE and SCAN
+        if is_wrong_type(backend, &self.key, "hash") {
+            return RESP_WRONGTYPE.clone();
+        }
+        let added = self
+            .fields
+            .into_iter()
+            .filter(|(field, value)| backend.hset(self.key.clone(), field.clone(), value.clone()))
+            .count();
+        RespFrame::Integer(added as i64)
+    }
+}
+
+impl CommandExecutor for HExpire {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "hash") {
+            return RESP_WRONGTYPE.clone();
+        }
+        let ttl = Duration::from_secs(self.seconds);
+        let ret = self
+            .fields
+            .iter()
+            .map(|field| backend.hexpire(&self.key, field, ttl).map(RespFrame::from))
+            .collect::<Option<Vec<RespFrame>>>();
+        match ret {
+            Some(ret) => RespArray::new(ret).into(),
+            None => CommandError::InvalidExpireTime("hexpire".to_string()).into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for HExpire {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hexpire"], 5)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = match args.next() {
+            Some(frame) => parse_bytes_arg(frame, "key")?,
+            None => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        };
+        let seconds = args
+            .next()
+            .map(|frame| parse_integer::<u64>(&frame))
+            .transpose()?
+            .unwrap_or_default();
+        let fields = parse_fields_arg(args)?;
+        Ok(Self {
+            key,
+            seconds,
+            fields,
+        })
+    }
+}
+
+impl CommandExecutor for HTtl {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "hash") {
+            return RESP_WRONGTYPE.clone();
+        }
+        let ret = self
+            .fields
+            .iter()
+            .map(|field| backend.httl(&self.key, field).into())
+            .collect::<Vec<RespFrame>>();
+        RespArray::new(ret).into()
+    }
+}
+
+impl CommandExecutor for HPersist {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "hash") {
+            return RESP_WRONGTYPE.clone();
+        }
+        let ret = self
+            .fields
+            .iter()
+            .map(|field| backend.hpersist(&self.key, field).into())
+            .collect::<Vec<RespFrame>>();
+        RespArray::new(ret).into()
+    }
+}
+
+impl TryFrom<RespArray> for HTtl {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["httl"], 4)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = match args.next() {
+            Some(frame) => parse_bytes_arg(frame, "key")?,
+            None => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        };
+        let fields = parse_fields_arg(args)?;
+        Ok(Self { key, fields })
+    }
+}
+
+impl TryFrom<RespArray> for HPersist {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hpersist"], 4)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = match args.next() {
+            Some(frame) => parse_bytes_arg(frame, "key")?,
+            None => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        };
+        let fields = parse_fields_arg(args)?;
+        Ok(Self { key, fields })
+    }
+}
+
+// parse the `FIELDS numfields field [field ...]` tail shared by the hash field TTL commands
+fn parse_fields_arg(
+    mut args: impl Iterator<Item = RespFrame>,
+) -> Result<Vec<String>, CommandError> {
+    match args.next() {
+        Some(RespFrame::BulkString(s)) if s.eq_ignore_ascii_case(b"fields") => {}
+        _ => {
+            return Err(CommandError::InvalidArgument(
+                "Mandatory argument FIELDS is missing or not at the right position".to_string(),
+            ));
+        }
+    }
+    let num_fields = match args.next() {
+        Some(frame) => parse_integer::<usize>(&frame)?,
+        None => 0,
+    };
+    let fields = args
+        .enumerate()
+        .map(|(i, frame)| parse_string_arg(frame, &format!("field {}", i + 1)))
+        .collect::<Result<Vec<_>, _>>()?;
+    if num_fields == 0 || num_fields != fields.len() {
+        return Err(CommandError::InvalidArgument(
+            "The `numfields` parameter must match the number of arguments".to_string(),
+        ));
     }
+    Ok(fields)
 }
 
 impl TryFrom<RespArray> for HGet {
@@ -61,8 +342,8 @@ impl TryFrom<RespArray> for HGet {
         let mut args = extract_args(value, 1)?.into_iter();
         match (args.next(), args.next()) {
             (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(field))) => Ok(Self {
-                key: String::from_utf8(key.0)?,
-                field: String::from_utf8(field.0)?,
+                key: key.into_vec(),
+                field: String::from_utf8(field.into_vec())?,
             }),
             _ => Err(CommandError::InvalidArgument(
                 "Invalid key or field".to_string(),
@@ -71,6 +352,47 @@ impl TryFrom<RespArray> for HGet {
     }
 }
 
+impl TryFrom<RespArray> for HExists {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hexists"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(field))) => Ok(Self {
+                key: key.into_vec(),
+                field: String::from_utf8(field.into_vec())?,
+            }),
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid key or field".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for HIncrBy {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hincrby"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(field)), Some(delta)) => {
+                Ok(Self {
+                    key: key.into_vec(),
+                    field: String::from_utf8(field.into_vec())?,
+                    delta: parse_integer(&delta)?,
+                })
+            }
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid key or field".to_string(),
+            )),
+        }
+    }
+}
+
 impl TryFrom<RespArray> for HMGet {
     type Error = CommandError;
 
@@ -84,7 +406,7 @@ impl TryFrom<RespArray> for HMGet {
                 "HMGET command must have at least 2 arguments".to_string(),
             ));
         }
-        let key = parse_string_arg(args.remove(0), "key")?;
+        let key = parse_bytes_arg(args.remove(0), "key")?;
 
         let fields = args
             .into_iter()
@@ -95,9 +417,30 @@ impl TryFrom<RespArray> for HMGet {
     }
 }
 
+impl TryFrom<RespArray> for HDel {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hdel"], 2)?;
+
+        let mut args = extract_args(value, 1)?;
+        let key = parse_bytes_arg(args.remove(0), "key")?;
+        let fields = args
+            .into_iter()
+            .enumerate()
+            .map(|(i, frame)| parse_string_arg(frame, &format!("field {}", i + 1)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { key, fields })
+    }
+}
+
 fn parse_string_arg(frames: RespFrame, arg_name: &str) -> Result<String, CommandError> {
+    Ok(String::from_utf8(parse_bytes_arg(frames, arg_name)?)?)
+}
+
+fn parse_bytes_arg(frames: RespFrame, arg_name: &str) -> Result<Vec<u8>, CommandError> {
     match frames {
-        RespFrame::BulkString(bytes) => Ok(String::from_utf8(bytes.0)?),
+        RespFrame::BulkString(bytes) => Ok(bytes.into_vec()),
         _ => Err(CommandError::InvalidArgument(format!(
             "Invalid {} argument",
             arg_name
@@ -113,7 +456,7 @@ impl TryFrom<RespArray> for HGetAll {
         let mut args = extract_args(value, 1)?.into_iter();
         match args.next() {
             Some(RespFrame::BulkString(key)) => Ok(Self {
-                key: String::from_utf8(key.0)?,
+                key: key.into_vec(),
                 sort: false,
             }),
             _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
@@ -121,6 +464,36 @@ impl TryFrom<RespArray> for HGetAll {
     }
 }
 
+impl TryFrom<RespArray> for HKeys {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hkeys"], 1)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(HKeys {
+                key: key.into_vec(),
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for HVals {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hvals"], 1)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(HVals {
+                key: key.into_vec(),
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+
 impl TryFrom<RespArray> for HSet {
     type Error = CommandError;
 
@@ -128,24 +501,30 @@ impl TryFrom<RespArray> for HSet {
         validate_command(&value, &["hset"], 3)?;
 
         let mut args = extract_args(value, 1)?.into_iter();
-        match (args.next(), args.next(), args.next()) {
-            (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(field)), Some(value)) => {
-                Ok(Self {
-                    key: String::from_utf8(key.0)?,
-                    field: String::from_utf8(field.0)?,
-                    value,
-                })
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => key.into_vec(),
+            _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        };
+        if args.len() % 2 != 0 {
+            return Err(CommandError::WrongArity("hset".to_string()));
+        }
+        let mut fields = Vec::with_capacity(args.len() / 2);
+        while let (Some(field), Some(value)) = (args.next(), args.next()) {
+            match field {
+                RespFrame::BulkString(field) => {
+                    fields.push((String::from_utf8(field.into_vec())?, value))
+                }
+                _ => return Err(CommandError::InvalidArgument("Invalid field".to_string())),
             }
-            _ => Err(CommandError::InvalidArgument(
-                "Invalid key or field".to_string(),
-            )),
         }
+        Ok(HSet { key, fields })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::RespDecode;
+    use crate::cmd::Command;
+    use crate::{RespDecode, RespEncode};
 
     use super::*;
 
@@ -160,7 +539,7 @@ mod tests {
         let frame = RespArray::decode(&mut buf)?;
 
         let result: HGet = frame.try_into()?;
-        assert_eq!(result.key, "map");
+        assert_eq!(result.key, b"map");
         assert_eq!(result.field, "hello");
 
         Ok(())
@@ -172,7 +551,297 @@ mod tests {
         buf.extend_from_slice(b"*2\r\n$7\r\nhgetall\r\n$3\r\nmap\r\n");
         let frame = RespArray::decode(&mut buf)?;
         let result: HGetAll = frame.try_into()?;
-        assert_eq!(result.key, "map");
+        assert_eq!(result.key, b"map");
+        Ok(())
+    }
+
+    #[test]
+    fn test_hgetall_missing_key_is_empty_array() {
+        let cmd = HGetAll {
+            key: b"missing".to_vec(),
+            sort: false,
+        };
+        assert_eq!(cmd.execute(&Backend::new()).encode(), b"*0\r\n");
+    }
+
+    #[test]
+    fn test_hgetall_after_key_expiry() -> Result<()> {
+        let backend = Backend::new();
+        backend.hset(
+            b"map".to_vec(),
+            "f".to_string(),
+            BulkString::from("v").into(),
+        );
+        backend.set(b"string".to_vec(), BulkString::from("v").into());
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$7\r\nhgetall\r\n$6\r\nstring\r\n");
+        let cmd: HGetAll = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RESP_WRONGTYPE.clone());
+
+        assert!(backend.expire(b"map", 100));
+        assert_eq!(backend.ttl(b"map"), 100);
+        backend.expire_at(b"map", std::time::Instant::now());
+        let cmd = HGetAll {
+            key: b"map".to_vec(),
+            sort: false,
+        };
+        assert_eq!(cmd.execute(&backend), RespArray::new([]).into());
+        assert!(!backend.hmap.contains_key(b"map".as_slice()));
+        assert_eq!(backend.ttl(b"map"), -2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_hexists() {
+        let backend = Backend::new();
+        backend.hset(
+            b"map".to_vec(),
+            "f".to_string(),
+            BulkString::from("v").into(),
+        );
+        backend.set(b"string".to_vec(), BulkString::from("v").into());
+
+        let hexists = |key: &[u8], field: &str| {
+            HExists {
+                key: key.to_vec(),
+                field: field.to_string(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(hexists(b"map", "f"), RespFrame::Integer(1));
+        assert_eq!(hexists(b"map", "g"), RespFrame::Integer(0));
+        assert_eq!(hexists(b"missing", "f"), RespFrame::Integer(0));
+        assert_eq!(hexists(b"string", "f"), RESP_WRONGTYPE.clone());
+    }
+
+    #[test]
+    fn test_hincrby() -> Result<()> {
+        let backend = Backend::new();
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$7\r\nhincrby\r\n$3\r\nmap\r\n$1\r\nn\r\n$1\r\n5\r\n");
+        let cmd: HIncrBy = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(5));
+
+        let cmd = HIncrBy {
+            key: b"map".to_vec(),
+            field: "n".to_string(),
+            delta: -7,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-2));
+        assert_eq!(
+            backend.hget(b"map", "n"),
+            Some(BulkString::from("-2").into())
+        );
+
+        // an existing TTL survives the update
+        backend.hexpire(b"map", "n", Duration::from_secs(100));
+        let cmd = HIncrBy {
+            key: b"map".to_vec(),
+            field: "n".to_string(),
+            delta: 1,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-1));
+        assert_eq!(backend.httl(b"map", "n"), 100);
+        Ok(())
+    }
+
+    #[test]
+    fn test_hincrby_errors() -> Result<()> {
+        let backend = Backend::new();
+        backend.hset(
+            b"map".to_vec(),
+            "f".to_string(),
+            BulkString::from("abc").into(),
+        );
+        let cmd = HIncrBy {
+            key: b"map".to_vec(),
+            field: "f".to_string(),
+            delta: 1,
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            CommandError::HashValueNotAnInteger.into()
+        );
+        assert_eq!(
+            backend.hget(b"map", "f"),
+            Some(BulkString::from("abc").into())
+        );
+
+        backend.hset(
+            b"map".to_vec(),
+            "max".to_string(),
+            BulkString::from(i64::MAX.to_string()).into(),
+        );
+        let cmd = HIncrBy {
+            key: b"map".to_vec(),
+            field: "max".to_string(),
+            delta: 1,
+        };
+        assert_eq!(cmd.execute(&backend), CommandError::Overflow.into());
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$7\r\nhincrby\r\n$3\r\nmap\r\n$1\r\nn\r\n$1\r\nx\r\n");
+        let ret: Result<HIncrBy, _> = RespArray::decode(&mut buf)?.try_into();
+        assert!(matches!(ret, Err(CommandError::NotAnInteger)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hdel() -> Result<()> {
+        let backend = Backend::new();
+        for field in ["a", "b", "c"] {
+            backend.hset(
+                b"map".to_vec(),
+                field.to_string(),
+                BulkString::from("v").into(),
+            );
+        }
+        backend.expire(b"map", 100);
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$4\r\nhdel\r\n$3\r\nmap\r\n$1\r\na\r\n$1\r\nx\r\n");
+        let cmd: HDel = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.hget(b"map", "a"), None);
+        assert_eq!(
+            backend.hget(b"map", "b"),
+            Some(BulkString::from("v").into())
+        );
+
+        // a field that already expired is gone, deleting it again counts for nothing
+        backend.hexpire(b"map", "b", Duration::from_millis(1));
+        std::thread::sleep(Duration::from_millis(5));
+        let cmd = HDel {
+            key: b"map".to_vec(),
+            fields: vec!["b".to_string(), "c".to_string(), "c".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert!(!backend.hmap.contains_key(b"map".as_slice()));
+        assert_eq!(backend.type_of(b"map"), None);
+        // the key TTL went with it, a new hash under the same name has none
+        backend.hset(
+            b"map".to_vec(),
+            "a".to_string(),
+            BulkString::from("v").into(),
+        );
+        assert_eq!(backend.ttl(b"map"), -1);
+
+        let cmd = HDel {
+            key: b"missing".to_vec(),
+            fields: vec!["a".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+
+        buf.extend_from_slice(b"*2\r\n$4\r\nhdel\r\n$3\r\nmap\r\n");
+        let ret: Result<HDel, _> = RespArray::decode(&mut buf)?.try_into();
+        assert!(ret.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hmget_wrong_type() -> Result<()> {
+        let backend = Backend::new();
+        backend.set(b"string".to_vec(), BulkString::from("v").into());
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$5\r\nhmget\r\n$6\r\nstring\r\n$1\r\nf\r\n");
+        let cmd: HMGet = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RESP_WRONGTYPE.clone());
+
+        let cmd = HMGet {
+            key: b"missing".to_vec(),
+            fields: vec!["f".to_string()],
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new([RespNullBulkString.into()]).into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_hkeys_and_hvals() -> Result<()> {
+        let backend = Backend::new();
+        for (field, value) in [("a", "1"), ("b", "2"), ("c", "3")] {
+            backend.hset(
+                b"map".to_vec(),
+                field.to_string(),
+                BulkString::from(value).into(),
+            );
+        }
+        backend.hexpire(b"map", "c", Duration::from_millis(1));
+        std::thread::sleep(Duration::from_millis(5));
+
+        let sorted = |frame: RespFrame| {
+            let RespFrame::Array(array) = frame else {
+                panic!("expected an array reply");
+            };
+            let mut elements = array.to_vec();
+            elements.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            elements
+        };
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$5\r\nhkeys\r\n$3\r\nmap\r\n");
+        let cmd: HKeys = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(
+            sorted(cmd.execute(&backend)),
+            vec![BulkString::from("a").into(), BulkString::from("b").into()]
+        );
+        buf.extend_from_slice(b"*2\r\n$5\r\nhvals\r\n$3\r\nmap\r\n");
+        let cmd: HVals = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(
+            sorted(cmd.execute(&backend)),
+            vec![BulkString::from("1").into(), BulkString::from("2").into()]
+        );
+
+        let cmd = HKeys {
+            key: b"missing".to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), RespArray::new([]).into());
+        backend.set(b"string".to_vec(), BulkString::from("v").into());
+        let cmd = HVals {
+            key: b"string".to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), RESP_WRONGTYPE.clone());
+        Ok(())
+    }
+
+    #[test]
+    fn test_streamed_hash_reply_matches_buffered() -> Result<()> {
+        let backend = Backend::new();
+        for i in 0..1000 {
+            backend.hset(
+                b"map".to_vec(),
+                format!("field:{}", i),
+                BulkString::from(i.to_string()).into(),
+            );
+        }
+        backend.set(b"string".to_vec(), BulkString::from("v").into());
+
+        let streamed = |cmd: Command| {
+            let mut dst = BytesMut::new();
+            cmd.stream(&backend, &mut dst);
+            dst.to_vec()
+        };
+        let check = |key: &[u8]| {
+            let key = key.to_vec();
+            assert_eq!(
+                streamed(HKeys { key: key.clone() }.into()),
+                HKeys { key: key.clone() }.execute(&backend).encode()
+            );
+            assert_eq!(
+                streamed(HVals { key: key.clone() }.into()),
+                HVals { key }.execute(&backend).encode()
+            );
+        };
+        check(b"map");
+        check(b"missing");
+        check(b"string");
+        backend
+            .set_config("max-reply-elements", "10")
+            .map_err(anyhow::Error::msg)?;
+        check(b"map");
         Ok(())
     }
 
@@ -184,9 +853,44 @@ mod tests {
         let frame = RespArray::decode(&mut buf)?;
         let result: HSet = frame.try_into()?;
 
-        assert_eq!(result.key, "map");
-        assert_eq!(result.field, "hello");
-        assert_eq!(result.value, RespFrame::BulkString(b"world".into()));
+        assert_eq!(result.key, b"map");
+        assert_eq!(
+            result.fields,
+            vec![("hello".to_string(), RespFrame::BulkString(b"world".into()))]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_hset_multiple_pairs() -> Result<()> {
+        let backend = Backend::new();
+        backend.hset(
+            b"map".to_vec(),
+            "a".to_string(),
+            RespFrame::BulkString(b"0".into()),
+        );
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*8\r\n$4\r\nhset\r\n$3\r\nmap\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n$1\r\n2\r\n$1\r\nc\r\n$1\r\n3\r\n",
+        );
+        let cmd: HSet = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.fields.len(), 3);
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        assert_eq!(
+            backend.hget(b"map", "a"),
+            Some(RespFrame::BulkString(b"1".into()))
+        );
+        assert_eq!(
+            backend.hget(b"map", "c"),
+            Some(RespFrame::BulkString(b"3".into()))
+        );
+
+        buf.extend_from_slice(
+            b"*5\r\n$4\r\nhset\r\n$3\r\nmap\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n",
+        );
+        let ret: Result<HSet, _> = RespArray::decode(&mut buf)?.try_into();
+        assert!(matches!(ret, Err(CommandError::WrongArity(_))));
         Ok(())
     }
 
@@ -194,26 +898,27 @@ mod tests {
     fn test_hset_hget_hgetall_commands() -> Result<()> {
         let backend = Backend::new();
         let cmd = HSet {
-            key: "map".to_string(),
-            field: "hello".to_string(),
-            value: RespFrame::BulkString(b"world".into()),
+            key: b"map".to_vec(),
+            fields: vec![("hello".to_string(), RespFrame::BulkString(b"world".into()))],
         };
         let result = cmd.execute(&backend);
-        assert_eq!(result, RESP_OK.clone());
+        assert_eq!(result, RespFrame::Integer(1));
         let cmd = HSet {
-            key: "map".to_string(),
-            field: "hello1".to_string(),
-            value: RespFrame::BulkString(b"world1".into()),
+            key: b"map".to_vec(),
+            fields: vec![(
+                "hello1".to_string(),
+                RespFrame::BulkString(b"world1".into()),
+            )],
         };
         cmd.execute(&backend);
         let cmd = HGet {
-            key: "map".to_string(),
+            key: b"map".to_vec(),
             field: "hello".to_string(),
         };
         let result = cmd.execute(&backend);
         assert_eq!(result, RespFrame::BulkString(b"world".into()));
         let cmd = HGetAll {
-            key: "map".to_string(),
+            key: b"map".to_vec(),
             sort: true,
         };
         let result = cmd.execute(&backend);
@@ -226,4 +931,230 @@ mod tests {
         assert_eq!(result, expected.into());
         Ok(())
     }
+
+    #[test]
+    fn test_hexpire_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*6\r\n$7\r\nhexpire\r\n$3\r\nmap\r\n$2\r\n10\r\n$6\r\nFIELDS\r\n$1\r\n1\r\n$5\r\nhello\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: HExpire = frame.try_into()?;
+        assert_eq!(result.key, b"map");
+        assert_eq!(result.seconds, 10);
+        assert_eq!(result.fields, vec!["hello"]);
+
+        buf.extend_from_slice(
+            b"*6\r\n$7\r\nhexpire\r\n$3\r\nmap\r\n$2\r\n10\r\n$6\r\nFIELDS\r\n$1\r\n2\r\n$5\r\nhello\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(HExpire::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hexpire_command() {
+        let backend = Backend::new();
+        for field in ["a", "b"] {
+            backend.hset(
+                b"map".to_vec(),
+                field.to_string(),
+                RespFrame::BulkString(b"value".into()),
+            );
+        }
+
+        let cmd = HExpire {
+            key: b"map".to_vec(),
+            seconds: 0,
+            fields: vec!["a".to_string(), "missing".to_string()],
+        };
+        let expected = RespArray::new([RespFrame::Integer(2), RespFrame::Integer(-2)]);
+        assert_eq!(cmd.execute(&backend), expected.into());
+
+        let cmd = HExpire {
+            key: b"map".to_vec(),
+            seconds: 100,
+            fields: vec!["b".to_string()],
+        };
+        let expected = RespArray::new([RespFrame::Integer(1)]);
+        assert_eq!(cmd.execute(&backend), expected.into());
+
+        assert_eq!(backend.hget(b"map", "a"), None);
+        assert_eq!(
+            backend.hget(b"map", "b"),
+            Some(RespFrame::BulkString(b"value".into()))
+        );
+    }
+
+    #[test]
+    fn test_hexpire_invalid_time_and_wrong_type() {
+        let backend = Backend::new();
+        backend.hset(
+            b"map".to_vec(),
+            "a".to_string(),
+            RespFrame::BulkString(b"value".into()),
+        );
+        backend.set(b"string".to_vec(), BulkString::from("v").into());
+
+        let cmd = HExpire {
+            key: b"map".to_vec(),
+            seconds: u64::MAX,
+            fields: vec!["a".to_string()],
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            CommandError::InvalidExpireTime("hexpire".to_string()).into()
+        );
+        assert_eq!(backend.httl(b"map", "a"), -1);
+
+        let fields = vec!["a".to_string()];
+        let cmd = HExpire {
+            key: b"string".to_vec(),
+            seconds: 100,
+            fields: fields.clone(),
+        };
+        assert_eq!(cmd.execute(&backend), RESP_WRONGTYPE.clone());
+        let cmd = HTtl {
+            key: b"string".to_vec(),
+            fields: fields.clone(),
+        };
+        assert_eq!(cmd.execute(&backend), RESP_WRONGTYPE.clone());
+        let cmd = HPersist {
+            key: b"string".to_vec(),
+            fields,
+        };
+        assert_eq!(cmd.execute(&backend), RESP_WRONGTYPE.clone());
+    }
+
+    #[test]
+    fn test_expired_fields_are_removed_lazily() {
+        let backend = Backend::new();
+        for field in ["a", "b"] {
+            backend.hset(
+                b"map".to_vec(),
+                field.to_string(),
+                RespFrame::BulkString(b"value".into()),
+            );
+        }
+        backend
+            .hexpires
+            .entry(b"map".to_vec())
+            .or_default()
+            .insert("a".to_string(), std::time::Instant::now());
+
+        let cmd = HGetAll {
+            key: b"map".to_vec(),
+            sort: true,
+        };
+        let expected = RespArray::new([
+            RespFrame::BulkString(b"b".into()),
+            RespFrame::BulkString(b"value".into()),
+        ]);
+        assert_eq!(cmd.execute(&backend), expected.into());
+        assert!(!backend.hexpires.contains_key(b"map".as_slice()));
+    }
+
+    #[test]
+    fn test_httl_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$4\r\nhttl\r\n$3\r\nmap\r\n$6\r\nfields\r\n$1\r\n1\r\n$5\r\nhello\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: HTtl = frame.try_into()?;
+        assert_eq!(result.key, b"map");
+        assert_eq!(result.fields, vec!["hello"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_httl_hpersist_commands() {
+        let backend = Backend::new();
+        for field in ["with_ttl", "without_ttl"] {
+            backend.hset(
+                b"map".to_vec(),
+                field.to_string(),
+                RespFrame::BulkString(b"value".into()),
+            );
+        }
+        backend.hexpire(b"map", "with_ttl", Duration::from_secs(100));
+        let fields = vec![
+            "with_ttl".to_string(),
+            "without_ttl".to_string(),
+            "missing".to_string(),
+        ];
+
+        let cmd = HTtl {
+            key: b"map".to_vec(),
+            fields: fields.clone(),
+        };
+        let expected = RespArray::new([
+            RespFrame::Integer(100),
+            RespFrame::Integer(-1),
+            RespFrame::Integer(-2),
+        ]);
+        assert_eq!(cmd.execute(&backend), expected.into());
+
+        let cmd = HPersist {
+            key: b"map".to_vec(),
+            fields: fields.clone(),
+        };
+        let expected = RespArray::new([
+            RespFrame::Integer(1),
+            RespFrame::Integer(-1),
+            RespFrame::Integer(-2),
+        ]);
+        assert_eq!(cmd.execute(&backend), expected.into());
+
+        let cmd = HTtl {
+            key: b"map".to_vec(),
+            fields,
+        };
+        let expected = RespArray::new([
+            RespFrame::Integer(-1),
+            RespFrame::Integer(-1),
+            RespFrame::Integer(-2),
+        ]);
+        assert_eq!(cmd.execute(&backend), expected.into());
+    }
+
+    #[test]
+    fn test_hset_returns_new_field_count() {
+        let backend = Backend::new();
+        let cmd = HSet {
+            key: b"map".to_vec(),
+            fields: vec![("a".to_string(), RespFrame::BulkString(b"1".into()))],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        let cmd = HSet {
+            key: b"map".to_vec(),
+            fields: vec![("a".to_string(), RespFrame::BulkString(b"2".into()))],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        assert_eq!(
+            backend.hget(b"map", "a"),
+            Some(RespFrame::BulkString(b"2".into()))
+        );
+    }
+
+    #[test]
+    fn test_hset_hget_wrong_type() {
+        let backend = Backend::new();
+        backend.set(b"string".to_vec(), BulkString::from("v").into());
+
+        let cmd = HSet {
+            key: b"string".to_vec(),
+            fields: vec![("a".to_string(), RespFrame::BulkString(b"1".into()))],
+        };
+        assert_eq!(cmd.execute(&backend), RESP_WRONGTYPE.clone());
+        assert!(!backend.hmap.contains_key(b"string".as_slice()));
+        assert_eq!(backend.dbsize(), 1);
+
+        let cmd = HGet {
+            key: b"string".to_vec(),
+            field: "a".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RESP_WRONGTYPE.clone());
+    }
 }
@@ -0,0 +1,857 @@
+use std::time::Duration;
+
+use crate::{
+    Backend, BulkString, ReplyBuilder, RespArray, RespFrame, RespNullBulkString, SimpleString,
+    glob_match,
+};
+
+use super::{
+    CommandError, CommandExecutor, DbSize, Del, Exists, Expire, FlushAll, FlushDb, ObjectEncoding,
+    Persist, RESP_OK, Scan, Touch, Ttl, Type, TypeIf, WaitKey, extract_args, parse_integer,
+    validate_command,
+};
+
+impl CommandExecutor for ObjectEncoding {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.object_encoding(&self.key) {
+            Some(encoding) => BulkString::from(encoding).into(),
+            None => RespFrame::NullBulkString(RespNullBulkString),
+        }
+    }
+}
+
+impl CommandExecutor for Type {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        SimpleString::new(backend.type_of(&self.key).unwrap_or("none")).into()
+    }
+}
+
+impl TryFrom<RespArray> for Type {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["type"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(Type {
+                key: key.into_vec(),
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+
+impl CommandExecutor for TypeIf {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let matches = backend
+            .type_of(&self.key)
+            .is_some_and(|t| t.eq_ignore_ascii_case(&self.expected));
+        RespFrame::Integer(matches as i64)
+    }
+}
+
+impl TryFrom<RespArray> for TypeIf {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["typeif"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(expected))) => {
+                Ok(TypeIf {
+                    key: key.into_vec(),
+                    expected: String::from_utf8_lossy(&expected).into_owned(),
+                })
+            }
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+
+impl CommandExecutor for Expire {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.expire(&self.key, self.seconds) as i64)
+    }
+}
+
+impl CommandExecutor for Ttl {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.ttl(&self.key))
+    }
+}
+
+impl CommandExecutor for Persist {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.persist(&self.key) as i64)
+    }
+}
+
+impl TryFrom<RespArray> for Expire {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["expire"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(seconds)) => {
+                // same bound as redis, the deadline has to fit in milliseconds
+                let seconds = parse_integer::<i64>(&seconds)?;
+                if seconds.checked_mul(1000).is_none() {
+                    return Err(CommandError::InvalidExpireTime("expire".to_string()));
+                }
+                Ok(Expire {
+                    key: key.into_vec(),
+                    seconds,
+                })
+            }
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Ttl {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["ttl"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(Ttl {
+                key: key.into_vec(),
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Persist {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["persist"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(Persist {
+                key: key.into_vec(),
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+
+// the network handler awaits WaitKey::wait instead, this only answers without blocking
+impl CommandExecutor for WaitKey {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.type_of(&self.key).is_some() as i64)
+    }
+}
+
+impl WaitKey {
+    pub async fn wait(self, backend: &Backend) -> RespFrame {
+        let found = backend.wait_for_key(&self.key, self.timeout).await;
+        RespFrame::Integer(found as i64)
+    }
+}
+
+impl TryFrom<RespArray> for WaitKey {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["waitkey"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(timeout)) => {
+                // in seconds like the other blocking commands, 0 blocks forever
+                let secs: f64 =
+                    parse_integer(&timeout).map_err(|_| CommandError::InvalidTimeout)?;
+                if secs < 0.0 {
+                    return Err(CommandError::NegativeTimeout);
+                }
+                let timeout =
+                    Duration::try_from_secs_f64(secs).map_err(|_| CommandError::InvalidTimeout)?;
+                Ok(WaitKey {
+                    key: key.into_vec(),
+                    timeout: (!timeout.is_zero()).then_some(timeout),
+                })
+            }
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+
+impl CommandExecutor for FlushDb {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        backend.flush();
+        RESP_OK.clone()
+    }
+}
+
+impl CommandExecutor for FlushAll {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        backend.flush();
+        RESP_OK.clone()
+    }
+}
+
+impl CommandExecutor for DbSize {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.dbsize() as i64)
+    }
+}
+
+impl CommandExecutor for Del {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let removed = self.keys.iter().filter(|key| backend.del(key)).count();
+        RespFrame::Integer(removed as i64)
+    }
+}
+
+impl TryFrom<RespArray> for Del {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["del"], 1)
+            .or_else(|_| validate_command(&value, &["unlink"], 1))?;
+        Ok(Del {
+            keys: key_args(value)?,
+        })
+    }
+}
+
+impl CommandExecutor for Exists {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let found = self.keys.iter().filter(|key| backend.exists(key)).count();
+        RespFrame::Integer(found as i64)
+    }
+}
+
+impl TryFrom<RespArray> for Exists {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["exists"], 1)?;
+        Ok(Exists {
+            keys: key_args(value)?,
+        })
+    }
+}
+
+impl CommandExecutor for Touch {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let found = self.keys.iter().filter(|key| backend.exists(key)).count();
+        RespFrame::Integer(found as i64)
+    }
+}
+
+impl TryFrom<RespArray> for Touch {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["touch"], 1)?;
+        Ok(Touch {
+            keys: key_args(value)?,
+        })
+    }
+}
+
+// every argument after the command name is a key
+fn key_args(value: RespArray) -> Result<Vec<Vec<u8>>, CommandError> {
+    extract_args(value, 1)?
+        .into_iter()
+        .map(|frame| match frame {
+            RespFrame::BulkString(key) => Ok(key.into_vec()),
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        })
+        .collect()
+}
+
+impl TryFrom<RespArray> for DbSize {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["dbsize"], 0)?;
+        Ok(DbSize)
+    }
+}
+
+impl TryFrom<RespArray> for FlushDb {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["flushdb"], 0)?;
+        validate_flush_mode(value)?;
+        Ok(FlushDb)
+    }
+}
+
+impl TryFrom<RespArray> for FlushAll {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["flushall"], 0)?;
+        validate_flush_mode(value)?;
+        Ok(FlushAll)
+    }
+}
+
+fn validate_flush_mode(value: RespArray) -> Result<(), CommandError> {
+    let args = extract_args(value, 1)?;
+    match args.as_slice() {
+        [] => Ok(()),
+        [RespFrame::BulkString(mode)]
+            if mode.eq_ignore_ascii_case(b"async") || mode.eq_ignore_ascii_case(b"sync") =>
+        {
+            Ok(())
+        }
+        _ => Err(CommandError::SyntaxError),
+    }
+}
+
+impl CommandExecutor for Scan {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let (cursor, keys) = backend.scan(self.cursor, self.count);
+        // like redis, MATCH filters the batch after it is picked
+        let keys = keys
+            .into_iter()
+            .filter(|key| self.pattern.as_ref().is_none_or(|p| glob_match(p, key)));
+        ReplyBuilder::new()
+            .bulk(cursor.to_string())
+            .frame(RespArray::from_iter_bulk(keys))
+            .build()
+    }
+}
+
+impl TryFrom<RespArray> for Scan {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["scan"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let cursor = args
+            .next()
+            .map(|frame| parse_integer(&frame))
+            .transpose()
+            .map_err(|_| CommandError::InvalidArgument("invalid cursor".to_string()))?
+            .unwrap_or_default();
+        let mut scan = Scan {
+            cursor,
+            pattern: None,
+            count: 10,
+        };
+        while let Some(option) = args.next() {
+            let (RespFrame::BulkString(option), Some(arg)) = (option, args.next()) else {
+                return Err(CommandError::SyntaxError);
+            };
+            match option.to_ascii_lowercase().as_slice() {
+                b"match" => match arg {
+                    RespFrame::BulkString(pattern) => scan.pattern = Some(pattern.into_vec()),
+                    _ => return Err(CommandError::SyntaxError),
+                },
+                b"count" => match parse_integer::<usize>(&arg)? {
+                    0 => return Err(CommandError::SyntaxError),
+                    count => scan.count = count,
+                },
+                _ => return Err(CommandError::SyntaxError),
+            }
+        }
+        Ok(scan)
+    }
+}
+
+impl TryFrom<RespArray> for ObjectEncoding {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["object", "encoding"], 1)?;
+
+        let mut args = extract_args(value, 2)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(ObjectEncoding {
+                key: key.into_vec(),
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RespDecode;
+    use crate::cmd::Command;
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+    use std::collections::HashMap;
+    use std::sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    };
+
+    #[test]
+    fn test_typeif() -> Result<()> {
+        let backend = Backend::new();
+        backend.set(b"string".to_vec(), BulkString::from("v").into());
+        backend.hset(
+            b"hash".to_vec(),
+            "f".to_string(),
+            BulkString::from("v").into(),
+        );
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$6\r\ntypeif\r\n$6\r\nstring\r\n$6\r\nstring\r\n");
+        let cmd: Command = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        let typeif = |key: &[u8], expected: &str| {
+            TypeIf {
+                key: key.to_vec(),
+                expected: expected.to_string(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(typeif(b"hash", "HASH"), RespFrame::Integer(1));
+        assert_eq!(typeif(b"hash", "string"), RespFrame::Integer(0));
+        assert_eq!(typeif(b"missing", "string"), RespFrame::Integer(0));
+        assert_eq!(typeif(b"missing", "none"), RespFrame::Integer(0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_encoding_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$6\r\nobject\r\n$8\r\nencoding\r\n$3\r\nmap\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: ObjectEncoding = frame.try_into()?;
+        assert_eq!(result.key, b"map");
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_encoding_string() {
+        let backend = Backend::new();
+        backend.set(b"int".to_vec(), RespFrame::BulkString(b"12345".into()));
+        backend.set(b"str".to_vec(), RespFrame::BulkString(b"hello".into()));
+        backend.set(
+            b"raw".to_vec(),
+            RespFrame::BulkString(BulkString::new(vec![b'a'; 45])),
+        );
+
+        let encoding = |key: &[u8]| ObjectEncoding { key: key.to_vec() }.execute(&backend);
+        assert_eq!(encoding(b"int"), RespFrame::BulkString(b"int".into()));
+        assert_eq!(encoding(b"str"), RespFrame::BulkString(b"embstr".into()));
+        assert_eq!(encoding(b"raw"), RespFrame::BulkString(b"raw".into()));
+        assert_eq!(
+            encoding(b"missing"),
+            RespFrame::NullBulkString(RespNullBulkString)
+        );
+    }
+
+    #[test]
+    fn test_object_encoding_hash_grows_to_hashtable() {
+        let backend = Backend::new();
+        backend
+            .set_config("hash-max-listpack-entries", "2")
+            .unwrap();
+
+        for field in ["a", "b"] {
+            backend.hset(
+                b"map".to_vec(),
+                field.to_string(),
+                RespFrame::BulkString(b"value".into()),
+            );
+        }
+        let cmd = ObjectEncoding {
+            key: b"map".to_vec(),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespFrame::BulkString(b"listpack".into())
+        );
+
+        backend.hset(
+            b"map".to_vec(),
+            "c".to_string(),
+            RespFrame::BulkString(b"value".into()),
+        );
+        let cmd = ObjectEncoding {
+            key: b"map".to_vec(),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespFrame::BulkString(b"hashtable".into())
+        );
+    }
+
+    #[test]
+    fn test_object_encoding_set_grows_to_hashtable() {
+        let backend = Backend::new();
+        backend.set_config("set-max-listpack-entries", "1").unwrap();
+
+        backend.add_member(b"set".to_vec(), "a".to_string());
+        let encoding = || {
+            ObjectEncoding {
+                key: b"set".to_vec(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(encoding(), RespFrame::BulkString(b"listpack".into()));
+
+        backend.add_member(b"set".to_vec(), "b".to_string());
+        assert_eq!(encoding(), RespFrame::BulkString(b"hashtable".into()));
+    }
+
+    #[test]
+    fn test_flush_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$7\r\nflushdb\r\n$5\r\nASYNC\r\n");
+        buf.extend_from_slice(b"*2\r\n$7\r\nflushdb\r\n$4\r\nsync\r\n");
+        buf.extend_from_slice(b"*2\r\n$8\r\nflushall\r\n$5\r\nasync\r\n");
+        buf.extend_from_slice(b"*2\r\n$7\r\nflushdb\r\n$4\r\nlazy\r\n");
+
+        FlushDb::try_from(RespArray::decode(&mut buf)?)?;
+        FlushDb::try_from(RespArray::decode(&mut buf)?)?;
+        FlushAll::try_from(RespArray::decode(&mut buf)?)?;
+        let ret = FlushDb::try_from(RespArray::decode(&mut buf)?);
+        assert_eq!(ret.unwrap_err().to_string(), "ERR syntax error");
+        Ok(())
+    }
+
+    #[test]
+    fn test_flushdb_command() {
+        let backend = Backend::new();
+        backend.set(b"key".to_vec(), RespFrame::BulkString(b"value".into()));
+        backend.add_member(b"set".to_vec(), "member".to_string());
+
+        assert_eq!(FlushDb.execute(&backend), RESP_OK.clone());
+        assert_eq!(backend.type_of(b"key"), None);
+        assert_eq!(backend.type_of(b"set"), None);
+    }
+
+    #[test]
+    fn test_del_counts_removed_keys() -> Result<()> {
+        let backend = Backend::new();
+        backend.set(b"string".to_vec(), RespFrame::BulkString(b"value".into()));
+        backend.hset(
+            b"map".to_vec(),
+            "field".to_string(),
+            RespFrame::BulkString(b"value".into()),
+        );
+        backend.add_member(b"set".to_vec(), "member".to_string());
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$3\r\ndel\r\n$6\r\nstring\r\n$7\r\nmissing\r\n");
+        let cmd = Del::try_from(RespArray::decode(&mut buf)?)?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.type_of(b"string"), None);
+
+        let cmd = Del {
+            keys: vec![b"map".to_vec(), b"set".to_vec(), b"map".to_vec()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        assert_eq!(backend.dbsize(), 0);
+
+        buf.extend_from_slice(b"*1\r\n$3\r\ndel\r\n");
+        assert!(Del::try_from(RespArray::decode(&mut buf)?).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_unlink_behaves_like_del() -> Result<()> {
+        let mut replies = Vec::new();
+        for name in ["del", "UNLINK"] {
+            let backend = Backend::new();
+            backend.set(b"string".to_vec(), RespFrame::BulkString(b"value".into()));
+            backend.add_member(b"set".to_vec(), "member".to_string());
+            backend.expire(b"set", 100);
+
+            let args = [name, "string", "set", "string", "missing"];
+            let cmd: Command =
+                RespArray::new(args.map(|arg| RespFrame::BulkString(arg.into())).to_vec())
+                    .try_into()?;
+            assert_eq!(cmd.command_name(), "del");
+            replies.push(cmd.execute(&backend));
+            assert_eq!(backend.dbsize(), 0);
+            assert_eq!(backend.ttl(b"set"), -2);
+        }
+        assert_eq!(replies, [RespFrame::Integer(2), RespFrame::Integer(2)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_touch_counts_existing_keys() -> Result<()> {
+        let backend = Backend::new();
+        backend.set(b"foo".to_vec(), RespFrame::BulkString(b"value".into()));
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$5\r\ntouch\r\n$3\r\nfoo\r\n$3\r\nfoo\r\n$3\r\nbar\r\n");
+        let cmd: Command = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        assert_eq!(
+            backend.get(b"foo"),
+            Some(RespFrame::BulkString(b"value".into()))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_exists_counts_every_listed_key() -> Result<()> {
+        let backend = Backend::new();
+        backend.set(b"foo".to_vec(), RespFrame::BulkString(b"value".into()));
+        backend.hset(
+            b"map".to_vec(),
+            "field".to_string(),
+            RespFrame::BulkString(b"value".into()),
+        );
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$6\r\nexists\r\n$3\r\nfoo\r\n$3\r\nfoo\r\n$7\r\nmissing\r\n$3\r\nmap\r\n",
+        );
+        let cmd = Exists::try_from(RespArray::decode(&mut buf)?)?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(3));
+
+        let cmd = Exists {
+            keys: vec![b"missing".to_vec()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        assert!(backend.exists(b"map"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_expire_ttl_countdown() -> Result<()> {
+        let backend = Backend::new();
+        backend.set(b"key".to_vec(), RespFrame::BulkString(b"value".into()));
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$6\r\nexpire\r\n$3\r\nkey\r\n$1\r\n1\r\n");
+        let cmd: Command = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        buf.extend_from_slice(b"*2\r\n$3\r\nttl\r\n$3\r\nkey\r\n");
+        let cmd: Command = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        std::thread::sleep(Duration::from_millis(600));
+        let ttl = Ttl {
+            key: b"key".to_vec(),
+        };
+        assert_eq!(ttl.execute(&backend), RespFrame::Integer(0));
+
+        // gone for good once the reaper ran, without anyone reading the key
+        std::thread::sleep(Duration::from_millis(600));
+        assert!(!backend.map.contains_key(b"key".as_slice()));
+        let ttl = Ttl {
+            key: b"key".to_vec(),
+        };
+        assert_eq!(ttl.execute(&backend), RespFrame::Integer(-2));
+        let persist = Persist {
+            key: b"key".to_vec(),
+        };
+        assert_eq!(persist.execute(&backend), RespFrame::Integer(0));
+
+        buf.extend_from_slice(b"*3\r\n$6\r\nexpire\r\n$3\r\nkey\r\n$19\r\n9223372036854775807\r\n");
+        let ret: Result<Expire, _> = RespArray::decode(&mut buf)?.try_into();
+        assert!(matches!(ret, Err(CommandError::InvalidExpireTime(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_expiry_applies_to_every_type() {
+        let backend = Backend::new();
+        backend.set(b"string".to_vec(), RespFrame::BulkString(b"value".into()));
+        backend.hset(
+            b"hash".to_vec(),
+            "field".to_string(),
+            RespFrame::BulkString(b"value".into()),
+        );
+        backend.add_member(b"set".to_vec(), "member".to_string());
+        backend.zadd(b"zset".to_vec(), vec![(1.0, "member".to_string())]);
+        backend.push(b"list".to_vec(), vec![b"a".to_vec()], crate::ListEnd::Left);
+
+        let keys = [b"string".as_slice(), b"hash", b"set", b"zset", b"list"];
+        for key in keys {
+            let cmd = Expire {
+                key: key.to_vec(),
+                seconds: 100,
+            };
+            assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+            let ttl = Ttl { key: key.to_vec() };
+            assert_eq!(ttl.execute(&backend), RespFrame::Integer(100));
+        }
+        let persist = Persist {
+            key: b"zset".to_vec(),
+        };
+        assert_eq!(persist.execute(&backend), RespFrame::Integer(1));
+
+        for key in keys {
+            backend.expire_at(key, std::time::Instant::now());
+            let ttl = Ttl { key: key.to_vec() };
+            assert_eq!(ttl.execute(&backend), RespFrame::Integer(-2));
+        }
+        assert_eq!(backend.dbsize(), 0);
+        assert!(backend.expires.is_empty());
+    }
+
+    #[test]
+    fn test_emptied_hash_leaves_no_key_behind() -> Result<()> {
+        let backend = Backend::new();
+        backend.set(b"key".to_vec(), RespFrame::BulkString(b"value".into()));
+        backend.hset(
+            b"map".to_vec(),
+            "field".to_string(),
+            RespFrame::BulkString(b"value".into()),
+        );
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*1\r\n$6\r\ndbsize\r\n");
+        let cmd = DbSize::try_from(RespArray::decode(&mut buf)?)?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+
+        // a zero TTL deletes the last field right away
+        assert_eq!(backend.hexpire(b"map", "field", Duration::ZERO), Some(2));
+        assert_eq!(backend.type_of(b"map"), None);
+        assert_eq!(DbSize.execute(&backend), RespFrame::Integer(1));
+        let (_, keys) = backend.scan(0, 10);
+        assert_eq!(keys, vec![b"key".to_vec()]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_emptying_a_collection_removes_the_key() -> Result<()> {
+        let run = |backend: &Backend, args: &[&str]| -> Result<RespFrame> {
+            let args = args.iter().map(|arg| RespFrame::from(arg.as_bytes()));
+            let cmd = Command::try_from(RespArray::new(args.collect::<Vec<_>>()))?;
+            Ok(cmd.execute(backend))
+        };
+        for (fill, empty) in [
+            (&["sadd", "k", "m"][..], &["srem", "k", "m"][..]),
+            (&["hset", "k", "f", "v"], &["hdel", "k", "f"]),
+            (&["rpush", "k", "a"], &["lpop", "k"]),
+            (&["rpush", "k", "a"], &["rpop", "k"]),
+            (&["zadd", "k", "1", "m"], &["zpopmin", "k"]),
+            (&["zadd", "k", "1", "m"], &["zpopmax", "k"]),
+        ] {
+            let backend = Backend::new();
+            run(&backend, fill)?;
+            assert_eq!(run(&backend, &["exists", "k"])?, RespFrame::Integer(1));
+            run(&backend, empty)?;
+            assert_eq!(
+                run(&backend, &["exists", "k"])?,
+                RespFrame::Integer(0),
+                "{} left the key",
+                empty[0]
+            );
+            assert_eq!(
+                run(&backend, &["type", "k"])?,
+                SimpleString::new("none").into()
+            );
+            assert_eq!(run(&backend, &["dbsize"])?, RespFrame::Integer(0));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*6\r\n$4\r\nscan\r\n$1\r\n0\r\n$5\r\nMATCH\r\n$2\r\nk*\r\n$5\r\ncount\r\n$2\r\n20\r\n",
+        );
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Scan = frame.try_into()?;
+        assert_eq!(result.cursor, 0);
+        assert_eq!(result.pattern, Some(b"k*".to_vec()));
+        assert_eq!(result.count, 20);
+
+        buf.extend_from_slice(b"*3\r\n$4\r\nscan\r\n$1\r\n0\r\n$5\r\ncount\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Scan::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_command_with_match() {
+        let backend = Backend::new();
+        for key in ["key1", "key2", "other"] {
+            backend.set(key.as_bytes().to_vec(), RespFrame::BulkString(b"v".into()));
+        }
+
+        let cmd = Scan {
+            cursor: 0,
+            pattern: Some(b"key*".to_vec()),
+            count: 10,
+        };
+        let RespFrame::Array(reply) = cmd.execute(&backend) else {
+            panic!("scan should reply with an array");
+        };
+        assert_eq!(reply[0], RespFrame::BulkString(b"0".into()));
+        let RespFrame::Array(ref keys) = reply[1] else {
+            panic!("scan keys should be an array");
+        };
+        let mut keys = keys.to_vec();
+        keys.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(
+            keys,
+            vec![
+                RespFrame::BulkString(b"key1".into()),
+                RespFrame::BulkString(b"key2".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_returns_long_lived_keys_once_under_writes() {
+        let backend = Backend::new();
+        for i in 0..1000 {
+            backend.set(
+                format!("stable:{}", i).into_bytes(),
+                RespFrame::BulkString(b"v".into()),
+            );
+        }
+
+        let done = Arc::new(AtomicBool::new(false));
+        let writer = {
+            let backend = backend.clone();
+            let done = done.clone();
+            std::thread::spawn(move || {
+                let mut i = 0;
+                while !done.load(Ordering::Relaxed) {
+                    let key = format!("temp:{}", i % 2000).into_bytes();
+                    backend.set(key.clone(), RespFrame::BulkString(b"v".into()));
+                    if i % 2 == 0 {
+                        backend.del(&key);
+                    }
+                    i += 1;
+                }
+            })
+        };
+
+        let mut seen: HashMap<Vec<u8>, usize> = HashMap::new();
+        let mut cursor = 0;
+        loop {
+            let (next, keys) = backend.scan(cursor, 7);
+            for key in keys.into_iter().filter(|k| k.starts_with(b"stable:")) {
+                *seen.entry(key).or_default() += 1;
+            }
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+        done.store(true, Ordering::Relaxed);
+        writer.join().unwrap();
+
+        assert_eq!(seen.len(), 1000);
+        assert!(seen.values().all(|&n| n == 1));
+    }
+}
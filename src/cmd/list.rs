@@ -0,0 +1,578 @@
+use crate::{
+    Backend, BulkString, ListEnd, RespArray, RespFrame, RespNullArray, RespNullBulkString,
+};
+
+use super::{
+    CommandError, CommandExecutor, LInsert, LMove, LPop, LPush, LRange, LSet, RESP_OK,
+    RESP_REPLY_TOO_LARGE, RESP_WRONGTYPE, RPop, RPopLPush, RPush, extract_args, is_wrong_type,
+    parse_integer, reply_too_large, validate_command,
+};
+
+impl CommandExecutor for LPush {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        push(backend, self.key, self.values, ListEnd::Left)
+    }
+}
+
+impl CommandExecutor for RPush {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        push(backend, self.key, self.values, ListEnd::Right)
+    }
+}
+
+// checked before touching the list map, a key of another type must never get a list
+// living next to it under the same name
+fn push(backend: &Backend, key: Vec<u8>, values: Vec<Vec<u8>>, end: ListEnd) -> RespFrame {
+    if is_wrong_type(backend, &key, "list") {
+        return RESP_WRONGTYPE.clone();
+    }
+    RespFrame::Integer(backend.push(key, values, end) as i64)
+}
+
+impl CommandExecutor for LPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        pop(backend, &self.key, self.count, ListEnd::Left)
+    }
+}
+
+impl CommandExecutor for RPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        pop(backend, &self.key, self.count, ListEnd::Right)
+    }
+}
+
+// a missing key is a nil in the shape of the reply: a null bulk string for a single
+// element and a null array when a count was given, as RESP2 clients expect
+fn pop(backend: &Backend, key: &[u8], count: Option<usize>, end: ListEnd) -> RespFrame {
+    if is_wrong_type(backend, key, "list") {
+        return RESP_WRONGTYPE.clone();
+    }
+    match count {
+        None => match backend.pop(key, end, 1).and_then(|mut v| v.pop()) {
+            Some(element) => BulkString::new(element).into(),
+            None => RespFrame::NullBulkString(RespNullBulkString),
+        },
+        Some(count) => match backend.pop(key, end, count) {
+            Some(elements) => RespArray::from_iter_bulk(elements.into_iter()).into(),
+            None => RespFrame::NullArray(RespNullArray),
+        },
+    }
+}
+
+impl CommandExecutor for LRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "list") {
+            return RESP_WRONGTYPE.clone();
+        }
+        let elements = backend.lrange(&self.key, self.start, self.stop);
+        if reply_too_large(backend, elements.len()) {
+            return RESP_REPLY_TOO_LARGE.clone();
+        }
+        RespArray::from_iter_bulk(elements.into_iter()).into()
+    }
+}
+
+impl CommandExecutor for LSet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "list") {
+            return RESP_WRONGTYPE.clone();
+        }
+        match backend.lset(&self.key, self.index, self.value) {
+            Some(true) => RESP_OK.clone(),
+            Some(false) => CommandError::IndexOutOfRange.into(),
+            None => CommandError::NoSuchKey.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LInsert {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "list") {
+            return RESP_WRONGTYPE.clone();
+        }
+        RespFrame::Integer(backend.linsert(&self.key, self.after, &self.pivot, self.value))
+    }
+}
+
+impl CommandExecutor for RPopLPush {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        lmove(
+            backend,
+            &self.source,
+            &self.destination,
+            ListEnd::Right,
+            ListEnd::Left,
+        )
+    }
+}
+
+impl CommandExecutor for LMove {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        lmove(backend, &self.source, &self.destination, self.from, self.to)
+    }
+}
+
+// both keys are checked up front, an element is never popped without a list to land in
+fn lmove(backend: &Backend, src: &[u8], dst: &[u8], from: ListEnd, to: ListEnd) -> RespFrame {
+    if is_wrong_type(backend, src, "list") || is_wrong_type(backend, dst, "list") {
+        return RESP_WRONGTYPE.clone();
+    }
+    match backend.lmove(src, dst, from, to) {
+        Some(element) => BulkString::new(element).into(),
+        None => RespFrame::NullBulkString(RespNullBulkString),
+    }
+}
+
+impl TryFrom<RespArray> for LPush {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["lpush"], 2)?;
+        let (key, values) = key_and_values(value)?;
+        Ok(LPush { key, values })
+    }
+}
+
+impl TryFrom<RespArray> for RPush {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["rpush"], 2)?;
+        let (key, values) = key_and_values(value)?;
+        Ok(RPush { key, values })
+    }
+}
+
+fn key_and_values(value: RespArray) -> Result<(Vec<u8>, Vec<Vec<u8>>), CommandError> {
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = match args.next() {
+        Some(RespFrame::BulkString(key)) => key.into_vec(),
+        _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+    };
+    let values = args
+        .map(|frame| match frame {
+            RespFrame::BulkString(value) => Ok(value.into_vec()),
+            _ => Err(CommandError::InvalidArgument("Invalid value".to_string())),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((key, values))
+}
+
+impl TryFrom<RespArray> for LPop {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["lpop"], 1)?;
+        let (key, count) = key_and_count(value)?;
+        Ok(LPop { key, count })
+    }
+}
+
+impl TryFrom<RespArray> for RPop {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["rpop"], 1)?;
+        let (key, count) = key_and_count(value)?;
+        Ok(RPop { key, count })
+    }
+}
+
+fn key_and_count(value: RespArray) -> Result<(Vec<u8>, Option<usize>), CommandError> {
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = match args.next() {
+        Some(RespFrame::BulkString(key)) => key.into_vec(),
+        _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+    };
+    let count = match (args.next(), args.next()) {
+        (None, _) => None,
+        (Some(count), None) => match parse_integer::<i64>(&count)? {
+            n if n < 0 => return Err(CommandError::NotPositive),
+            n => Some(n as usize),
+        },
+        _ => return Err(CommandError::SyntaxError),
+    };
+    Ok((key, count))
+}
+
+impl TryFrom<RespArray> for LRange {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["lrange"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(start), Some(stop)) => Ok(LRange {
+                key: key.into_vec(),
+                start: parse_integer(&start)?,
+                stop: parse_integer(&stop)?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for LSet {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["lset"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(index), Some(RespFrame::BulkString(value))) => {
+                Ok(LSet {
+                    key: key.into_vec(),
+                    index: parse_integer(&index)?,
+                    value: value.into_vec(),
+                })
+            }
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for LInsert {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["linsert"], 4)?;
+
+        let (key, mut args) = key_and_values(value)?;
+        let (Some(value), Some(pivot), Some(position)) = (args.pop(), args.pop(), args.pop())
+        else {
+            return Err(CommandError::SyntaxError);
+        };
+        let after = match position.to_ascii_lowercase().as_slice() {
+            b"before" => false,
+            b"after" => true,
+            _ => return Err(CommandError::SyntaxError),
+        };
+        Ok(LInsert {
+            key,
+            after,
+            pivot,
+            value,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for RPopLPush {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["rpoplpush"], 2)?;
+
+        let (source, mut args) = key_and_values(value)?;
+        match args.pop() {
+            Some(destination) => Ok(RPopLPush {
+                source,
+                destination,
+            }),
+            None => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for LMove {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["lmove"], 4)?;
+
+        let (source, mut args) = key_and_values(value)?;
+        let (Some(to), Some(from), Some(destination)) = (args.pop(), args.pop(), args.pop()) else {
+            return Err(CommandError::SyntaxError);
+        };
+        Ok(LMove {
+            source,
+            destination,
+            from: list_end(&from)?,
+            to: list_end(&to)?,
+        })
+    }
+}
+
+fn list_end(arg: &[u8]) -> Result<ListEnd, CommandError> {
+    match arg.to_ascii_lowercase().as_slice() {
+        b"left" => Ok(ListEnd::Left),
+        b"right" => Ok(ListEnd::Right),
+        _ => Err(CommandError::SyntaxError),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{RespDecode, RespEncode, SimpleError};
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_push_and_lrange() -> Result<()> {
+        let backend = Backend::new();
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$5\r\nlpush\r\n$4\r\nlist\r\n$1\r\na\r\n$1\r\nb\r\n");
+        let cmd: LPush = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+
+        buf.extend_from_slice(b"*3\r\n$5\r\nrpush\r\n$4\r\nlist\r\n$1\r\nc\r\n");
+        let cmd: RPush = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(3));
+
+        buf.extend_from_slice(b"*4\r\n$6\r\nlrange\r\n$4\r\nlist\r\n$1\r\n0\r\n$2\r\n-1\r\n");
+        let cmd: LRange = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::from_iter_bulk([b"b".to_vec(), b"a".to_vec(), b"c".to_vec()].into_iter())
+                .into()
+        );
+
+        let cmd = LRange {
+            key: b"list".to_vec(),
+            start: -2,
+            stop: 10,
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::from_iter_bulk([b"a".to_vec(), b"c".to_vec()].into_iter()).into()
+        );
+        assert_eq!(backend.type_of(b"list"), Some("list"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_lpush_on_string_key() {
+        let backend = Backend::new();
+        backend.set(b"key".to_vec(), BulkString::from("value").into());
+
+        let cmd = LPush {
+            key: b"key".to_vec(),
+            values: vec![b"a".to_vec()],
+        };
+        assert_eq!(cmd.execute(&backend), RESP_WRONGTYPE.clone());
+        assert!(backend.list.get(b"key".as_slice()).is_none());
+        assert_eq!(backend.type_of(b"key"), Some("string"));
+    }
+
+    #[test]
+    fn test_lset() -> Result<()> {
+        let backend = Backend::new();
+        backend.push(
+            b"list".to_vec(),
+            vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()],
+            ListEnd::Right,
+        );
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$4\r\nlset\r\n$4\r\nlist\r\n$1\r\n1\r\n$1\r\nx\r\n");
+        let cmd: LSet = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+
+        let cmd = LSet {
+            key: b"list".to_vec(),
+            index: -1,
+            value: b"z".to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        assert_eq!(
+            backend.lrange(b"list", 0, -1),
+            [b"a".to_vec(), b"x".to_vec(), b"z".to_vec()]
+        );
+
+        for index in [3, -4] {
+            let cmd = LSet {
+                key: b"list".to_vec(),
+                index,
+                value: b"y".to_vec(),
+            };
+            assert_eq!(
+                cmd.execute(&backend),
+                SimpleError::new("ERR index out of range").into()
+            );
+        }
+        let cmd = LSet {
+            key: b"missing".to_vec(),
+            index: 0,
+            value: b"y".to_vec(),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            SimpleError::new("ERR no such key").into()
+        );
+        assert_eq!(backend.type_of(b"missing"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_linsert() -> Result<()> {
+        let backend = Backend::new();
+        backend.push(
+            b"list".to_vec(),
+            vec![b"a".to_vec(), b"c".to_vec(), b"a".to_vec()],
+            ListEnd::Right,
+        );
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$7\r\nlinsert\r\n$4\r\nlist\r\n$6\r\nBEFORE\r\n$1\r\nc\r\n$1\r\nb\r\n",
+        );
+        let cmd: LInsert = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(4));
+
+        let cmd = LInsert {
+            key: b"list".to_vec(),
+            after: true,
+            pivot: b"a".to_vec(),
+            value: b"z".to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(5));
+        assert_eq!(
+            backend.lrange(b"list", 0, -1),
+            [b"a", b"z", b"b", b"c", b"a"].map(|e| e.to_vec())
+        );
+
+        let cmd = LInsert {
+            key: b"list".to_vec(),
+            after: false,
+            pivot: b"missing".to_vec(),
+            value: b"y".to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-1));
+        let cmd = LInsert {
+            key: b"nolist".to_vec(),
+            after: false,
+            pivot: b"a".to_vec(),
+            value: b"y".to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        assert_eq!(backend.type_of(b"nolist"), None);
+
+        buf.extend_from_slice(
+            b"*5\r\n$7\r\nlinsert\r\n$4\r\nlist\r\n$5\r\nbefor\r\n$1\r\nc\r\n$1\r\nb\r\n",
+        );
+        let ret: Result<LInsert, _> = RespArray::decode(&mut buf)?.try_into();
+        assert!(matches!(ret, Err(CommandError::SyntaxError)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_rpoplpush_across_lists() -> Result<()> {
+        let backend = Backend::new();
+        backend.push(
+            b"src".to_vec(),
+            vec![b"a".to_vec(), b"b".to_vec()],
+            ListEnd::Right,
+        );
+        backend.push(b"dst".to_vec(), vec![b"x".to_vec()], ListEnd::Right);
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$9\r\nrpoplpush\r\n$3\r\nsrc\r\n$3\r\ndst\r\n");
+        let cmd: RPopLPush = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), BulkString::from("b").into());
+        assert_eq!(backend.lrange(b"src", 0, -1), [b"a".to_vec()]);
+        assert_eq!(
+            backend.lrange(b"dst", 0, -1),
+            [b"b".to_vec(), b"x".to_vec()]
+        );
+
+        // moving the last element drops the source and creates the destination
+        buf.extend_from_slice(
+            b"*5\r\n$5\r\nlmove\r\n$3\r\nsrc\r\n$3\r\nnew\r\n$4\r\nLEFT\r\n$5\r\nright\r\n",
+        );
+        let cmd: LMove = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), BulkString::from("a").into());
+        assert_eq!(backend.type_of(b"src"), None);
+        assert_eq!(backend.lrange(b"new", 0, -1), [b"a".to_vec()]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_lmove_rotates_the_same_list() {
+        let backend = Backend::new();
+        backend.push(
+            b"list".to_vec(),
+            vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()],
+            ListEnd::Right,
+        );
+
+        let cmd = LMove {
+            source: b"list".to_vec(),
+            destination: b"list".to_vec(),
+            from: ListEnd::Left,
+            to: ListEnd::Right,
+        };
+        assert_eq!(cmd.execute(&backend), BulkString::from("a").into());
+        let cmd = RPopLPush {
+            source: b"list".to_vec(),
+            destination: b"list".to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), BulkString::from("a").into());
+        assert_eq!(
+            backend.lrange(b"list", 0, -1),
+            [b"a", b"b", b"c"].map(|e| e.to_vec())
+        );
+    }
+
+    #[test]
+    fn test_lmove_from_empty_source() {
+        let backend = Backend::new();
+        backend.set(b"string".to_vec(), BulkString::from("value").into());
+
+        let cmd = RPopLPush {
+            source: b"missing".to_vec(),
+            destination: b"dst".to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend).encode(), b"$-1\r\n");
+        assert_eq!(backend.type_of(b"dst"), None);
+
+        let cmd = LMove {
+            source: b"missing".to_vec(),
+            destination: b"string".to_vec(),
+            from: ListEnd::Left,
+            to: ListEnd::Left,
+        };
+        assert_eq!(cmd.execute(&backend), RESP_WRONGTYPE.clone());
+    }
+
+    #[test]
+    fn test_pop_nil_replies_under_resp2() -> Result<()> {
+        let backend = Backend::new();
+        backend.push(
+            b"list".to_vec(),
+            vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()],
+            ListEnd::Right,
+        );
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$4\r\nlpop\r\n$4\r\nlist\r\n");
+        let cmd: LPop = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), BulkString::from("a").into());
+
+        buf.extend_from_slice(b"*3\r\n$4\r\nrpop\r\n$4\r\nlist\r\n$1\r\n5\r\n");
+        let cmd: RPop = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::from_iter_bulk([b"c".to_vec(), b"b".to_vec()].into_iter()).into()
+        );
+        assert_eq!(backend.type_of(b"list"), None);
+
+        let cmd = LPop {
+            key: b"list".to_vec(),
+            count: None,
+        };
+        assert_eq!(cmd.execute(&backend).encode(), b"$-1\r\n");
+        let cmd = LPop {
+            key: b"list".to_vec(),
+            count: Some(2),
+        };
+        assert_eq!(cmd.execute(&backend).encode(), b"*-1\r\n");
+
+        buf.extend_from_slice(b"*3\r\n$4\r\nlpop\r\n$4\r\nlist\r\n$2\r\n-1\r\n");
+        let ret: Result<LPop, _> = RespArray::decode(&mut buf)?.try_into();
+        assert!(matches!(ret, Err(CommandError::NotPositive)));
+        Ok(())
+    }
+}
@@ -1,24 +1,173 @@
-use super::{CommandError, CommandExecutor, Get, RESP_OK, Set, extract_args, validate_command};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use super::{
+    Append, BitPos, BitUnit, CommandError, CommandExecutor, Decr, DecrBy, Get, GetBit, GetDel,
+    GetSet, Incr, IncrBy, Lcs, MGet, MSet, RESP_OK, RESP_WRONGTYPE, Set, SetBit, SetCondition,
+    SetExpiry, SetNx, SetOptions, SetRange, Strlen, extract_args, is_wrong_type, parse_integer,
+    validate_command,
+};
 use crate::RespArray;
-use crate::RespNull;
-use crate::{RespFrame, backend::Backend};
+use crate::RespNullBulkString;
+use crate::{BulkString, RespFrame, backend::Backend};
 
 impl CommandExecutor for Get {
     fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "string") {
+            return RESP_WRONGTYPE.clone();
+        }
         match backend.get(&self.key) {
             Some(value) => value,
-            None => RespFrame::Null(RespNull),
+            None => RespFrame::NullBulkString(RespNullBulkString),
         }
     }
 }
 
 impl CommandExecutor for Set {
     fn execute(self, backend: &Backend) -> RespFrame {
-        backend.set(self.key, self.value);
+        let deadline = match self.options.expiry {
+            None => None,
+            Some(SetExpiry::KeepTtl) => backend.deadline(&self.key),
+            Some(expiry) => match expiry.deadline() {
+                Some(deadline) => Some(deadline),
+                None => return CommandError::InvalidExpireTime("set".to_string()).into(),
+            },
+        };
+        let Some(condition) = self.options.condition else {
+            backend.set_with_deadline(self.key, self.value, deadline);
+            return RESP_OK.clone();
+        };
+        let exists = condition == SetCondition::IfExists;
+        if backend.set_if(self.key, self.value, deadline, exists) {
+            RESP_OK.clone()
+        } else {
+            RespFrame::NullBulkString(RespNullBulkString)
+        }
+    }
+}
+
+impl CommandExecutor for SetNx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let set = backend.set_if(self.key, self.value, None, false);
+        RespFrame::Integer(set as i64)
+    }
+}
+
+impl CommandExecutor for MSet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        for (key, value) in self.pairs {
+            backend.set(key, value);
+        }
         RESP_OK.clone()
     }
 }
 
+impl CommandExecutor for MGet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let values = self
+            .keys
+            .iter()
+            .map(|key| {
+                backend
+                    .get(key)
+                    .unwrap_or(RespFrame::NullBulkString(RespNullBulkString))
+            })
+            .collect::<Vec<RespFrame>>();
+        RespArray::new(values).into()
+    }
+}
+
+impl CommandExecutor for Strlen {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "string") {
+            return RESP_WRONGTYPE.clone();
+        }
+        RespFrame::Integer(backend.strlen(&self.key) as i64)
+    }
+}
+
+impl CommandExecutor for Append {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "string") {
+            return RESP_WRONGTYPE.clone();
+        }
+        let max_len = backend.config().proto_max_bulk_len;
+        match backend.append(self.key, &self.value, max_len) {
+            Some(len) => RespFrame::Integer(len as i64),
+            None => CommandError::StringTooLong.into(),
+        }
+    }
+}
+
+impl CommandExecutor for GetSet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "string") {
+            return RESP_WRONGTYPE.clone();
+        }
+        backend
+            .getset(self.key, self.value)
+            .unwrap_or(RespFrame::NullBulkString(RespNullBulkString))
+    }
+}
+
+impl CommandExecutor for GetDel {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "string") {
+            return RESP_WRONGTYPE.clone();
+        }
+        backend
+            .getdel(&self.key)
+            .unwrap_or(RespFrame::NullBulkString(RespNullBulkString))
+    }
+}
+
+impl CommandExecutor for Incr {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        incr_by(backend, self.key, 1)
+    }
+}
+
+impl CommandExecutor for Decr {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        incr_by(backend, self.key, -1)
+    }
+}
+
+impl CommandExecutor for IncrBy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        incr_by(backend, self.key, self.increment)
+    }
+}
+
+impl CommandExecutor for DecrBy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self.decrement.checked_neg() {
+            Some(delta) => incr_by(backend, self.key, delta),
+            None => CommandError::Overflow.into(),
+        }
+    }
+}
+
+// a missing key counts as 0, the result is stored back as its decimal text
+fn incr_by(backend: &Backend, key: Vec<u8>, delta: i64) -> RespFrame {
+    if is_wrong_type(backend, &key, "string") {
+        return RESP_WRONGTYPE.clone();
+    }
+    let ret = backend.update_string(key, |current| {
+        // a value set as a RESP integer, like SET n :12, is already a number
+        let current = match current {
+            None => 0,
+            Some(RespFrame::Integer(n)) => *n,
+            Some(v) => parse_integer::<i64>(v)?,
+        };
+        let value = current.checked_add(delta).ok_or(CommandError::Overflow)?;
+        Ok::<_, CommandError>((BulkString::from(value.to_string()).into(), value))
+    });
+    match ret {
+        Ok(value) => RespFrame::Integer(value),
+        Err(e) => e.into(),
+    }
+}
+
 impl TryFrom<RespArray> for Get {
     type Error = CommandError;
 
@@ -28,7 +177,7 @@ impl TryFrom<RespArray> for Get {
         let mut args = extract_args(value, 1)?.into_iter();
         match args.next() {
             Some(RespFrame::BulkString(key)) => Ok(Get {
-                key: String::from_utf8(key.0)?,
+                key: key.into_vec(),
             }),
             _ => Err(CommandError::InvalidCommand("Invalid command".to_string())),
         }
@@ -44,65 +193,1601 @@ impl TryFrom<RespArray> for Set {
         let mut args = extract_args(value, 1)?.into_iter();
         match (args.next(), args.next()) {
             (Some(RespFrame::BulkString(key)), Some(value)) => Ok(Set {
-                key: String::from_utf8(key.0)?,
+                key: key.into_vec(),
                 value,
+                options: SetOptions::parse(args)?,
             }),
             _ => Err(CommandError::InvalidCommand("Invalid command".to_string())),
         }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::RespDecode;
+impl TryFrom<RespArray> for SetNx {
+    type Error = CommandError;
 
-    use super::*;
-    use anyhow::Result;
-    use bytes::BytesMut;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["setnx"], 2)?;
 
-    #[test]
-    fn test_get_from_resp_array() -> Result<()> {
-        let mut buf = BytesMut::new();
-        buf.extend_from_slice(b"*2\r\n$3\r\nget\r\n$5\r\nhello\r\n");
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(value)) => Ok(SetNx {
+                key: key.into_vec(),
+                value,
+            }),
+            _ => Err(CommandError::InvalidCommand("Invalid command".to_string())),
+        }
+    }
+}
 
-        let frame = RespArray::decode(&mut buf)?;
+impl TryFrom<RespArray> for Incr {
+    type Error = CommandError;
 
-        let result: Get = frame.try_into()?;
-        assert_eq!(result.key, "hello");
-        Ok(())
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["incr"], 1)?;
+        Ok(Incr {
+            key: counter_key(value)?,
+        })
     }
+}
 
-    #[test]
-    fn test_set_from_resp_array() -> Result<()> {
-        let mut buf = BytesMut::new();
-        buf.extend_from_slice(b"*3\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n");
+impl TryFrom<RespArray> for Decr {
+    type Error = CommandError;
 
-        let frame = RespArray::decode(&mut buf)?;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["decr"], 1)?;
+        Ok(Decr {
+            key: counter_key(value)?,
+        })
+    }
+}
 
-        let result: Set = frame.try_into()?;
-        assert_eq!(result.key, "hello");
-        assert_eq!(result.value, RespFrame::BulkString(b"world".into()));
-        Ok(())
+impl TryFrom<RespArray> for IncrBy {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["incrby"], 2)?;
+        let increment = parse_integer(&value[2])?;
+        Ok(IncrBy {
+            key: counter_key(value)?,
+            increment,
+        })
     }
+}
 
-    #[test]
-    fn test_set_get_command() -> Result<()> {
-        let backend = Backend::new();
+impl TryFrom<RespArray> for DecrBy {
+    type Error = CommandError;
 
-        let cmd = Set {
-            key: "hello".to_string(),
-            value: RespFrame::BulkString(b"world".into()),
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["decrby"], 2)?;
+        let decrement = parse_integer(&value[2])?;
+        Ok(DecrBy {
+            key: counter_key(value)?,
+            decrement,
+        })
+    }
+}
+
+fn counter_key(value: RespArray) -> Result<Vec<u8>, CommandError> {
+    match extract_args(value, 1)?.into_iter().next() {
+        Some(RespFrame::BulkString(key)) => Ok(key.into_vec()),
+        _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+    }
+}
+
+impl SetExpiry {
+    // None for KEEPTTL and for a time too far out to be represented
+    fn deadline(self) -> Option<Instant> {
+        let now = Instant::now();
+        let since_epoch = || {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
         };
+        match self {
+            SetExpiry::Seconds(secs) => now.checked_add(Duration::from_secs(secs)),
+            SetExpiry::Milliseconds(ms) => now.checked_add(Duration::from_millis(ms)),
+            // a unix time in the past gives a deadline of now, the key expires right away
+            SetExpiry::UnixSeconds(secs) => {
+                now.checked_add(Duration::from_secs(secs).saturating_sub(since_epoch()))
+            }
+            SetExpiry::UnixMilliseconds(ms) => {
+                now.checked_add(Duration::from_millis(ms).saturating_sub(since_epoch()))
+            }
+            SetExpiry::KeepTtl => None,
+        }
+    }
+}
 
-        let result = cmd.execute(&backend);
-        assert_eq!(result, RESP_OK.clone());
+impl SetOptions {
+    /// Parse NX, XX, EX, PX, EXAT, PXAT and KEEPTTL. At most one condition and one expiry
+    /// may be given, anything else is a syntax error.
+    pub fn parse(args: impl IntoIterator<Item = RespFrame>) -> Result<Self, CommandError> {
+        let mut options = SetOptions::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let RespFrame::BulkString(arg) = arg else {
+                return Err(CommandError::SyntaxError);
+            };
+            let arg = arg.to_ascii_lowercase();
+            if let Some(condition) = match arg.as_slice() {
+                b"nx" => Some(SetCondition::IfMissing),
+                b"xx" => Some(SetCondition::IfExists),
+                _ => None,
+            } {
+                if options.condition.replace(condition).is_some() {
+                    return Err(CommandError::SyntaxError);
+                }
+                continue;
+            }
+            let expiry = match arg.as_slice() {
+                b"keepttl" => SetExpiry::KeepTtl,
+                b"ex" | b"px" | b"exat" | b"pxat" => {
+                    let time = args.next().ok_or(CommandError::SyntaxError)?;
+                    let time = match parse_integer::<i64>(&time)? {
+                        t if t <= 0 => {
+                            return Err(CommandError::InvalidExpireTime("set".to_string()));
+                        }
+                        t => t as u64,
+                    };
+                    match arg.as_slice() {
+                        b"ex" => SetExpiry::Seconds(time),
+                        b"px" => SetExpiry::Milliseconds(time),
+                        b"exat" => SetExpiry::UnixSeconds(time),
+                        _ => SetExpiry::UnixMilliseconds(time),
+                    }
+                }
+                _ => return Err(CommandError::SyntaxError),
+            };
+            if options.expiry.replace(expiry).is_some() {
+                return Err(CommandError::SyntaxError);
+            }
+        }
+        Ok(options)
+    }
+}
 
-        let cmd = Get {
-            key: "hello".to_string(),
+impl CommandExecutor for SetRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "string") {
+            return RESP_WRONGTYPE.clone();
+        }
+        // checked before anything is allocated, the offset comes straight from the client
+        if self.offset.saturating_add(self.value.len()) > backend.config().proto_max_bulk_len {
+            return CommandError::StringTooLong.into();
+        }
+        let len = backend.setrange(self.key, self.offset, &self.value);
+        RespFrame::Integer(len as i64)
+    }
+}
+
+impl CommandExecutor for GetBit {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "string") {
+            return RESP_WRONGTYPE.clone();
+        }
+        if self.offset / 8 >= backend.config().proto_max_bulk_len {
+            return CommandError::BitOffsetOutOfRange.into();
+        }
+        RespFrame::Integer(backend.getbit(&self.key, self.offset) as i64)
+    }
+}
+
+impl CommandExecutor for SetBit {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "string") {
+            return RESP_WRONGTYPE.clone();
+        }
+        if self.offset / 8 >= backend.config().proto_max_bulk_len {
+            return CommandError::BitOffsetOutOfRange.into();
+        }
+        RespFrame::Integer(backend.setbit(self.key, self.offset, self.on) as i64)
+    }
+}
+
+impl TryFrom<RespArray> for MSet {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["mset"], 2)?;
+        if value.len().is_multiple_of(2) {
+            return Err(CommandError::WrongArity("mset".to_string()));
+        }
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let mut pairs = Vec::new();
+        while let (Some(key), Some(value)) = (args.next(), args.next()) {
+            match key {
+                RespFrame::BulkString(key) => pairs.push((key.into_vec(), value)),
+                _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+            }
+        }
+        Ok(MSet { pairs })
+    }
+}
+
+impl TryFrom<RespArray> for MGet {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["mget"], 1)?;
+
+        let keys = extract_args(value, 1)?
+            .into_iter()
+            .map(|frame| match frame {
+                RespFrame::BulkString(key) => Ok(key.into_vec()),
+                _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(MGet { keys })
+    }
+}
+
+impl TryFrom<RespArray> for Strlen {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["strlen"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(Strlen {
+                key: key.into_vec(),
+            }),
+            _ => Err(CommandError::InvalidCommand("Invalid command".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Append {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["append"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(value))) => Ok(Append {
+                key: key.into_vec(),
+                value: value.into_vec(),
+            }),
+            _ => Err(CommandError::InvalidCommand("Invalid command".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for GetSet {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["getset"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(value)) => Ok(GetSet {
+                key: key.into_vec(),
+                value,
+            }),
+            _ => Err(CommandError::InvalidCommand("Invalid command".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for GetDel {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["getdel"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(GetDel {
+                key: key.into_vec(),
+            }),
+            _ => Err(CommandError::InvalidCommand("Invalid command".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for SetRange {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["setrange"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (
+                Some(RespFrame::BulkString(key)),
+                Some(offset),
+                Some(RespFrame::BulkString(value)),
+            ) => {
+                let offset = parse_integer::<i64>(&offset)?;
+                Ok(SetRange {
+                    key: key.into_vec(),
+                    offset: usize::try_from(offset).map_err(|_| CommandError::OffsetOutOfRange)?,
+                    value: value.into_vec(),
+                })
+            }
+            _ => Err(CommandError::InvalidCommand("Invalid command".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for GetBit {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["getbit"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(offset)) => Ok(GetBit {
+                key: key.into_vec(),
+                offset: parse_bit_offset(&offset)?,
+            }),
+            _ => Err(CommandError::InvalidCommand("Invalid command".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for SetBit {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["setbit"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(offset), Some(bit)) => {
+                let on = match parse_integer::<u8>(&bit) {
+                    Ok(0) => false,
+                    Ok(1) => true,
+                    _ => return Err(CommandError::BitOutOfRange),
+                };
+                Ok(SetBit {
+                    key: key.into_vec(),
+                    offset: parse_bit_offset(&offset)?,
+                    on,
+                })
+            }
+            _ => Err(CommandError::InvalidCommand("Invalid command".to_string())),
+        }
+    }
+}
+
+impl CommandExecutor for BitPos {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "string") {
+            return RESP_WRONGTYPE.clone();
+        }
+        // a missing key is an endless run of 0 bits
+        let pos = match backend.get_bytes(&self.key) {
+            Some(bytes) => bitpos(&bytes, self.bit, self.start, self.end, self.unit),
+            None if self.bit == 0 => 0,
+            None => -1,
         };
+        RespFrame::Integer(pos)
+    }
+}
 
-        let result = cmd.execute(&backend);
-        assert_eq!(result, RespFrame::BulkString(b"world".into()));
+// start and end index bytes or bits depending on `unit`, negative ones count from the end.
+// When looking for a 0 without an explicit end the string is taken as padded with 0 bits,
+// so a string of all 1s finds the bit right after it; with an end it's -1 instead
+fn bitpos(bytes: &[u8], bit: u8, start: i64, end: Option<i64>, unit: BitUnit) -> i64 {
+    let len = match unit {
+        BitUnit::Byte => bytes.len() as i64,
+        BitUnit::Bit => bytes.len() as i64 * 8,
+    };
+    let index = |i: i64| if i < 0 { (len + i).max(0) } else { i };
+    let start = index(start);
+    let stop = end.map_or(len - 1, |end| index(end).min(len - 1));
+    if start > stop {
+        return -1;
+    }
+    let (first, last) = match unit {
+        BitUnit::Byte => (start * 8, stop * 8 + 7),
+        BitUnit::Bit => (start, stop),
+    };
+
+    // whole bytes holding none of the bits looked for are skipped at once
+    let skip = if bit == 1 { 0x00 } else { 0xff };
+    let mut i = first;
+    while i <= last {
+        let byte = bytes[(i / 8) as usize];
+        if i % 8 == 0 && i + 7 <= last && byte == skip {
+            i += 8;
+            continue;
+        }
+        if (byte >> (7 - i % 8)) & 1 == bit {
+            return i;
+        }
+        i += 1;
+    }
+    match end {
+        None if bit == 0 => last + 1,
+        _ => -1,
+    }
+}
+
+impl TryFrom<RespArray> for BitPos {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["bitpos"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => key.into_vec(),
+            _ => return Err(CommandError::InvalidCommand("Invalid command".to_string())),
+        };
+        let bit = match args.next().map(|bit| parse_integer::<i64>(&bit)) {
+            Some(Ok(bit @ (0 | 1))) => bit as u8,
+            Some(Ok(_)) => return Err(CommandError::BitNotZeroOrOne),
+            _ => return Err(CommandError::NotAnInteger),
+        };
+        let start = args.next().map_or(Ok(0), |start| parse_integer(&start))?;
+        let end = args.next().map(|end| parse_integer(&end)).transpose()?;
+        let unit = match args.next() {
+            None => BitUnit::Byte,
+            Some(RespFrame::BulkString(unit)) if unit.eq_ignore_ascii_case(b"byte") => {
+                BitUnit::Byte
+            }
+            Some(RespFrame::BulkString(unit)) if unit.eq_ignore_ascii_case(b"bit") => BitUnit::Bit,
+            Some(_) => return Err(CommandError::SyntaxError),
+        };
+        if args.next().is_some() {
+            return Err(CommandError::SyntaxError);
+        }
+        Ok(BitPos {
+            key,
+            bit,
+            start,
+            end,
+            unit,
+        })
+    }
+}
+
+fn parse_bit_offset(frame: &RespFrame) -> Result<usize, CommandError> {
+    parse_integer(frame).map_err(|_| CommandError::BitOffsetOutOfRange)
+}
+
+impl CommandExecutor for Lcs {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key1, "string")
+            || is_wrong_type(backend, &self.key2, "string")
+        {
+            return RESP_WRONGTYPE.clone();
+        }
+        // a missing key is an empty string
+        let a = backend.get_bytes(&self.key1).unwrap_or_default();
+        let b = backend.get_bytes(&self.key2).unwrap_or_default();
+        // the table holds a u32 per pair of prefixes, bounded like any other allocation
+        // a client can ask for
+        let cells = (a.len() + 1)
+            .checked_mul(b.len() + 1)
+            .and_then(|cells| cells.checked_mul(size_of::<u32>()));
+        if cells.is_none_or(|size| size > backend.config().proto_max_bulk_len) {
+            return CommandError::LcsTooLarge.into();
+        }
+
+        let table = LcsTable::new(&a, &b);
+        if self.len {
+            return RespFrame::Integer(table.len() as i64);
+        }
+        let (lcs, matches) = table.backtrack(self.min_match_len);
+        if !self.idx {
+            return BulkString::new(lcs).into();
+        }
+        let range = |start: usize, end: usize| {
+            RespArray::new([
+                RespFrame::Integer(start as i64),
+                RespFrame::Integer(end as i64),
+            ])
+            .into()
+        };
+        let matches = matches
+            .into_iter()
+            .map(|m| {
+                let mut item = vec![range(m.a.0, m.a.1), range(m.b.0, m.b.1)];
+                if self.with_match_len {
+                    item.push(RespFrame::Integer(m.len() as i64));
+                }
+                RespArray::new(item).into()
+            })
+            .collect::<Vec<RespFrame>>();
+        RespArray::new([
+            BulkString::from("matches").into(),
+            RespArray::new(matches).into(),
+            BulkString::from("len").into(),
+            RespFrame::Integer(table.len() as i64),
+        ])
+        .into()
+    }
+}
+
+impl TryFrom<RespArray> for Lcs {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["lcs"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let (Some(RespFrame::BulkString(key1)), Some(RespFrame::BulkString(key2))) =
+            (args.next(), args.next())
+        else {
+            return Err(CommandError::InvalidCommand("Invalid command".to_string()));
+        };
+        let mut lcs = Lcs {
+            key1: key1.into_vec(),
+            key2: key2.into_vec(),
+            len: false,
+            idx: false,
+            min_match_len: 0,
+            with_match_len: false,
+        };
+        while let Some(arg) = args.next() {
+            let RespFrame::BulkString(arg) = arg else {
+                return Err(CommandError::SyntaxError);
+            };
+            match arg.to_ascii_lowercase().as_slice() {
+                b"len" => lcs.len = true,
+                b"idx" => lcs.idx = true,
+                b"withmatchlen" => lcs.with_match_len = true,
+                b"minmatchlen" => {
+                    let len = args.next().ok_or(CommandError::SyntaxError)?;
+                    // a negative length filters nothing, as in redis
+                    lcs.min_match_len = parse_integer::<i64>(&len)?.max(0) as usize;
+                }
+                _ => return Err(CommandError::SyntaxError),
+            }
+        }
+        if lcs.len && lcs.idx {
+            return Err(CommandError::LcsLenAndIdx);
+        }
+        Ok(lcs)
+    }
+}
+
+// lengths of the LCS of every pair of prefixes, (a.len() + 1) x (b.len() + 1) row major
+struct LcsTable<'a> {
+    a: &'a [u8],
+    b: &'a [u8],
+    cells: Vec<u32>,
+}
+
+// a run of consecutive matching bytes, inclusive ranges into both strings
+#[derive(Debug, PartialEq)]
+struct LcsMatch {
+    a: (usize, usize),
+    b: (usize, usize),
+}
+
+impl LcsMatch {
+    fn len(&self) -> usize {
+        self.a.1 - self.a.0 + 1
+    }
+}
+
+impl<'a> LcsTable<'a> {
+    fn new(a: &'a [u8], b: &'a [u8]) -> Self {
+        let width = b.len() + 1;
+        let mut cells = vec![0u32; (a.len() + 1) * width];
+        for i in 1..=a.len() {
+            for j in 1..=b.len() {
+                cells[i * width + j] = if a[i - 1] == b[j - 1] {
+                    cells[(i - 1) * width + j - 1] + 1
+                } else {
+                    cells[(i - 1) * width + j].max(cells[i * width + j - 1])
+                };
+            }
+        }
+        LcsTable { a, b, cells }
+    }
+
+    fn at(&self, i: usize, j: usize) -> u32 {
+        self.cells[i * (self.b.len() + 1) + j]
+    }
+
+    fn len(&self) -> usize {
+        self.at(self.a.len(), self.b.len()) as usize
+    }
+
+    // walks back from the end of both strings, so the matches come last to first like
+    // redis reports them; runs shorter than `min_match_len` are left out of the matches
+    fn backtrack(&self, min_match_len: usize) -> (Vec<u8>, Vec<LcsMatch>) {
+        let mut lcs = vec![0; self.len()];
+        let mut matches = Vec::new();
+        let mut current: Option<LcsMatch> = None;
+        let (mut i, mut j, mut k) = (self.a.len(), self.b.len(), self.len());
+        let mut emit = |m: LcsMatch| {
+            if m.len() >= min_match_len {
+                matches.push(m);
+            }
+        };
+        while i > 0 && j > 0 {
+            if self.a[i - 1] == self.b[j - 1] {
+                lcs[k - 1] = self.a[i - 1];
+                k -= 1;
+                i -= 1;
+                j -= 1;
+                match current.as_mut() {
+                    Some(m) if m.a.0 == i + 1 && m.b.0 == j + 1 => {
+                        m.a.0 = i;
+                        m.b.0 = j;
+                    }
+                    _ => {
+                        if let Some(m) = current.take() {
+                            emit(m);
+                        }
+                        current = Some(LcsMatch {
+                            a: (i, i),
+                            b: (j, j),
+                        });
+                    }
+                }
+            } else {
+                if self.at(i - 1, j) > self.at(i, j - 1) {
+                    i -= 1;
+                } else {
+                    j -= 1;
+                }
+                if let Some(m) = current.take() {
+                    emit(m);
+                }
+            }
+        }
+        if let Some(m) = current {
+            emit(m);
+        }
+        (lcs, matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{RespDecode, RespEncode};
+
+    use super::*;
+    use crate::cmd::{Command, Type};
+    use crate::{SimpleError, SimpleString};
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_get_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$3\r\nget\r\n$5\r\nhello\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+
+        let result: Get = frame.try_into()?;
+        assert_eq!(result.key, b"hello");
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+
+        let result: Set = frame.try_into()?;
+        assert_eq!(result.key, b"hello");
+        assert_eq!(result.value, RespFrame::BulkString(b"world".into()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_get_command() -> Result<()> {
+        let backend = Backend::new();
+
+        let cmd = Set {
+            key: b"hello".to_vec(),
+            value: RespFrame::BulkString(b"world".into()),
+            options: SetOptions::default(),
+        };
+
+        let result = cmd.execute(&backend);
+        assert_eq!(result, RESP_OK.clone());
+
+        let cmd = Get {
+            key: b"hello".to_vec(),
+        };
+
+        let result = cmd.execute(&backend);
+        assert_eq!(result, RespFrame::BulkString(b"world".into()));
+        Ok(())
+    }
+
+    fn set_options(args: &[&str]) -> Result<SetOptions, CommandError> {
+        SetOptions::parse(args.iter().map(|arg| BulkString::from(*arg).into()))
+    }
+
+    #[test]
+    fn test_set_options() -> Result<()> {
+        assert_eq!(set_options(&[])?, SetOptions::default());
+        assert_eq!(
+            set_options(&["EX", "10", "nx"])?,
+            SetOptions {
+                condition: Some(SetCondition::IfMissing),
+                expiry: Some(SetExpiry::Seconds(10)),
+            }
+        );
+        assert_eq!(
+            set_options(&["xx", "pxat", "1700000000000"])?,
+            SetOptions {
+                condition: Some(SetCondition::IfExists),
+                expiry: Some(SetExpiry::UnixMilliseconds(1700000000000)),
+            }
+        );
+
+        for illegal in [
+            &["nx", "xx"][..],
+            &["xx", "nx"],
+            &["ex", "10", "px", "100"],
+            &["exat", "10", "pxat", "100"],
+            &["ex", "10", "keepttl"],
+            &["keepttl", "pxat", "100"],
+            &["ex"],
+            &["ttl", "10"],
+        ] {
+            assert!(
+                matches!(set_options(illegal), Err(CommandError::SyntaxError)),
+                "{:?} should be a syntax error",
+                illegal
+            );
+        }
+        assert!(matches!(
+            set_options(&["px", "0"]),
+            Err(CommandError::InvalidExpireTime(_))
+        ));
+        assert!(matches!(
+            set_options(&["ex", "ten"]),
+            Err(CommandError::NotAnInteger)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_nx_xx() -> Result<()> {
+        let backend = Backend::new();
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$3\r\nset\r\n$1\r\nk\r\n$1\r\n1\r\n$2\r\nXX\r\n");
+        buf.extend_from_slice(b"*4\r\n$3\r\nset\r\n$1\r\nk\r\n$1\r\n2\r\n$2\r\nNX\r\n");
+        buf.extend_from_slice(b"*4\r\n$3\r\nset\r\n$1\r\nk\r\n$1\r\n3\r\n$2\r\nNX\r\n");
+        buf.extend_from_slice(b"*4\r\n$3\r\nset\r\n$1\r\nk\r\n$1\r\n4\r\n$2\r\nXX\r\n");
+
+        let nil = RespFrame::NullBulkString(RespNullBulkString);
+        for expected in [nil.clone(), RESP_OK.clone(), nil, RESP_OK.clone()] {
+            let cmd: Set = RespArray::decode(&mut buf)?.try_into()?;
+            assert_eq!(cmd.execute(&backend), expected);
+        }
+        assert_eq!(backend.get(b"k"), Some(BulkString::from("4").into()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_setnx() -> Result<()> {
+        let backend = Backend::new();
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$5\r\nsetnx\r\n$1\r\nk\r\n$1\r\n1\r\n");
+        buf.extend_from_slice(b"*3\r\n$5\r\nSETNX\r\n$1\r\nk\r\n$1\r\n2\r\n");
+
+        for expected in [1, 0] {
+            let cmd: Command = RespArray::decode(&mut buf)?.try_into()?;
+            assert_eq!(cmd.execute(&backend), RespFrame::Integer(expected));
+        }
+        assert_eq!(backend.get(b"k"), Some(BulkString::from("1").into()));
+
+        // a key of any type counts as existing
+        backend.hset(
+            b"hash".to_vec(),
+            "f".to_string(),
+            BulkString::from("v").into(),
+        );
+        let cmd = SetNx {
+            key: b"hash".to_vec(),
+            value: BulkString::from("v").into(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_concurrent_setnx_has_one_winner() {
+        const THREADS: usize = 8;
+        const KEYS: usize = 1000;
+
+        let backend = Backend::new();
+        let handles: Vec<_> = (0..THREADS)
+            .map(|i| {
+                let backend = backend.clone();
+                std::thread::spawn(move || {
+                    let (mut set_wins, mut setnx_wins) = (0, 0);
+                    for key in 0..KEYS {
+                        let cmd = Set {
+                            key: format!("set:{}", key).into_bytes(),
+                            value: RespFrame::Integer(i as i64),
+                            options: SetOptions {
+                                condition: Some(SetCondition::IfMissing),
+                                ..Default::default()
+                            },
+                        };
+                        if cmd.execute(&backend) == RESP_OK.clone() {
+                            set_wins += 1;
+                        }
+                        let cmd = SetNx {
+                            key: format!("setnx:{}", key).into_bytes(),
+                            value: RespFrame::Integer(i as i64),
+                        };
+                        if cmd.execute(&backend) == RespFrame::Integer(1) {
+                            setnx_wins += 1;
+                        }
+                    }
+                    (set_wins, setnx_wins)
+                })
+            })
+            .collect();
+        let wins = handles
+            .into_iter()
+            .map(|h| h.join().unwrap())
+            .fold((0, 0), |acc, wins| (acc.0 + wins.0, acc.1 + wins.1));
+        // each key is created by exactly one of the threads racing for it
+        assert_eq!(wins, (KEYS, KEYS));
+    }
+
+    #[test]
+    fn test_incr_fresh_key() -> Result<()> {
+        let backend = Backend::new();
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$4\r\nINCR\r\n$7\r\ncounter\r\n");
+        buf.extend_from_slice(b"*3\r\n$6\r\ndecrby\r\n$7\r\ncounter\r\n$2\r\n10\r\n");
+
+        let cmd: Command = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        let cmd: Command = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-9));
+        assert_eq!(backend.get(b"counter"), Some(BulkString::from("-9").into()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_incr_existing_numeric_string() {
+        let backend = Backend::new();
+        backend.set(b"n".to_vec(), BulkString::from("41").into());
+
+        let cmd = Incr { key: b"n".to_vec() };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(42));
+        let cmd = IncrBy {
+            key: b"n".to_vec(),
+            increment: 100,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(142));
+        let cmd = Decr { key: b"n".to_vec() };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(141));
+        assert_eq!(backend.get(b"n"), Some(BulkString::from("141").into()));
+    }
+
+    #[test]
+    fn test_incr_integer_value() {
+        let backend = Backend::new();
+        backend.set(b"n".to_vec(), RespFrame::Integer(12));
+
+        let cmd = Incr { key: b"n".to_vec() };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(13));
+        let cmd = DecrBy {
+            key: b"n".to_vec(),
+            decrement: 20,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-7));
+        assert_eq!(backend.get(b"n"), Some(BulkString::from("-7").into()));
+
+        backend.set(b"max".to_vec(), RespFrame::Integer(i64::MAX));
+        let cmd = Incr {
+            key: b"max".to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), CommandError::Overflow.into());
+    }
+
+    #[test]
+    fn test_incr_errors() {
+        let backend = Backend::new();
+        backend.set(b"text".to_vec(), BulkString::from("hello").into());
+        backend.set(
+            b"max".to_vec(),
+            BulkString::from(i64::MAX.to_string()).into(),
+        );
+
+        let cmd = Incr {
+            key: b"text".to_vec(),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            SimpleError::new("ERR value is not an integer or out of range").into()
+        );
+        assert_eq!(backend.get(b"text"), Some(BulkString::from("hello").into()));
+
+        let cmd = Incr {
+            key: b"max".to_vec(),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            SimpleError::new("ERR increment or decrement would overflow").into()
+        );
+        let cmd = DecrBy {
+            key: b"fresh".to_vec(),
+            decrement: i64::MIN,
+        };
+        assert_eq!(cmd.execute(&backend), CommandError::Overflow.into());
+    }
+
+    #[test]
+    fn test_set_with_expiry() -> Result<()> {
+        let backend = Backend::new();
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*5\r\n$3\r\nset\r\n$1\r\nk\r\n$1\r\nv\r\n$2\r\nEX\r\n$2\r\n10\r\n");
+        buf.extend_from_slice(b"*4\r\n$3\r\nset\r\n$1\r\nk\r\n$1\r\nw\r\n$7\r\nKEEPTTL\r\n");
+        buf.extend_from_slice(
+            b"*5\r\n$3\r\nset\r\n$1\r\nk\r\n$1\r\nx\r\n$4\r\nexat\r\n$1\r\n1\r\n",
+        );
+
+        let cmd: Set = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        assert_eq!(backend.ttl(b"k"), 10);
+        let cmd: Set = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        assert_eq!(backend.ttl(b"k"), 10);
+        assert_eq!(backend.get(b"k"), Some(BulkString::from("w").into()));
+
+        // a deadline in the past, the key is set and gone at once
+        let cmd: Set = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        assert_eq!(backend.get(b"k"), None);
+
+        let cmd = Set {
+            key: b"k".to_vec(),
+            value: BulkString::from("y").into(),
+            options: SetOptions {
+                condition: None,
+                expiry: Some(SetExpiry::Seconds(u64::MAX)),
+            },
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            SimpleError::new("ERR invalid expire time in 'set' command").into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_empty_value_is_not_nil() -> Result<()> {
+        let backend = Backend::new();
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$3\r\nset\r\n$1\r\nk\r\n$0\r\n\r\n");
+        let cmd: Set = RespArray::decode(&mut buf)?.try_into()?;
+        cmd.execute(&backend);
+
+        let get = |key: &[u8]| Get { key: key.to_vec() }.execute(&backend).encode();
+        assert_eq!(get(b"k"), b"$0\r\n\r\n");
+        assert_eq!(get(b"missing"), b"$-1\r\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_stored_value_does_not_share_read_buffer() -> Result<()> {
+        let backend = Backend::new();
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$3\r\nset\r\n$3\r\nkey\r\n$5\r\nvalue\r\n+PING\r\n");
+        let frame = RespFrame::decode(&mut buf)?;
+        Command::try_from(frame)?.execute(&backend);
+        assert_eq!(&buf[..], b"+PING\r\n");
+
+        match backend.map.get(b"key".as_slice()).as_deref() {
+            Some(RespFrame::BulkString(value)) => {
+                assert_eq!(value.as_ref(), b"value");
+                assert!(value.0.is_unique());
+            }
+            value => panic!("unexpected value {:?}", value),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_get_binary_key() -> Result<()> {
+        let backend = Backend::new();
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$3\r\nset\r\n$2\r\n\xff\xfe\r\n$5\r\nworld\r\n");
+        let cmd: Set = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.key, b"\xff\xfe");
+        cmd.execute(&backend);
+
+        let cmd = Get {
+            key: b"\xff\xfe".to_vec(),
+        };
+        let result = cmd.execute(&backend);
+        assert_eq!(result, RespFrame::BulkString(b"world".into()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_on_hash_key() {
+        let backend = Backend::new();
+        backend.hset(
+            b"map".to_vec(),
+            "field".to_string(),
+            RespFrame::BulkString(b"value".into()),
+        );
+
+        let cmd = Get {
+            key: b"map".to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), RESP_WRONGTYPE.clone());
+    }
+
+    #[test]
+    fn test_set_overwrites_hash_key() {
+        let backend = Backend::new();
+        backend.hset(
+            b"map".to_vec(),
+            "field".to_string(),
+            RespFrame::BulkString(b"value".into()),
+        );
+
+        let cmd = Set {
+            key: b"map".to_vec(),
+            value: RespFrame::BulkString(b"plain".into()),
+            options: SetOptions::default(),
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+
+        let cmd = Type {
+            key: b"map".to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), SimpleString::new("string").into());
+        let cmd = Get {
+            key: b"map".to_vec(),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespFrame::BulkString(b"plain".into())
+        );
+    }
+
+    #[test]
+    fn test_setrange_getbit_setbit() -> Result<()> {
+        let backend = Backend::new();
+        backend.set(
+            b"key".to_vec(),
+            RespFrame::BulkString(b"Hello World".into()),
+        );
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$8\r\nsetrange\r\n$3\r\nkey\r\n$1\r\n6\r\n$5\r\nRedis\r\n");
+        let cmd: SetRange = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(11));
+        assert_eq!(
+            backend.get(b"key"),
+            Some(RespFrame::BulkString(b"Hello Redis".into()))
+        );
+
+        let cmd = SetRange {
+            key: b"padded".to_vec(),
+            offset: 2,
+            value: b"x".to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(3));
+        assert_eq!(
+            backend.get(b"padded"),
+            Some(RespFrame::BulkString(b"\0\0x".into()))
+        );
+
+        let cmd = SetBit {
+            key: b"bits".to_vec(),
+            offset: 7,
+            on: true,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        assert_eq!(
+            backend.get(b"bits"),
+            Some(RespFrame::BulkString(b"\x01".into()))
+        );
+        let cmd = GetBit {
+            key: b"bits".to_vec(),
+            offset: 7,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        let cmd = GetBit {
+            key: b"bits".to_vec(),
+            offset: 100,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_bitpos() -> Result<()> {
+        let backend = Backend::new();
+        let bitpos = |key: &[u8], bit: u8, start: i64, end: Option<i64>, unit: BitUnit| {
+            BitPos {
+                key: key.to_vec(),
+                bit,
+                start,
+                end,
+                unit,
+            }
+            .execute(&backend)
+        };
+
+        backend.set(
+            b"set".to_vec(),
+            BulkString::new(b"\x00\xff\xf0".to_vec()).into(),
+        );
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$6\r\nbitpos\r\n$3\r\nset\r\n$1\r\n1\r\n");
+        let cmd: Command = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(8));
+        assert_eq!(
+            bitpos(b"set", 1, 2, None, BitUnit::Byte),
+            RespFrame::Integer(16)
+        );
+        assert_eq!(
+            bitpos(b"set", 1, 2, Some(-1), BitUnit::Byte),
+            RespFrame::Integer(16)
+        );
+        assert_eq!(
+            bitpos(b"set", 1, 7, Some(15), BitUnit::Bit),
+            RespFrame::Integer(8)
+        );
+        assert_eq!(
+            bitpos(b"set", 1, 0, Some(0), BitUnit::Byte),
+            RespFrame::Integer(-1)
+        );
+        assert_eq!(
+            bitpos(b"set", 1, 2, Some(1), BitUnit::Byte),
+            RespFrame::Integer(-1)
+        );
+
+        backend.set(
+            b"clear".to_vec(),
+            BulkString::new(b"\xff\xf0\x00".to_vec()).into(),
+        );
+        assert_eq!(
+            bitpos(b"clear", 0, 0, None, BitUnit::Byte),
+            RespFrame::Integer(12)
+        );
+        assert_eq!(
+            bitpos(b"clear", 0, 2, Some(9), BitUnit::Bit),
+            RespFrame::Integer(-1)
+        );
+        assert_eq!(
+            bitpos(b"clear", 0, 2, Some(12), BitUnit::Bit),
+            RespFrame::Integer(12)
+        );
+
+        // a clear bit past a string of 1s is found unless the range has an end
+        backend.set(
+            b"ones".to_vec(),
+            BulkString::new(b"\xff\xff\xff".to_vec()).into(),
+        );
+        assert_eq!(
+            bitpos(b"ones", 0, 0, None, BitUnit::Byte),
+            RespFrame::Integer(24)
+        );
+        assert_eq!(
+            bitpos(b"ones", 0, 1, None, BitUnit::Byte),
+            RespFrame::Integer(24)
+        );
+        assert_eq!(
+            bitpos(b"ones", 0, 0, Some(-1), BitUnit::Byte),
+            RespFrame::Integer(-1)
+        );
+        backend.set(
+            b"zeros".to_vec(),
+            BulkString::new(b"\x00\x00".to_vec()).into(),
+        );
+        assert_eq!(
+            bitpos(b"zeros", 1, 0, None, BitUnit::Byte),
+            RespFrame::Integer(-1)
+        );
+
+        assert_eq!(
+            bitpos(b"missing", 0, 0, None, BitUnit::Byte),
+            RespFrame::Integer(0)
+        );
+        assert_eq!(
+            bitpos(b"missing", 1, 0, None, BitUnit::Byte),
+            RespFrame::Integer(-1)
+        );
+        backend.set(b"empty".to_vec(), BulkString::new(b"".to_vec()).into());
+        assert_eq!(
+            bitpos(b"empty", 0, 0, None, BitUnit::Byte),
+            RespFrame::Integer(-1)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_bitpos_errors() -> Result<()> {
+        let parse = |args: &[&str]| -> Result<BitPos, CommandError> {
+            RespArray::new(
+                args.iter()
+                    .map(|arg| BulkString::from(*arg).into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .try_into()
+        };
+        assert!(matches!(
+            parse(&["bitpos", "k", "2"]),
+            Err(CommandError::BitNotZeroOrOne)
+        ));
+        assert!(matches!(
+            parse(&["bitpos", "k", "x"]),
+            Err(CommandError::NotAnInteger)
+        ));
+        assert!(matches!(
+            parse(&["bitpos", "k", "1", "0", "x"]),
+            Err(CommandError::NotAnInteger)
+        ));
+        assert!(matches!(
+            parse(&["bitpos", "k", "1", "0", "1", "bits"]),
+            Err(CommandError::SyntaxError)
+        ));
+        assert!(matches!(
+            parse(&["bitpos", "k", "1", "0", "1", "BIT", "x"]),
+            Err(CommandError::SyntaxError)
+        ));
+        let cmd = parse(&["bitpos", "k", "0", "-2", "-1", "BIT"])?;
+        assert_eq!((cmd.start, cmd.end, cmd.unit), (-2, Some(-1), BitUnit::Bit));
+
+        let backend = Backend::new();
+        backend.hset(
+            b"hash".to_vec(),
+            "f".to_string(),
+            BulkString::from("v").into(),
+        );
+        let cmd = parse(&["bitpos", "hash", "1"])?;
+        assert_eq!(cmd.execute(&backend), RESP_WRONGTYPE.clone());
+        Ok(())
+    }
+
+    #[test]
+    fn test_offsets_over_proto_max_bulk_len() -> Result<()> {
+        let backend = Backend::new();
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$6\r\nsetbit\r\n$1\r\nk\r\n$10\r\n4294967296\r\n$1\r\n1\r\n");
+        let cmd: SetBit = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(
+            cmd.execute(&backend),
+            CommandError::BitOffsetOutOfRange.into()
+        );
+
+        let cmd = SetRange {
+            key: b"k".to_vec(),
+            offset: 512 * 1024 * 1024,
+            value: b"x".to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), CommandError::StringTooLong.into());
+        assert_eq!(backend.type_of(b"k"), None);
+
+        buf.extend_from_slice(b"*4\r\n$8\r\nsetrange\r\n$1\r\nk\r\n$2\r\n-1\r\n$1\r\nx\r\n");
+        let ret: Result<SetRange, _> = RespArray::decode(&mut buf)?.try_into();
+        assert!(matches!(ret, Err(CommandError::OffsetOutOfRange)));
+        Ok(())
+    }
+
+    fn lcs(backend: &Backend, args: &[&str]) -> Result<RespFrame> {
+        let mut frames = vec![BulkString::from("lcs").into()];
+        frames.extend(args.iter().map(|arg| BulkString::from(*arg).into()));
+        let cmd: Command = RespArray::new(frames).try_into()?;
+        Ok(cmd.execute(backend))
+    }
+
+    #[test]
+    fn test_lcs() -> Result<()> {
+        let backend = Backend::new();
+        backend.set(b"key1".to_vec(), BulkString::from("ohmytext").into());
+        backend.set(b"key2".to_vec(), BulkString::from("mynewtext").into());
+
+        assert_eq!(
+            lcs(&backend, &["key1", "key2"])?,
+            BulkString::from("mytext").into()
+        );
+        assert_eq!(
+            lcs(&backend, &["key1", "key2", "LEN"])?,
+            RespFrame::Integer(6)
+        );
+        assert_eq!(
+            lcs(&backend, &["key1", "missing"])?,
+            BulkString::from("").into()
+        );
+        assert_eq!(
+            lcs(&backend, &["key1", "missing", "len"])?,
+            RespFrame::Integer(0)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_lcs_idx() -> Result<()> {
+        let backend = Backend::new();
+        backend.set(b"key1".to_vec(), BulkString::from("ohmytext").into());
+        backend.set(b"key2".to_vec(), BulkString::from("mynewtext").into());
+
+        let ret = lcs(&backend, &["key1", "key2", "idx"])?;
+        assert_eq!(
+            ret.encode(),
+            b"*4\r\n$7\r\nmatches\r\n*2\r\n\
+              *2\r\n*2\r\n:4\r\n:7\r\n*2\r\n:5\r\n:8\r\n\
+              *2\r\n*2\r\n:2\r\n:3\r\n*2\r\n:0\r\n:1\r\n\
+              $3\r\nlen\r\n:6\r\n"
+        );
+
+        let ret = lcs(
+            &backend,
+            &["key1", "key2", "IDX", "MINMATCHLEN", "4", "WITHMATCHLEN"],
+        )?;
+        assert_eq!(
+            ret.encode(),
+            b"*4\r\n$7\r\nmatches\r\n*1\r\n\
+              *3\r\n*2\r\n:4\r\n:7\r\n*2\r\n:5\r\n:8\r\n:4\r\n\
+              $3\r\nlen\r\n:6\r\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_lcs_errors() -> Result<()> {
+        let backend = Backend::new();
+        backend.set(b"key1".to_vec(), BulkString::from("ohmytext").into());
+        backend.hset(
+            b"hash".to_vec(),
+            "f".to_string(),
+            BulkString::from("v").into(),
+        );
+
+        assert_eq!(lcs(&backend, &["key1", "hash"])?, RESP_WRONGTYPE.clone());
+        let cmd = RespArray::new(
+            ["lcs", "key1", "key1", "len", "idx"]
+                .map(|arg| BulkString::from(arg).into())
+                .to_vec(),
+        );
+        assert!(matches!(
+            Command::try_from(cmd),
+            Err(CommandError::LcsLenAndIdx)
+        ));
+
+        backend.set_config("proto-max-bulk-len", "64").unwrap();
+        assert_eq!(
+            lcs(&backend, &["key1", "key1"])?,
+            CommandError::LcsTooLarge.into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_mset_and_mget() -> Result<()> {
+        let backend = Backend::new();
+        backend.hset(
+            b"hash".to_vec(),
+            "f".to_string(),
+            BulkString::from("v").into(),
+        );
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*5\r\n$4\r\nMSET\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n$1\r\n2\r\n");
+        let cmd: Command = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        assert_eq!(backend.get(b"b"), Some(BulkString::from("2").into()));
+
+        let cmd = MGet {
+            keys: vec![
+                b"a".to_vec(),
+                b"missing".to_vec(),
+                b"hash".to_vec(),
+                b"b".to_vec(),
+            ],
+        };
+        assert_eq!(
+            cmd.execute(&backend).encode(),
+            b"*4\r\n$1\r\n1\r\n$-1\r\n$-1\r\n$1\r\n2\r\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_mset_odd_argument_count() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$4\r\nmset\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n");
+        let ret = Command::try_from(RespArray::decode(&mut buf)?);
+        assert_eq!(
+            ret.unwrap_err().to_string(),
+            "ERR wrong number of arguments for 'mset' command"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_strlen_on_int_encoded_key() -> Result<()> {
+        let backend = Backend::new();
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$3\r\nset\r\n$1\r\nk\r\n$5\r\n12345\r\n");
+        buf.extend_from_slice(b"*2\r\n$6\r\nSTRLEN\r\n$1\r\nk\r\n");
+        let cmd: Command = RespArray::decode(&mut buf)?.try_into()?;
+        cmd.execute(&backend);
+        let cmd: Command = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(5));
+        assert_eq!(backend.object_encoding(b"k"), Some("int"));
+
+        // a value set as a RESP integer keeps that frame, its length is that of its digits
+        backend.set(b"n".to_vec(), RespFrame::Integer(-1024));
+        let cmd = Strlen { key: b"n".to_vec() };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(5));
+        assert_eq!(backend.get(b"n"), Some(RespFrame::Integer(-1024)));
+        assert_eq!(backend.object_encoding(b"n"), Some("int"));
+
+        let cmd = Strlen {
+            key: b"missing".to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        backend.add_member(b"set".to_vec(), "member".to_string());
+        let cmd = Strlen {
+            key: b"set".to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), RESP_WRONGTYPE.clone());
+        Ok(())
+    }
+
+    #[test]
+    fn test_append() -> Result<()> {
+        let backend = Backend::new();
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$6\r\nappend\r\n$3\r\nkey\r\n$5\r\nHello\r\n");
+        let cmd: Command = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(5));
+
+        let cmd = Append {
+            key: b"key".to_vec(),
+            value: b" World".to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(11));
+        assert_eq!(
+            backend.get(b"key"),
+            Some(BulkString::from("Hello World").into())
+        );
+        let cmd = Strlen {
+            key: b"key".to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(11));
+
+        // appending to a counter works on its digits
+        backend.set(b"n".to_vec(), RespFrame::Integer(12));
+        backend.expire(b"n", 100);
+        let cmd = Append {
+            key: b"n".to_vec(),
+            value: b"3".to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(3));
+        assert_eq!(backend.get(b"n"), Some(BulkString::from("123").into()));
+        assert_eq!(backend.ttl(b"n"), 100);
+        Ok(())
+    }
+
+    #[test]
+    fn test_append_errors() {
+        let backend = Backend::new();
+        backend.hset(
+            b"hash".to_vec(),
+            "f".to_string(),
+            BulkString::from("v").into(),
+        );
+        let cmd = Append {
+            key: b"hash".to_vec(),
+            value: b"x".to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), RESP_WRONGTYPE.clone());
+
+        backend.set_config("proto-max-bulk-len", "4").unwrap();
+        backend.set(b"key".to_vec(), BulkString::from("abc").into());
+        let cmd = Append {
+            key: b"key".to_vec(),
+            value: b"de".to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), CommandError::StringTooLong.into());
+        assert_eq!(backend.get(b"key"), Some(BulkString::from("abc").into()));
+    }
+
+    #[test]
+    fn test_concurrent_appends_stay_within_limit() {
+        const THREADS: usize = 8;
+        const KEYS: usize = 1000;
+        const LIMIT: usize = 2;
+
+        // every key is raced for right at the limit, where a check made before the entry
+        // lock lets more than one append through
+        let backend = Backend::new();
+        backend
+            .set_config("proto-max-bulk-len", &LIMIT.to_string())
+            .unwrap();
+        let start = std::sync::Arc::new(std::sync::Barrier::new(THREADS));
+        let handles: Vec<_> = (0..THREADS)
+            .map(|_| {
+                let (backend, start) = (backend.clone(), start.clone());
+                std::thread::spawn(move || {
+                    start.wait();
+                    let mut appended = 0;
+                    for key in 0..KEYS {
+                        let cmd = Append {
+                            key: format!("key:{}", key).into_bytes(),
+                            value: b"x".to_vec(),
+                        };
+                        if let RespFrame::Integer(_) = cmd.execute(&backend) {
+                            appended += 1;
+                        }
+                    }
+                    appended
+                })
+            })
+            .collect();
+        let appended: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+
+        assert_eq!(appended, KEYS * LIMIT);
+        for key in 0..KEYS {
+            let cmd = Strlen {
+                key: format!("key:{}", key).into_bytes(),
+            };
+            assert_eq!(cmd.execute(&backend), RespFrame::Integer(LIMIT as i64));
+        }
+    }
+
+    #[test]
+    fn test_getset() -> Result<()> {
+        let backend = Backend::new();
+        backend.set(b"key".to_vec(), BulkString::from("old").into());
+        backend.expire(b"key", 100);
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$6\r\ngetset\r\n$3\r\nkey\r\n$3\r\nnew\r\n");
+        let cmd: Command = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), BulkString::from("old").into());
+        assert_eq!(backend.get(b"key"), Some(BulkString::from("new").into()));
+        assert_eq!(backend.ttl(b"key"), -1);
+
+        let cmd = GetSet {
+            key: b"missing".to_vec(),
+            value: BulkString::from("v").into(),
+        };
+        assert_eq!(cmd.execute(&backend), RespNullBulkString.into());
+        assert_eq!(backend.get(b"missing"), Some(BulkString::from("v").into()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_getdel() -> Result<()> {
+        let backend = Backend::new();
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$6\r\ngetdel\r\n$7\r\nmissing\r\n");
+        let cmd: Command = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RespNullBulkString.into());
+
+        backend.set(b"key".to_vec(), BulkString::from("value").into());
+        let cmd = GetDel {
+            key: b"key".to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), BulkString::from("value").into());
+        assert_eq!(backend.get(b"key"), None);
+
+        backend.hset(
+            b"hash".to_vec(),
+            "f".to_string(),
+            BulkString::from("v").into(),
+        );
+        let cmd = GetDel {
+            key: b"hash".to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), RESP_WRONGTYPE.clone());
         Ok(())
     }
 }
@@ -1,18 +1,32 @@
+mod acl;
+mod config;
 mod echo;
 mod hmap;
+mod keyspace;
+mod list;
 mod map;
+mod server;
 mod set;
+mod zset;
 
-use enum_dispatch::enum_dispatch;
+use std::time::Duration;
+
+use bytes::BytesMut;
 use lazy_static::lazy_static;
 use thiserror::Error;
 
 use crate::RespArray;
+use crate::RespEncode;
+use crate::SimpleError;
 use crate::SimpleString;
-use crate::{RespError, RespFrame, backend::Backend};
+use crate::{LexBound, ListEnd, RespError, RespFrame, backend::Backend};
 
 lazy_static! {
     static ref RESP_OK: RespFrame = SimpleString::from("OK").into();
+    static ref RESP_WRONGTYPE: RespFrame =
+        SimpleError::from("WRONGTYPE Operation against a key holding the wrong kind of value")
+            .into();
+    static ref RESP_REPLY_TOO_LARGE: RespFrame = SimpleError::from("ERR reply too large").into();
 }
 
 #[derive(Error, Debug)]
@@ -21,6 +35,56 @@ pub enum CommandError {
     InvalidCommand(String),
     #[error("Invalid argument: {0}")]
     InvalidArgument(String),
+    #[error("ERR wrong number of arguments for '{0}' command")]
+    WrongArity(String),
+    #[error("ERR syntax error")]
+    SyntaxError,
+    #[error("ERR value is not an integer or out of range")]
+    NotAnInteger,
+    #[error("ERR hash value is not an integer")]
+    HashValueNotAnInteger,
+    #[error("ERR offset is out of range")]
+    OffsetOutOfRange,
+    #[error("ERR bit offset is not an integer or out of range")]
+    BitOffsetOutOfRange,
+    #[error("ERR bit is not an integer or out of range")]
+    BitOutOfRange,
+    #[error("ERR The bit argument must be 1 or 0.")]
+    BitNotZeroOrOne,
+    #[error("ERR string exceeds maximum allowed size (proto-max-bulk-len)")]
+    StringTooLong,
+    #[error("ERR timeout is not a float or out of range")]
+    InvalidTimeout,
+    #[error("ERR timeout is negative")]
+    NegativeTimeout,
+    #[error("ERR numkeys should be greater than 0")]
+    NumKeysNotPositive,
+    #[error("ERR Number of keys can't be greater than number of args")]
+    NumKeysTooLarge,
+    #[error("ERR LIMIT can't be negative")]
+    NegativeLimit,
+    #[error("ERR value is not a valid float")]
+    NotAFloat,
+    #[error("ERR min or max not valid string range item")]
+    InvalidLexRange,
+    #[error("ERR value is out of range, must be positive")]
+    NotPositive,
+    #[error("ERR increment or decrement would overflow")]
+    Overflow,
+    #[error("ERR no such key")]
+    NoSuchKey,
+    #[error("ERR index out of range")]
+    IndexOutOfRange,
+    #[error("ERR If you want both the length and indexes, please just use IDX.")]
+    LcsLenAndIdx,
+    #[error("ERR Insufficient memory, transient memory for LCS exceeds proto-max-bulk-len")]
+    LcsTooLarge,
+    #[error("ERR invalid expire time in '{0}' command")]
+    InvalidExpireTime(String),
+    #[error("NOPERM this user has no permissions to run the '{0}' command")]
+    NoPermission(String),
+    #[error("OOM command not allowed when used memory > 'maxmemory'.")]
+    OutOfMemory,
 
     #[error("{0}")]
     RespError(#[from] RespError),
@@ -28,36 +92,461 @@ pub enum CommandError {
     Utf8Error(#[from] std::string::FromUtf8Error),
 }
 
-#[enum_dispatch]
+impl From<CommandError> for RespFrame {
+    fn from(e: CommandError) -> Self {
+        SimpleError::new(e.to_string()).into()
+    }
+}
+
 pub trait CommandExecutor {
     fn execute(self, backend: &Backend) -> RespFrame;
 }
 
-#[derive(Debug)]
-#[enum_dispatch(CommandExecutor)]
-pub enum Command {
-    Get(Get),
-    Set(Set),
-    HGet(HGet),
-    HMGet(HMGet),
-    HSet(HSet),
-    HGetAll(HGetAll),
-    Unrecognized(Unrecognized),
-    Echo(Echo),
-    SisMember(SisMember),
-    AddMember(AddMember),
+// Declares the command set in one place: every entry gives the struct implementing the
+// command, its name, any aliases after `|`, the subcommand after the name for commands
+// like CONFIG GET, and the arity. Generates the enum with its `From` and `CommandExecutor`
+// impls, `command_name`, the arity table and the dispatch in `TryFrom<RespArray>`; each
+// struct still parses its own arguments with its own `TryFrom<RespArray>`. Subcommands of
+// the same command must share its arity. A trailing `write` marks the commands that may
+// store new data, those are refused once used memory is past maxmemory; commands that only
+// remove data, like DEL, aren't marked so memory can still be freed.
+macro_rules! register_commands {
+    (@write write) => {
+        true
+    };
+    (@write) => {
+        false
+    };
+    (
+        $vis:vis enum $enum:ident {
+            $($variant:ident => $name:literal $(| $alias:literal)* $($sub:literal)?, $arity:literal $(, $flag:ident)?;)*
+            _ => $fallback:ident,
+        }
+    ) => {
+        #[derive(Debug)]
+        $vis enum $enum {
+            $($variant($variant),)*
+            $fallback($fallback),
+        }
+
+        $(impl From<$variant> for $enum {
+            fn from(cmd: $variant) -> Self {
+                $enum::$variant(cmd)
+            }
+        })*
+
+        impl From<$fallback> for $enum {
+            fn from(cmd: $fallback) -> Self {
+                $enum::$fallback(cmd)
+            }
+        }
+
+        impl CommandExecutor for $enum {
+            fn execute(self, backend: &Backend) -> RespFrame {
+                match self {
+                    $($enum::$variant(cmd) => cmd.execute(backend),)*
+                    $enum::$fallback(cmd) => cmd.execute(backend),
+                }
+            }
+        }
+
+        impl $enum {
+            // same convention as redis: a positive arity is the exact number of arguments
+            // (command name included), a negative one is the minimum number of arguments
+            const ARITY: &[(&str, i64)] = &[$(($name, $arity), $(($alias, $arity),)*)*];
+
+            pub fn command_name(&self) -> &'static str {
+                match self {
+                    $($enum::$variant(_) => $name,)*
+                    $enum::$fallback(_) => "unrecognized",
+                }
+            }
+
+            pub fn is_write(&self) -> bool {
+                match self {
+                    $($enum::$variant(_) => register_commands!(@write $($flag)?),)*
+                    $enum::$fallback(_) => false,
+                }
+            }
+        }
+
+        impl TryFrom<RespArray> for $enum {
+            type Error = CommandError;
+
+            fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+                validate_arity(&value, Self::ARITY)?;
+                // command names are case insensitive, GET and Get are the same command
+                let name = match value.first() {
+                    Some(RespFrame::BulkString(cmd)) => {
+                        String::from_utf8_lossy(cmd).to_ascii_lowercase()
+                    }
+                    _ => {
+                        return Err(CommandError::InvalidCommand(
+                            "Command must have a BulkString as the first argument".to_string(),
+                        ));
+                    }
+                };
+                let sub = subcommand(&value);
+                // the arms without a guard catch an unknown subcommand, the first one wins
+                #[allow(unreachable_patterns)]
+                match name.as_str() {
+                    $(
+                        $name $(| $alias)* $(if sub.as_deref() == Some($sub.as_bytes()))? => {
+                            Ok($variant::try_from(value)?.into())
+                        }
+                    )*
+                    $($($name => {
+                        let _ = $sub;
+                        Err(unknown_subcommand(&value))
+                    })?)*
+                    _ => Ok($fallback.into()),
+                }
+            }
+        }
+    };
+}
+
+register_commands! {
+    pub enum Command {
+        Echo => "echo", 2;
+        Ping => "ping", -1;
+        Get => "get", 2;
+        Set => "set", -3, write;
+        SetNx => "setnx", 3, write;
+        MSet => "mset", -3, write;
+        MGet => "mget", -2;
+        Strlen => "strlen", 2;
+        Append => "append", 3, write;
+        GetSet => "getset", 3, write;
+        GetDel => "getdel", 2;
+        SetRange => "setrange", 4, write;
+        GetBit => "getbit", 3;
+        SetBit => "setbit", 4, write;
+        BitPos => "bitpos", -3;
+        Incr => "incr", 2, write;
+        Decr => "decr", 2, write;
+        IncrBy => "incrby", 3, write;
+        DecrBy => "decrby", 3, write;
+        Lcs => "lcs", -3;
+        HGet => "hget", 3;
+        HMGet => "hmget", -3;
+        HSet => "hset", -4, write;
+        HDel => "hdel", -3;
+        HExists => "hexists", 3;
+        HIncrBy => "hincrby", 4, write;
+        HGetAll => "hgetall", 2;
+        HKeys => "hkeys", 2;
+        HVals => "hvals", 2;
+        HExpire => "hexpire", -6, write;
+        HTtl => "httl", -5;
+        HPersist => "hpersist", -5;
+        // addmember is kept as an alias of sadd for older clients
+        SAdd => "sadd" | "addmember", -3, write;
+        SIsMember => "sismember", 3;
+        SRem => "srem", -3;
+        SMembers => "smembers", 2;
+        SCard => "scard", 2;
+        SInterCard => "sintercard", -3;
+        ZAdd => "zadd", -4, write;
+        ZRangeByLex => "zrangebylex", -4;
+        ZPopMin => "zpopmin", -2;
+        ZPopMax => "zpopmax", -2;
+        LPush => "lpush", -3, write;
+        RPush => "rpush", -3, write;
+        LRange => "lrange", 4;
+        LSet => "lset", 4, write;
+        LInsert => "linsert", 5, write;
+        RPopLPush => "rpoplpush", 3, write;
+        LMove => "lmove", 5, write;
+        LPop => "lpop", -2;
+        RPop => "rpop", -2;
+        ObjectEncoding => "object" "encoding", -2;
+        Scan => "scan", -2;
+        Type => "type", 2;
+        TypeIf => "typeif", 3;
+        Expire => "expire", 3, write;
+        Ttl => "ttl", 2;
+        Persist => "persist", 2;
+        WaitKey => "waitkey", 3;
+        FlushDb => "flushdb", -1;
+        DbSize => "dbsize", 1;
+        // there is no lazy freeing, UNLINK is DEL under another name
+        Del => "del" | "unlink", -2;
+        Exists => "exists", -2;
+        Touch => "touch", -2;
+        FlushAll => "flushall", -1;
+        Info => "info", -1;
+        Debug => "debug", -2;
+        Save => "save", 1;
+        BgSave => "bgsave", -1;
+        LastSave => "lastsave", 1;
+        Role => "role", 1;
+        ConfigGet => "config" "get", -2;
+        ConfigSet => "config" "set", -2;
+        ConfigRewrite => "config" "rewrite", -2;
+        MemoryDoctor => "memory" "doctor", -2;
+        MemoryUsage => "memory" "usage", -2;
+        AclWhoAmI => "acl" "whoami", -2;
+        AclGetUser => "acl" "getuser", -2;
+        _ => Unrecognized,
+    }
 }
 
 #[derive(Debug)]
-pub struct SisMember {
-    pub key: String,
+pub struct SIsMember {
+    pub key: Vec<u8>,
     pub member: String,
 }
 
 #[derive(Debug)]
-pub struct AddMember {
-    pub key: String,
-    pub member: String,
+pub struct SAdd {
+    pub key: Vec<u8>,
+    pub members: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SRem {
+    pub key: Vec<u8>,
+    pub members: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SMembers {
+    pub key: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct SCard {
+    pub key: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct SInterCard {
+    pub keys: Vec<Vec<u8>>,
+    // 0 means no limit
+    pub limit: usize,
+}
+
+#[derive(Debug)]
+pub struct ZAdd {
+    pub key: Vec<u8>,
+    pub members: Vec<(f64, String)>,
+}
+
+#[derive(Debug)]
+pub struct LPush {
+    pub key: Vec<u8>,
+    pub values: Vec<Vec<u8>>,
+}
+
+#[derive(Debug)]
+pub struct RPush {
+    pub key: Vec<u8>,
+    pub values: Vec<Vec<u8>>,
+}
+
+// without a count the reply is a single element, with one it is an array
+#[derive(Debug)]
+pub struct LPop {
+    pub key: Vec<u8>,
+    pub count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct RPop {
+    pub key: Vec<u8>,
+    pub count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct LRange {
+    pub key: Vec<u8>,
+    pub start: i64,
+    pub stop: i64,
+}
+
+#[derive(Debug)]
+pub struct LSet {
+    pub key: Vec<u8>,
+    pub index: i64,
+    pub value: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct LInsert {
+    pub key: Vec<u8>,
+    // AFTER, BEFORE otherwise
+    pub after: bool,
+    pub pivot: Vec<u8>,
+    pub value: Vec<u8>,
+}
+
+// the same as LMOVE source destination RIGHT LEFT
+#[derive(Debug)]
+pub struct RPopLPush {
+    pub source: Vec<u8>,
+    pub destination: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct LMove {
+    pub source: Vec<u8>,
+    pub destination: Vec<u8>,
+    pub from: ListEnd,
+    pub to: ListEnd,
+}
+
+// without a count a single member is popped
+#[derive(Debug)]
+pub struct ZPopMin {
+    pub key: Vec<u8>,
+    pub count: usize,
+}
+
+#[derive(Debug)]
+pub struct ZPopMax {
+    pub key: Vec<u8>,
+    pub count: usize,
+}
+
+#[derive(Debug)]
+pub struct ZRangeByLex {
+    pub key: Vec<u8>,
+    pub min: LexBound,
+    pub max: LexBound,
+    // offset and count of LIMIT, a negative count returns everything after the offset
+    pub limit: Option<(i64, i64)>,
+}
+
+#[derive(Debug)]
+pub struct Type {
+    pub key: Vec<u8>,
+}
+
+// not in redis, 1 when the key exists and holds `expected`, so a client can guard on the type
+// without a TYPE round trip first
+#[derive(Debug)]
+pub struct TypeIf {
+    pub key: Vec<u8>,
+    pub expected: String,
+}
+
+#[derive(Debug)]
+pub struct Expire {
+    pub key: Vec<u8>,
+    pub seconds: i64,
+}
+
+#[derive(Debug)]
+pub struct Ttl {
+    pub key: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct Persist {
+    pub key: Vec<u8>,
+}
+
+// blocks the connection until the key exists, see WaitKey::wait
+#[derive(Debug)]
+pub struct WaitKey {
+    pub key: Vec<u8>,
+    pub timeout: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct ObjectEncoding {
+    pub key: Vec<u8>,
+}
+
+// there is a single database, so FLUSHDB and FLUSHALL are the same and the
+// ASYNC/SYNC modes are accepted but behave identically
+#[derive(Debug)]
+pub struct FlushDb;
+
+#[derive(Debug)]
+pub struct DbSize;
+
+#[derive(Debug)]
+pub struct Del {
+    pub keys: Vec<Vec<u8>>,
+}
+
+// a key listed twice is counted twice
+#[derive(Debug)]
+pub struct Exists {
+    pub keys: Vec<Vec<u8>>,
+}
+
+// there is no LRU clock to update, TOUCH only counts the keys that exist
+#[derive(Debug)]
+pub struct Touch {
+    pub keys: Vec<Vec<u8>>,
+}
+
+#[derive(Debug)]
+pub struct FlushAll;
+
+// only the stats section is tracked, other sections reply empty
+#[derive(Debug)]
+pub struct Info {
+    pub section: Option<String>,
+}
+
+// DEBUG only supports RELOAD, the subcommands in DEBUG_NOOP_SUBCOMMANDS are no-ops
+#[derive(Debug)]
+pub struct Debug {
+    pub subcommand: String,
+}
+
+#[derive(Debug)]
+pub struct MemoryDoctor;
+
+#[derive(Debug)]
+pub struct MemoryUsage {
+    pub key: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct Save;
+
+#[derive(Debug)]
+pub struct BgSave;
+
+#[derive(Debug)]
+pub struct LastSave;
+
+// there is no replication, the server is always a master without replicas
+#[derive(Debug)]
+pub struct Role;
+
+#[derive(Debug)]
+pub struct Scan {
+    pub cursor: u64,
+    pub pattern: Option<Vec<u8>>,
+    pub count: usize,
+}
+
+#[derive(Debug)]
+pub struct ConfigGet {
+    pub name: String,
+}
+
+#[derive(Debug)]
+pub struct ConfigSet {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug)]
+pub struct ConfigRewrite;
+
+#[derive(Debug)]
+pub struct AclWhoAmI;
+
+#[derive(Debug)]
+pub struct AclGetUser {
+    pub name: String,
 }
 
 #[derive(Debug)]
@@ -65,6 +554,11 @@ pub struct Echo {
     pub message: String,
 }
 
+#[derive(Debug)]
+pub struct Ping {
+    pub message: Option<Vec<u8>>,
+}
+
 #[derive(Debug)]
 pub struct Unrecognized;
 
@@ -76,40 +570,241 @@ impl CommandExecutor for Unrecognized {
 
 #[derive(Debug)]
 pub struct Get {
-    pub key: String,
+    pub key: Vec<u8>,
 }
 
 #[derive(Debug)]
 pub struct Set {
-    pub key: String,
+    pub key: Vec<u8>,
+    pub value: RespFrame,
+    pub options: SetOptions,
+}
+
+// the options following the value of SET, see `SetOptions::parse`
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SetOptions {
+    pub condition: Option<SetCondition>,
+    pub expiry: Option<SetExpiry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetCondition {
+    // NX, only set a key that doesn't exist
+    IfMissing,
+    // XX, only set a key that already exists
+    IfExists,
+}
+
+// EX and PX are relative, EXAT and PXAT are unix times
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetExpiry {
+    Seconds(u64),
+    Milliseconds(u64),
+    UnixSeconds(u64),
+    UnixMilliseconds(u64),
+    KeepTtl,
+}
+
+// SET NX with an integer reply, 1 when the key was set
+#[derive(Debug)]
+pub struct SetNx {
+    pub key: Vec<u8>,
     pub value: RespFrame,
 }
 
+#[derive(Debug)]
+pub struct MSet {
+    pub pairs: Vec<(Vec<u8>, RespFrame)>,
+}
+
+// a missing key, or one holding another type, is a nil in the reply
+#[derive(Debug)]
+pub struct MGet {
+    pub keys: Vec<Vec<u8>>,
+}
+
+#[derive(Debug)]
+pub struct Strlen {
+    pub key: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct Append {
+    pub key: Vec<u8>,
+    pub value: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct GetSet {
+    pub key: Vec<u8>,
+    pub value: RespFrame,
+}
+
+#[derive(Debug)]
+pub struct GetDel {
+    pub key: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct SetRange {
+    pub key: Vec<u8>,
+    pub offset: usize,
+    pub value: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct GetBit {
+    pub key: Vec<u8>,
+    pub offset: usize,
+}
+
+#[derive(Debug)]
+pub struct SetBit {
+    pub key: Vec<u8>,
+    pub offset: usize,
+    pub on: bool,
+}
+
+// the range of BITPOS, `end` defaults to the end of the string
+#[derive(Debug)]
+pub struct BitPos {
+    pub key: Vec<u8>,
+    pub bit: u8,
+    pub start: i64,
+    pub end: Option<i64>,
+    pub unit: BitUnit,
+}
+
+// whether the range of a bit command counts bytes, the default, or bits
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BitUnit {
+    #[default]
+    Byte,
+    Bit,
+}
+
+#[derive(Debug)]
+pub struct Lcs {
+    pub key1: Vec<u8>,
+    pub key2: Vec<u8>,
+    pub len: bool,
+    pub idx: bool,
+    pub min_match_len: usize,
+    pub with_match_len: bool,
+}
+
+#[derive(Debug)]
+pub struct Incr {
+    pub key: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct Decr {
+    pub key: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct IncrBy {
+    pub key: Vec<u8>,
+    pub increment: i64,
+}
+
+#[derive(Debug)]
+pub struct DecrBy {
+    pub key: Vec<u8>,
+    pub decrement: i64,
+}
+
 #[derive(Debug)]
 pub struct HGet {
-    pub key: String,
+    pub key: Vec<u8>,
     pub field: String,
 }
 
 #[derive(Debug)]
 pub struct HMGet {
-    pub key: String,
+    pub key: Vec<u8>,
     pub fields: Vec<String>,
 }
 
 #[derive(Debug)]
-pub struct HSet {
-    pub key: String,
+pub struct HDel {
+    pub key: Vec<u8>,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct HExists {
+    pub key: Vec<u8>,
     pub field: String,
-    pub value: RespFrame,
+}
+
+#[derive(Debug)]
+pub struct HIncrBy {
+    pub key: Vec<u8>,
+    pub field: String,
+    pub delta: i64,
+}
+
+#[derive(Debug)]
+pub struct HSet {
+    pub key: Vec<u8>,
+    pub fields: Vec<(String, RespFrame)>,
+}
+
+#[derive(Debug)]
+pub struct HExpire {
+    pub key: Vec<u8>,
+    pub seconds: u64,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct HTtl {
+    pub key: Vec<u8>,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct HPersist {
+    pub key: Vec<u8>,
+    pub fields: Vec<String>,
 }
 
 #[derive(Debug)]
 pub struct HGetAll {
-    pub key: String,
+    pub key: Vec<u8>,
     pub sort: bool,
 }
 
+#[derive(Debug)]
+pub struct HKeys {
+    pub key: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct HVals {
+    pub key: Vec<u8>,
+}
+
+impl Command {
+    // replies that grow with the data, like every field of a hash, are better encoded into
+    // the write buffer element by element than built as one frame and then encoded
+    pub fn is_streamed(&self) -> bool {
+        matches!(self, Command::HKeys(_) | Command::HVals(_))
+    }
+
+    // runs the command and writes its reply to `dst`, the same bytes as encoding the frame
+    // `execute` returns
+    pub fn stream(self, backend: &Backend, dst: &mut BytesMut) {
+        match self {
+            Command::HKeys(cmd) => cmd.stream(backend, dst),
+            Command::HVals(cmd) => cmd.stream(backend, dst),
+            cmd => dst.extend_from_slice(&cmd.execute(backend).encode()),
+        }
+    }
+}
+
 impl TryFrom<RespFrame> for Command {
     type Error = CommandError;
 
@@ -123,30 +818,51 @@ impl TryFrom<RespFrame> for Command {
     }
 }
 
-impl TryFrom<RespArray> for Command {
-    type Error = CommandError;
+// a key holding another type can't be touched by a command of this type
+fn is_wrong_type(backend: &Backend, key: &[u8], expected: &str) -> bool {
+    backend.type_of(key).is_some_and(|t| t != expected)
+}
 
-    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        match value.first() {
-            Some(RespFrame::BulkString(cmd)) => match cmd.as_ref() {
-                b"echo" => Ok(Echo::try_from(value)?.into()),
-                b"get" => Ok(Get::try_from(value)?.into()),
-                b"set" => Ok(Set::try_from(value)?.into()),
-                b"hget" => Ok(HGet::try_from(value)?.into()),
-                b"hmget" => Ok(HMGet::try_from(value)?.into()),
-                b"hset" => Ok(HSet::try_from(value)?.into()),
-                b"hgetall" => Ok(HGetAll::try_from(value)?.into()),
-                b"addmember" => Ok(AddMember::try_from(value)?.into()),
-                b"sismember" => Ok(SisMember::try_from(value)?.into()),
-                _ => Ok(Unrecognized.into()),
-            },
-            _ => Err(CommandError::InvalidCommand(
-                "Command must have a BulkString as the first argument".to_string(),
-            )),
-        }
+// guards against building a huge reply, see the max-reply-elements config
+fn reply_too_large(backend: &Backend, elements: usize) -> bool {
+    let limit = backend.config().max_reply_elements;
+    limit > 0 && elements > limit
+}
+
+fn subcommand(frames: &RespArray) -> Option<Vec<u8>> {
+    match frames.get(1) {
+        Some(RespFrame::BulkString(sub)) => Some(sub.to_ascii_lowercase()),
+        _ => None,
     }
 }
 
+fn unknown_subcommand(frames: &RespArray) -> CommandError {
+    let name = |i: usize| match frames.get(i) {
+        Some(RespFrame::BulkString(s)) => String::from_utf8_lossy(s).into_owned(),
+        _ => String::new(),
+    };
+    CommandError::InvalidCommand(format!(
+        "unknown subcommand '{}' for '{}'",
+        name(1),
+        name(0)
+    ))
+}
+
+fn validate_arity(frames: &RespArray, arities: &[(&str, i64)]) -> Result<(), CommandError> {
+    let Some(RespFrame::BulkString(cmd)) = frames.first() else {
+        return Ok(());
+    };
+    let name = String::from_utf8_lossy(cmd).to_ascii_lowercase();
+    let Some((_, arity)) = arities.iter().find(|(n, _)| *n == name) else {
+        return Ok(());
+    };
+    let argc = frames.len() as i64;
+    if (*arity > 0 && argc != *arity) || (*arity < 0 && argc < -*arity) {
+        return Err(CommandError::WrongArity(name));
+    }
+    Ok(())
+}
+
 fn validate_command(
     frames: &RespArray,
     cmds: &[&'static str],
@@ -180,6 +896,16 @@ fn validate_command(
     Ok(())
 }
 
+fn parse_integer<T: std::str::FromStr>(frame: &RespFrame) -> Result<T, CommandError> {
+    match frame {
+        RespFrame::BulkString(s) => std::str::from_utf8(s)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or(CommandError::NotAnInteger),
+        _ => Err(CommandError::NotAnInteger),
+    }
+}
+
 fn extract_args(frames: RespArray, start: usize) -> Result<Vec<RespFrame>, CommandError> {
     Ok(frames.0.into_iter().skip(start).collect::<Vec<RespFrame>>())
 }
@@ -189,10 +915,104 @@ mod tests {
     use crate::RespDecode;
 
     use super::*;
-    use crate::RespNull;
+    use crate::{BulkString, RespNullBulkString};
     use anyhow::Result;
     use bytes::BytesMut;
 
+    #[derive(Debug)]
+    struct Double {
+        value: i64,
+    }
+
+    impl TryFrom<RespArray> for Double {
+        type Error = CommandError;
+
+        fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+            let value = parse_integer(&value[1])?;
+            Ok(Double { value })
+        }
+    }
+
+    impl CommandExecutor for Double {
+        fn execute(self, _: &Backend) -> RespFrame {
+            RespFrame::Integer(self.value * 2)
+        }
+    }
+
+    register_commands! {
+        enum TestCommand {
+            Double => "double" | "twice", 2, write;
+            ConfigGet => "config" "get", -2;
+            _ => Unrecognized,
+        }
+    }
+
+    fn parse_test_command(args: &[&str]) -> Result<TestCommand, CommandError> {
+        RespArray::new(
+            args.iter()
+                .map(|arg| BulkString::from(*arg).into())
+                .collect::<Vec<RespFrame>>(),
+        )
+        .try_into()
+    }
+
+    #[test]
+    fn test_register_commands() -> Result<()> {
+        let backend = Backend::new();
+        for name in ["double", "TWICE"] {
+            let cmd = parse_test_command(&[name, "21"])?;
+            assert_eq!(cmd.command_name(), "double");
+            assert!(cmd.is_write());
+            assert_eq!(cmd.execute(&backend), RespFrame::Integer(42));
+        }
+        assert_eq!(
+            parse_test_command(&["twice"]).unwrap_err().to_string(),
+            "ERR wrong number of arguments for 'twice' command"
+        );
+        assert!(matches!(
+            parse_test_command(&["double", "x"]),
+            Err(CommandError::NotAnInteger)
+        ));
+
+        let cmd = parse_test_command(&["config", "GET", "maxmemory"])?;
+        assert!(matches!(cmd, TestCommand::ConfigGet(_)));
+        assert!(!cmd.is_write());
+        assert_eq!(
+            parse_test_command(&["config", "set"])
+                .unwrap_err()
+                .to_string(),
+            "Invalid command: unknown subcommand 'set' for 'config'"
+        );
+        assert!(matches!(
+            parse_test_command(&["get", "key"])?,
+            TestCommand::Unrecognized(_)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_command_name_is_case_insensitive() -> Result<()> {
+        for name in ["SET", "set", "Set"] {
+            let mut buf = BytesMut::new();
+            buf.extend_from_slice(
+                format!("*3\r\n$3\r\n{}\r\n$5\r\nhello\r\n$5\r\nworld\r\n", name).as_bytes(),
+            );
+            let cmd: Command = RespArray::decode(&mut buf)?.try_into()?;
+            assert!(
+                matches!(cmd, Command::Set(_)),
+                "{} parsed to {:?}",
+                name,
+                cmd
+            );
+        }
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$4\r\nHSet\r\n$3\r\nmap\r\n$1\r\nf\r\n$1\r\nv\r\n");
+        let cmd: Command = RespArray::decode(&mut buf)?.try_into()?;
+        assert!(matches!(cmd, Command::HSet(_)));
+        Ok(())
+    }
+
     #[test]
     fn test_command() -> Result<()> {
         let mut buf = BytesMut::new();
@@ -203,7 +1023,22 @@ mod tests {
 
         let backend = Backend::new();
         let ret = cmd.execute(&backend);
-        assert_eq!(ret, RespFrame::Null(RespNull));
+        assert_eq!(ret, RespFrame::NullBulkString(RespNullBulkString));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_command_wrong_arity() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$3\r\nset\r\n$5\r\nhello\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        let ret = Command::try_from(frame);
+        assert_eq!(
+            ret.unwrap_err().to_string(),
+            "ERR wrong number of arguments for 'set' command"
+        );
 
         Ok(())
     }
@@ -0,0 +1,507 @@
+use lazy_static::lazy_static;
+use tracing::warn;
+
+use crate::{
+    Backend, BulkString, RespArray, RespFrame, RespNullBulkString, SimpleError, SimpleString,
+};
+
+use super::{
+    BgSave, CommandError, CommandExecutor, Debug, Info, LastSave, MemoryDoctor, MemoryUsage, Ping,
+    RESP_OK, Role, Save, extract_args, parse_integer, subcommand, unknown_subcommand,
+    validate_command,
+};
+
+// tuning knobs of the redis internals that don't exist here; test suites send them
+// freely so they are acknowledged instead of failing the client
+const DEBUG_NOOP_SUBCOMMANDS: &[&str] = &[
+    "quicklist-packed-threshold",
+    "stringmatch-len",
+    "set-active-expire",
+    "set-skip-checksum-validation",
+    "set-disable-deny-scripts",
+    "dict-resizing",
+    "pause-cron",
+    "replybuffer",
+    "listpack",
+    "quicklist",
+    "change-repl-id",
+];
+
+impl CommandExecutor for Info {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let mut info = String::new();
+        let wanted = |name: &str| {
+            self.section
+                .as_deref()
+                .is_none_or(|s| matches!(s, "all" | "default" | "everything") || s == name)
+        };
+        if wanted("stats") {
+            let stats = backend.stats();
+            info.push_str("# Stats\r\n");
+            info.push_str(&format!(
+                "total_net_input_bytes:{}\r\n",
+                stats.total_net_input_bytes()
+            ));
+            info.push_str(&format!(
+                "total_net_output_bytes:{}\r\n",
+                stats.total_net_output_bytes()
+            ));
+            info.push_str(&format!(
+                "total_writes_processed:{}\r\n",
+                stats.total_writes_processed()
+            ));
+        }
+        if wanted("memory") {
+            info.push_str("# Memory\r\n");
+            info.push_str(&format!("used_memory:{}\r\n", backend.used_memory()));
+            info.push_str(&format!("maxmemory:{}\r\n", backend.config().maxmemory));
+        }
+        BulkString::from(info).into()
+    }
+}
+
+// share of maxmemory past which MEMORY DOCTOR starts complaining
+const MEMORY_DOCTOR_HIGH_USAGE_PCT: usize = 90;
+
+impl CommandExecutor for MemoryDoctor {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let used = backend.used_memory();
+        let maxmemory = backend.config().maxmemory;
+        let report = if used == 0 {
+            "Hi Sam, this instance is empty or is using very little memory, my issues detector \
+             can't be used in these conditions. Please, leave for your mission on Earth and fill \
+             it with some data."
+                .to_string()
+        } else if maxmemory > 0 && used * 100 >= maxmemory * MEMORY_DOCTOR_HIGH_USAGE_PCT {
+            format!(
+                "Sam, I detected a few issues in this Redis instance memory implants:\n\n \
+                 * High memory usage: used_memory is {} bytes, {}% of the maxmemory limit of {} \
+                 bytes. Consider raising maxmemory or removing unused keys.\n\n\
+                 I'm here to keep you safe, Sam. I want to help you.\n",
+                used,
+                used * 100 / maxmemory,
+                maxmemory
+            )
+        } else {
+            "Hi Sam, I can't find any memory issue in your instance. I can only account for \
+             what occurs on this base."
+                .to_string()
+        };
+        BulkString::from(report).into()
+    }
+}
+
+impl TryFrom<RespArray> for MemoryDoctor {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["memory", "doctor"], 0)?;
+        Ok(MemoryDoctor)
+    }
+}
+
+impl CommandExecutor for MemoryUsage {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.memory_usage(&self.key) {
+            Some(bytes) => RespFrame::Integer(bytes as i64),
+            None => RespFrame::NullBulkString(RespNullBulkString),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for MemoryUsage {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["memory", "usage"], 1)?;
+
+        let mut args = extract_args(value, 2)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => key.into_vec(),
+            _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        };
+        // every value is walked in full, so SAMPLES is accepted and has nothing to tune
+        match (args.next(), args.next(), args.next()) {
+            (None, _, _) => {}
+            (Some(RespFrame::BulkString(opt)), Some(n), None)
+                if opt.eq_ignore_ascii_case(b"samples") =>
+            {
+                parse_integer::<i64>(&n)?;
+            }
+            _ => return Err(CommandError::SyntaxError),
+        }
+        Ok(MemoryUsage { key })
+    }
+}
+
+impl TryFrom<RespArray> for Info {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["info"], 0)?;
+
+        let args = extract_args(value, 1)?;
+        match args.as_slice() {
+            [] => Ok(Info { section: None }),
+            [RespFrame::BulkString(section)] => Ok(Info {
+                section: Some(String::from_utf8_lossy(section).to_ascii_lowercase()),
+            }),
+            _ => Err(CommandError::SyntaxError),
+        }
+    }
+}
+
+lazy_static! {
+    static ref RESP_BGSAVE_IN_PROGRESS: RespFrame =
+        SimpleError::from("ERR Background save already in progress").into();
+}
+
+// the error reply when the dataset can't be saved right now or the save failed
+fn save(backend: &Backend) -> Option<RespFrame> {
+    // a foreground save would race the background one on the same temporary file
+    if backend.stats().bgsave_in_progress() {
+        return Some(RESP_BGSAVE_IN_PROGRESS.clone());
+    }
+    match backend.save() {
+        Ok(()) => None,
+        Err(e) => {
+            let path = backend.config().snapshot_path();
+            warn!("failed to save snapshot to {}: {:?}", path.display(), e);
+            Some(SimpleError::new("ERR Error trying to save the DB".to_string()).into())
+        }
+    }
+}
+
+impl CommandExecutor for Save {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        save(backend).unwrap_or_else(|| RESP_OK.clone())
+    }
+}
+
+impl CommandExecutor for BgSave {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if backend.bgsave() {
+            SimpleString::new("Background saving started").into()
+        } else {
+            RESP_BGSAVE_IN_PROGRESS.clone()
+        }
+    }
+}
+
+impl CommandExecutor for LastSave {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.stats().last_save() as i64)
+    }
+}
+
+impl CommandExecutor for Role {
+    fn execute(self, _: &Backend) -> RespFrame {
+        RespArray::new([
+            BulkString::from("master").into(),
+            RespFrame::Integer(0),
+            RespArray::new([]).into(),
+        ])
+        .into()
+    }
+}
+
+impl TryFrom<RespArray> for Save {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["save"], 0)?;
+        Ok(Save)
+    }
+}
+
+impl TryFrom<RespArray> for BgSave {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["bgsave"], 0)?;
+
+        // SCHEDULE only matters while an AOF rewrite runs, which never happens here
+        match extract_args(value, 1)?.as_slice() {
+            [] => Ok(BgSave),
+            [RespFrame::BulkString(opt)] if opt.eq_ignore_ascii_case(b"schedule") => Ok(BgSave),
+            _ => Err(CommandError::SyntaxError),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for LastSave {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["lastsave"], 0)?;
+        Ok(LastSave)
+    }
+}
+
+impl TryFrom<RespArray> for Role {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["role"], 0)?;
+        Ok(Role)
+    }
+}
+
+impl CommandExecutor for Ping {
+    fn execute(self, _: &Backend) -> RespFrame {
+        match self.message {
+            Some(message) => BulkString::new(message).into(),
+            None => SimpleString::new("PONG").into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Ping {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["ping"], 0)?;
+
+        let args = extract_args(value, 1)?;
+        match args.as_slice() {
+            [] => Ok(Ping { message: None }),
+            [RespFrame::BulkString(message)] => Ok(Ping {
+                message: Some(message.to_vec()),
+            }),
+            _ => Err(CommandError::WrongArity("ping".to_string())),
+        }
+    }
+}
+
+impl CommandExecutor for Debug {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if self.subcommand == "reload" {
+            return debug_reload(backend);
+        }
+        tracing::debug!(subcommand = %self.subcommand, "ignoring DEBUG subcommand");
+        RESP_OK.clone()
+    }
+}
+
+// save the dataset and load it straight back, so tests can check nothing is lost on the way
+fn debug_reload(backend: &Backend) -> RespFrame {
+    if let Some(err) = save(backend) {
+        return err;
+    }
+    let path = backend.config().snapshot_path();
+    match backend.load_snapshot(&path) {
+        Ok(()) => RESP_OK.clone(),
+        Err(e) => {
+            warn!("failed to load snapshot from {}: {:?}", path.display(), e);
+            SimpleError::new("ERR Error trying to load the DB".to_string()).into()
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Debug {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["debug"], 1)?;
+
+        let sub = subcommand(&value).unwrap_or_default();
+        let sub = String::from_utf8_lossy(&sub).into_owned();
+        if sub != "reload" && !DEBUG_NOOP_SUBCOMMANDS.contains(&sub.as_str()) {
+            return Err(unknown_subcommand(&value));
+        }
+        Ok(Debug { subcommand: sub })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RespDecode;
+    use crate::cmd::Command;
+    use anyhow::Result;
+    use bytes::BytesMut;
+    use std::time::Duration;
+
+    #[test]
+    fn test_info_stats_section() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$4\r\ninfo\r\n$5\r\nSTATS\r\n");
+        let cmd = Info::try_from(RespArray::decode(&mut buf)?)?;
+        assert_eq!(cmd.section.as_deref(), Some("stats"));
+
+        let backend = Backend::new();
+        backend.stats().record_input(10);
+        backend.stats().record_output(5);
+        let ret = cmd.execute(&backend);
+        assert_eq!(
+            ret,
+            BulkString::from(
+                "# Stats\r\ntotal_net_input_bytes:10\r\ntotal_net_output_bytes:5\r\n\
+                 total_writes_processed:0\r\n"
+            )
+            .into()
+        );
+
+        let ret = Info {
+            section: Some("keyspace".to_string()),
+        }
+        .execute(&backend);
+        assert_eq!(ret, BulkString::from("").into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ping() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*1\r\n$4\r\nPING\r\n*2\r\n$4\r\nping\r\n$5\r\nhello\r\n");
+        let backend = Backend::new();
+        let cmd = Ping::try_from(RespArray::decode(&mut buf)?)?;
+        assert_eq!(cmd.execute(&backend), SimpleString::new("PONG").into());
+        let cmd = Ping::try_from(RespArray::decode(&mut buf)?)?;
+        assert_eq!(cmd.execute(&backend), BulkString::from("hello").into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_memory_doctor() -> Result<()> {
+        let backend = Backend::new();
+        backend.set(b"hello".to_vec(), BulkString::from("world").into());
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$6\r\nmemory\r\n$6\r\nDOCTOR\r\n");
+        let cmd = MemoryDoctor::try_from(RespArray::decode(&mut buf)?)?;
+        let RespFrame::BulkString(report) = cmd.execute(&backend) else {
+            panic!("expected a bulk string reply");
+        };
+        assert!(!report.is_empty());
+        assert!(report.starts_with(b"Hi Sam, I can't find any memory issue"));
+
+        backend.set_config("maxmemory", "10").unwrap();
+        let RespFrame::BulkString(report) = MemoryDoctor.execute(&backend) else {
+            panic!("expected a bulk string reply");
+        };
+        assert!(report.starts_with(b"Sam, I detected a few issues"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_memory_usage_int_encoded() -> Result<()> {
+        let backend = Backend::new();
+        backend.set(b"n".to_vec(), BulkString::from("1234567890123").into());
+        backend.set(b"s".to_vec(), BulkString::new(vec![b'x'; 100]).into());
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$6\r\nmemory\r\n$5\r\nUSAGE\r\n$1\r\nn\r\n");
+        let cmd = MemoryUsage::try_from(RespArray::decode(&mut buf)?)?;
+        let RespFrame::Integer(int_size) = cmd.execute(&backend) else {
+            panic!("expected an integer reply");
+        };
+        let cmd = MemoryUsage { key: b"s".to_vec() };
+        let RespFrame::Integer(raw_size) = cmd.execute(&backend) else {
+            panic!("expected an integer reply");
+        };
+        assert_eq!(int_size, 1 + 8);
+        assert_eq!(raw_size, 1 + 100);
+
+        let cmd = MemoryUsage {
+            key: b"missing".to_vec(),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespFrame::NullBulkString(RespNullBulkString)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug_noop_subcommand() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*3\r\n$5\r\ndebug\r\n$26\r\nQUICKLIST-PACKED-THRESHOLD\r\n$2\r\n1K\r\n",
+        );
+        let cmd = Debug::try_from(RespArray::decode(&mut buf)?)?;
+        assert_eq!(cmd.subcommand, "quicklist-packed-threshold");
+        assert_eq!(cmd.execute(&Backend::new()), RESP_OK.clone());
+
+        buf.extend_from_slice(b"*2\r\n$5\r\ndebug\r\n$7\r\nsegfault\r\n");
+        let ret = Debug::try_from(RespArray::decode(&mut buf)?);
+        assert!(matches!(ret, Err(CommandError::InvalidCommand(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_role() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*1\r\n$4\r\nROLE\r\n");
+        let cmd: Command = RespArray::decode(&mut buf)?.try_into()?;
+        let RespFrame::Array(reply) = cmd.execute(&Backend::new()) else {
+            panic!("expected an array reply");
+        };
+        assert_eq!(reply[0], BulkString::from("master").into());
+        assert_eq!(reply[1], RespFrame::Integer(0));
+        assert_eq!(reply[2], RespArray::new([]).into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_lastsave_increases_after_save() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let backend = Backend::new();
+        backend
+            .set_config("dir", dir.path().to_str().unwrap())
+            .map_err(anyhow::Error::msg)?;
+        backend.set(b"hello".to_vec(), BulkString::from("world").into());
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*1\r\n$8\r\nlastsave\r\n");
+        let cmd = LastSave::try_from(RespArray::decode(&mut buf)?)?;
+        let RespFrame::Integer(started) = cmd.execute(&backend) else {
+            panic!("expected an integer reply");
+        };
+
+        // LASTSAVE has a resolution of one second
+        std::thread::sleep(Duration::from_millis(1100));
+        buf.extend_from_slice(b"*1\r\n$4\r\nsave\r\n");
+        let cmd = Save::try_from(RespArray::decode(&mut buf)?)?;
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        assert!(dir.path().join("dump.rdb").exists());
+
+        let RespFrame::Integer(saved) = LastSave.execute(&backend) else {
+            panic!("expected an integer reply");
+        };
+        assert!(saved > started);
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug_reload() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let backend = Backend::new();
+        backend
+            .set_config("dir", dir.path().to_str().unwrap())
+            .map_err(anyhow::Error::msg)?;
+        backend.set(b"hello".to_vec(), BulkString::from("world").into());
+        backend.hset(
+            b"map".to_vec(),
+            "field".to_string(),
+            BulkString::from("value").into(),
+        );
+        backend.add_member(b"set".to_vec(), "member".to_string());
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$5\r\ndebug\r\n$6\r\nRELOAD\r\n");
+        let cmd = Debug::try_from(RespArray::decode(&mut buf)?)?;
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+
+        assert_eq!(
+            backend.get(b"hello"),
+            Some(BulkString::from("world").into())
+        );
+        assert_eq!(
+            backend.hget(b"map", "field"),
+            Some(BulkString::from("value").into())
+        );
+        assert_eq!(backend.type_of(b"set"), Some("set"));
+        Ok(())
+    }
+}
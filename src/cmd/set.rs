@@ -1,40 +1,141 @@
 use crate::{Backend, RespArray, RespFrame};
 
-use super::{AddMember, CommandError, CommandExecutor, SisMember, extract_args, validate_command};
+use super::{
+    CommandError, CommandExecutor, RESP_REPLY_TOO_LARGE, RESP_WRONGTYPE, SAdd, SCard, SInterCard,
+    SIsMember, SMembers, SRem, extract_args, is_wrong_type, parse_integer, reply_too_large,
+    validate_command,
+};
 
-impl CommandExecutor for AddMember {
+impl CommandExecutor for SAdd {
     fn execute(self, backend: &Backend) -> RespFrame {
-        backend.add_member(self.key, self.member);
-        RespFrame::Integer(1)
+        if is_wrong_type(backend, &self.key, "set") {
+            return RESP_WRONGTYPE.clone();
+        }
+        let added = self
+            .members
+            .into_iter()
+            .filter(|member| backend.add_member(self.key.clone(), member.clone()))
+            .count();
+        RespFrame::Integer(added as i64)
+    }
+}
+
+impl CommandExecutor for SRem {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "set") {
+            return RESP_WRONGTYPE.clone();
+        }
+        let removed = self
+            .members
+            .iter()
+            .filter(|member| backend.srem(&self.key, member))
+            .count();
+        RespFrame::Integer(removed as i64)
+    }
+}
+
+impl CommandExecutor for SCard {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "set") {
+            return RESP_WRONGTYPE.clone();
+        }
+        RespFrame::Integer(backend.scard(&self.key) as i64)
     }
 }
 
-impl CommandExecutor for SisMember {
+impl CommandExecutor for SIsMember {
     fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "set") {
+            return RESP_WRONGTYPE.clone();
+        }
         backend.sis_member(self.key, self.member)
     }
 }
 
-impl TryFrom<RespArray> for AddMember {
+impl CommandExecutor for SMembers {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "set") {
+            return RESP_WRONGTYPE.clone();
+        }
+        match backend.set.get(&self.key) {
+            Some(set) if reply_too_large(backend, set.len()) => RESP_REPLY_TOO_LARGE.clone(),
+            Some(set) => {
+                RespArray::from_iter_bulk(set.iter().map(|m| m.as_bytes().to_vec())).into()
+            }
+            None => RespArray::new([]).into(),
+        }
+    }
+}
+
+impl CommandExecutor for SInterCard {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if self.keys.iter().any(|k| is_wrong_type(backend, k, "set")) {
+            return RESP_WRONGTYPE.clone();
+        }
+        RespFrame::Integer(backend.sintercard(&self.keys, self.limit) as i64)
+    }
+}
+
+impl TryFrom<RespArray> for SAdd {
     type Error = CommandError;
 
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["addmember"], 2)?;
+        validate_command(&value, &["sadd"], 2)
+            .or_else(|_| validate_command(&value, &["addmember"], 2))?;
 
         let mut args = extract_args(value, 1)?.into_iter();
-        match (args.next(), args.next()) {
-            (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(member))) => {
-                Ok(AddMember {
-                    key: String::from_utf8(key.0)?,
-                    member: String::from_utf8(member.0)?,
-                })
-            }
-            _ => Err(CommandError::InvalidCommand("Invalid command".to_string())),
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => key.into_vec(),
+            _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        };
+        let members = args
+            .map(|frame| match frame {
+                RespFrame::BulkString(member) => Ok(String::from_utf8(member.into_vec())?),
+                _ => Err(CommandError::InvalidArgument("Invalid member".to_string())),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(SAdd { key, members })
+    }
+}
+
+impl TryFrom<RespArray> for SRem {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["srem"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => key.into_vec(),
+            _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        };
+        let members = args
+            .map(|frame| match frame {
+                RespFrame::BulkString(member) => Ok(String::from_utf8(member.into_vec())?),
+                _ => Err(CommandError::InvalidArgument("Invalid member".to_string())),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(SRem { key, members })
+    }
+}
+
+impl TryFrom<RespArray> for SCard {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["scard"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(SCard {
+                key: key.into_vec(),
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
         }
     }
 }
 
-impl TryFrom<RespArray> for SisMember {
+impl TryFrom<RespArray> for SIsMember {
     type Error = CommandError;
 
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
@@ -43,12 +144,266 @@ impl TryFrom<RespArray> for SisMember {
         let mut args = extract_args(value, 1)?.into_iter();
         match (args.next(), args.next()) {
             (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(member))) => {
-                Ok(SisMember {
-                    key: String::from_utf8(key.0)?,
-                    member: String::from_utf8(member.0)?,
+                Ok(SIsMember {
+                    key: key.into_vec(),
+                    member: String::from_utf8(member.into_vec())?,
                 })
             }
             _ => Err(CommandError::InvalidCommand("Invalid command".to_string())),
         }
     }
 }
+
+impl TryFrom<RespArray> for SMembers {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["smembers"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(SMembers {
+                key: key.into_vec(),
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for SInterCard {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["sintercard"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let numkeys = args.next().ok_or(CommandError::NotAnInteger)?;
+        let numkeys = match parse_integer::<i64>(&numkeys)? {
+            n if n <= 0 => return Err(CommandError::NumKeysNotPositive),
+            n => n as usize,
+        };
+        let keys = args
+            .by_ref()
+            .take(numkeys)
+            .map(|frame| match frame {
+                RespFrame::BulkString(key) => Ok(key.into_vec()),
+                _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if keys.len() < numkeys {
+            return Err(CommandError::NumKeysTooLarge);
+        }
+
+        let mut limit = 0;
+        while let Some(arg) = args.next() {
+            match (arg, args.next()) {
+                (RespFrame::BulkString(opt), Some(n)) if opt.eq_ignore_ascii_case(b"limit") => {
+                    limit = match parse_integer::<i64>(&n)? {
+                        n if n < 0 => return Err(CommandError::NegativeLimit),
+                        n => n as usize,
+                    };
+                }
+                _ => return Err(CommandError::SyntaxError),
+            }
+        }
+        Ok(SInterCard { keys, limit })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RespDecode;
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_sadd_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$4\r\nsadd\r\n$3\r\nset\r\n$1\r\na\r\n$1\r\nb\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: SAdd = frame.try_into()?;
+        assert_eq!(result.key, b"set");
+        assert_eq!(result.members, vec!["a", "b"]);
+
+        buf.extend_from_slice(b"*3\r\n$9\r\naddmember\r\n$3\r\nset\r\n$1\r\na\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: SAdd = frame.try_into()?;
+        assert_eq!(result.members, vec!["a"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sadd_returns_added_count() {
+        let backend = Backend::new();
+        let members = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+
+        let cmd = SAdd {
+            key: b"set".to_vec(),
+            members: members.clone(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(3));
+
+        let cmd = SAdd {
+            key: b"set".to_vec(),
+            members,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+
+        let cmd = SIsMember {
+            key: b"set".to_vec(),
+            member: "b".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+    }
+
+    #[test]
+    fn test_sadd_mixed_new_and_existing_members() {
+        let backend = Backend::new();
+        backend.add_member(b"set".to_vec(), "a".to_string());
+        backend.add_member(b"set".to_vec(), "b".to_string());
+
+        let cmd = SAdd {
+            key: b"set".to_vec(),
+            members: ["a", "c", "b", "d", "d"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        assert_eq!(backend.set.get(b"set".as_slice()).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_sismember_on_string_key() {
+        let backend = Backend::new();
+        backend.set(b"key".to_vec(), RespFrame::BulkString(b"value".into()));
+
+        let cmd = SIsMember {
+            key: b"key".to_vec(),
+            member: "value".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RESP_WRONGTYPE.clone());
+    }
+
+    #[test]
+    fn test_sadd_on_hash_key() {
+        let backend = Backend::new();
+        backend.hset(
+            b"key".to_vec(),
+            "field".to_string(),
+            RespFrame::BulkString(b"value".into()),
+        );
+
+        let cmd = SAdd {
+            key: b"key".to_vec(),
+            members: vec!["member".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), RESP_WRONGTYPE.clone());
+        assert_eq!(backend.type_of(b"key"), Some("hash"));
+    }
+
+    #[test]
+    fn test_srem_and_scard() -> Result<()> {
+        let backend = Backend::new();
+        for member in ["a", "b", "c"] {
+            backend.add_member(b"set".to_vec(), member.to_string());
+        }
+        backend.expire(b"set", 100);
+        let scard = |key: &[u8]| SCard { key: key.to_vec() }.execute(&backend);
+        assert_eq!(scard(b"set"), RespFrame::Integer(3));
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$4\r\nsrem\r\n$3\r\nset\r\n$1\r\na\r\n$1\r\nx\r\n");
+        let cmd: SRem = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(scard(b"set"), RespFrame::Integer(2));
+
+        // removing the last members removes the key, SMEMBERS then sees an empty set
+        let cmd = SRem {
+            key: b"set".to_vec(),
+            members: vec!["b".to_string(), "c".to_string(), "c".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        assert_eq!(scard(b"set"), RespFrame::Integer(0));
+        assert_eq!(backend.type_of(b"set"), None);
+        assert_eq!(backend.ttl(b"set"), -2);
+        let cmd = SMembers {
+            key: b"set".to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), RespArray::new([]).into());
+
+        backend.set(b"string".to_vec(), RespFrame::BulkString(b"value".into()));
+        assert_eq!(scard(b"string"), RESP_WRONGTYPE.clone());
+        let cmd = SRem {
+            key: b"string".to_vec(),
+            members: vec!["a".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), RESP_WRONGTYPE.clone());
+        Ok(())
+    }
+
+    #[test]
+    fn test_smembers_reply_limit() -> Result<()> {
+        let backend = Backend::new();
+        for member in ["a", "b", "c"] {
+            backend.add_member(b"set".to_vec(), member.to_string());
+        }
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$8\r\nsmembers\r\n$3\r\nset\r\n");
+        let cmd: SMembers = RespArray::decode(&mut buf)?.try_into()?;
+        let RespFrame::Array(members) = cmd.execute(&backend) else {
+            panic!("expected an array reply");
+        };
+        assert_eq!(members.len(), 3);
+
+        backend.set_config("max-reply-elements", "2").unwrap();
+        let cmd = SMembers {
+            key: b"set".to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), RESP_REPLY_TOO_LARGE.clone());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sintercard() -> Result<()> {
+        let backend = Backend::new();
+        for member in ["a", "b", "c", "d"] {
+            backend.add_member(b"s1".to_vec(), member.to_string());
+        }
+        for member in ["b", "c", "d", "e"] {
+            backend.add_member(b"s2".to_vec(), member.to_string());
+        }
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$10\r\nsintercard\r\n$1\r\n2\r\n$2\r\ns1\r\n$2\r\ns2\r\n");
+        let cmd: SInterCard = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.limit, 0);
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(3));
+
+        buf.extend_from_slice(
+            b"*6\r\n$10\r\nsintercard\r\n$1\r\n2\r\n$2\r\ns1\r\n$2\r\ns2\r\n$5\r\nLIMIT\r\n$1\r\n2\r\n",
+        );
+        let cmd: SInterCard = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.limit, 2);
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+
+        let cmd = SInterCard {
+            keys: vec![b"s1".to_vec(), b"missing".to_vec()],
+            limit: 0,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+
+        buf.extend_from_slice(b"*3\r\n$10\r\nsintercard\r\n$1\r\n2\r\n$2\r\ns1\r\n");
+        let ret: Result<SInterCard, _> = RespArray::decode(&mut buf)?.try_into();
+        assert!(matches!(ret, Err(CommandError::NumKeysTooLarge)));
+
+        buf.extend_from_slice(b"*3\r\n$10\r\nsintercard\r\n$1\r\n0\r\n$2\r\ns1\r\n");
+        let ret: Result<SInterCard, _> = RespArray::decode(&mut buf)?.try_into();
+        assert!(matches!(ret, Err(CommandError::NumKeysNotPositive)));
+        Ok(())
+    }
+}
@@ -0,0 +1,291 @@
+use crate::{Backend, LexBound, RespArray, RespFrame};
+
+use super::{
+    CommandError, CommandExecutor, RESP_WRONGTYPE, ZAdd, ZPopMax, ZPopMin, ZRangeByLex,
+    extract_args, is_wrong_type, parse_integer, validate_command,
+};
+
+impl CommandExecutor for ZAdd {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "zset") {
+            return RESP_WRONGTYPE.clone();
+        }
+        RespFrame::Integer(backend.zadd(self.key, self.members) as i64)
+    }
+}
+
+impl CommandExecutor for ZRangeByLex {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if is_wrong_type(backend, &self.key, "zset") {
+            return RESP_WRONGTYPE.clone();
+        }
+        let (offset, count) = match self.limit {
+            // a negative offset is an empty range in redis
+            Some((offset, _)) if offset < 0 => return RespArray::new([]).into(),
+            Some((offset, count)) => (offset as usize, usize::try_from(count).ok()),
+            None => (0, None),
+        };
+        let members = backend.zrange_by_lex(&self.key, &self.min, &self.max, offset, count);
+        RespArray::from_iter_bulk(members.into_iter().map(String::into_bytes)).into()
+    }
+}
+
+impl CommandExecutor for ZPopMin {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        zpop(backend, &self.key, self.count, false)
+    }
+}
+
+impl CommandExecutor for ZPopMax {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        zpop(backend, &self.key, self.count, true)
+    }
+}
+
+// a flat array of member, score, member, score... with the scores as bulk strings
+fn zpop(backend: &Backend, key: &[u8], count: usize, max: bool) -> RespFrame {
+    if is_wrong_type(backend, key, "zset") {
+        return RESP_WRONGTYPE.clone();
+    }
+    let popped = backend.zpop(key, count, max);
+    RespArray::from_iter_bulk(
+        popped
+            .into_iter()
+            .flat_map(|(member, score)| [member.into_bytes(), score.to_string().into_bytes()]),
+    )
+    .into()
+}
+
+impl TryFrom<RespArray> for ZAdd {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["zadd"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => key.into_vec(),
+            _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        };
+        let mut members = Vec::new();
+        while let Some(score) = args.next() {
+            let score = parse_integer::<f64>(&score)
+                .ok()
+                .filter(|s| !s.is_nan())
+                .ok_or(CommandError::NotAFloat)?;
+            match args.next() {
+                Some(RespFrame::BulkString(member)) => {
+                    members.push((score, String::from_utf8(member.into_vec())?))
+                }
+                _ => return Err(CommandError::SyntaxError),
+            }
+        }
+        Ok(ZAdd { key, members })
+    }
+}
+
+impl TryFrom<RespArray> for ZRangeByLex {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["zrangebylex"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let (key, min, max) = match (args.next(), args.next(), args.next()) {
+            (
+                Some(RespFrame::BulkString(key)),
+                Some(RespFrame::BulkString(min)),
+                Some(RespFrame::BulkString(max)),
+            ) => (key.into_vec(), min, max),
+            _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        };
+        let min = LexBound::parse(&min).ok_or(CommandError::InvalidLexRange)?;
+        let max = LexBound::parse(&max).ok_or(CommandError::InvalidLexRange)?;
+
+        let limit = match (args.next(), args.next(), args.next(), args.next()) {
+            (None, ..) => None,
+            (Some(RespFrame::BulkString(opt)), Some(offset), Some(count), None)
+                if opt.eq_ignore_ascii_case(b"limit") =>
+            {
+                Some((parse_integer(&offset)?, parse_integer(&count)?))
+            }
+            _ => return Err(CommandError::SyntaxError),
+        };
+        Ok(ZRangeByLex {
+            key,
+            min,
+            max,
+            limit,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZPopMin {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["zpopmin"], 1)?;
+        let (key, count) = key_and_count(value)?;
+        Ok(ZPopMin { key, count })
+    }
+}
+
+impl TryFrom<RespArray> for ZPopMax {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["zpopmax"], 1)?;
+        let (key, count) = key_and_count(value)?;
+        Ok(ZPopMax { key, count })
+    }
+}
+
+fn key_and_count(value: RespArray) -> Result<(Vec<u8>, usize), CommandError> {
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = match args.next() {
+        Some(RespFrame::BulkString(key)) => key.into_vec(),
+        _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+    };
+    let count = match (args.next(), args.next()) {
+        (None, _) => 1,
+        (Some(count), None) => match parse_integer::<i64>(&count)? {
+            n if n < 0 => return Err(CommandError::NotPositive),
+            n => n as usize,
+        },
+        _ => return Err(CommandError::SyntaxError),
+    };
+    Ok((key, count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RespDecode;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    fn populated_backend() -> Backend {
+        let backend = Backend::new();
+        let members = ["a", "b", "c", "d", "e"]
+            .into_iter()
+            .map(|m| (0.0, m.to_string()))
+            .collect();
+        backend.zadd(b"zset".to_vec(), members);
+        backend
+    }
+
+    fn range(backend: &Backend, min: &[u8], max: &[u8], limit: Option<(i64, i64)>) -> RespFrame {
+        ZRangeByLex {
+            key: b"zset".to_vec(),
+            min: LexBound::parse(min).unwrap(),
+            max: LexBound::parse(max).unwrap(),
+            limit,
+        }
+        .execute(backend)
+    }
+
+    fn members(members: &[&str]) -> RespFrame {
+        RespArray::from_iter_bulk(members.iter().map(|m| m.as_bytes().to_vec())).into()
+    }
+
+    #[test]
+    fn test_zadd() -> Result<()> {
+        let backend = Backend::new();
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*6\r\n$4\r\nzadd\r\n$4\r\nzset\r\n$1\r\n1\r\n$3\r\none\r\n$3\r\n2.5\r\n$3\r\ntwo\r\n",
+        );
+        let cmd: ZAdd = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+
+        let cmd = ZAdd {
+            key: b"zset".to_vec(),
+            members: vec![(3.0, "one".to_string()), (4.0, "three".to_string())],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.type_of(b"zset"), Some("zset"));
+
+        buf.extend_from_slice(b"*4\r\n$4\r\nzadd\r\n$4\r\nzset\r\n$3\r\nabc\r\n$1\r\nm\r\n");
+        let ret: Result<ZAdd, _> = RespArray::decode(&mut buf)?.try_into();
+        assert!(matches!(ret, Err(CommandError::NotAFloat)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrangebylex_bounds() -> Result<()> {
+        let backend = populated_backend();
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$11\r\nzrangebylex\r\n$4\r\nzset\r\n$1\r\n-\r\n$1\r\n+\r\n");
+        let cmd: ZRangeByLex = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), members(&["a", "b", "c", "d", "e"]));
+
+        assert_eq!(
+            range(&backend, b"[b", b"[d", None),
+            members(&["b", "c", "d"])
+        );
+        assert_eq!(range(&backend, b"(b", b"(d", None), members(&["c"]));
+        assert_eq!(range(&backend, b"-", b"(c", None), members(&["a", "b"]));
+        assert_eq!(range(&backend, b"[d", b"+", None), members(&["d", "e"]));
+        assert_eq!(range(&backend, b"+", b"-", None), members(&[]));
+        assert_eq!(
+            range(&backend, b"-", b"+", Some((1, 2))),
+            members(&["b", "c"])
+        );
+        assert_eq!(
+            range(&backend, b"-", b"+", Some((3, -1))),
+            members(&["d", "e"])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_zpopmin_zpopmax() -> Result<()> {
+        let backend = Backend::new();
+        backend.zadd(
+            b"zset".to_vec(),
+            vec![
+                (3.0, "c".to_string()),
+                (1.0, "a".to_string()),
+                (2.5, "b".to_string()),
+                (1.0, "aa".to_string()),
+            ],
+        );
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$7\r\nzpopmin\r\n$4\r\nzset\r\n");
+        let cmd: ZPopMin = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), members(&["a", "1"]));
+
+        buf.extend_from_slice(b"*3\r\n$7\r\nZPOPMAX\r\n$4\r\nzset\r\n$1\r\n2\r\n");
+        let cmd: ZPopMax = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), members(&["c", "3", "b", "2.5"]));
+
+        // asking for more than there is pops what is left and drops the key
+        let cmd = ZPopMin {
+            key: b"zset".to_vec(),
+            count: 10,
+        };
+        assert_eq!(cmd.execute(&backend), members(&["aa", "1"]));
+        assert_eq!(backend.type_of(b"zset"), None);
+        let cmd = ZPopMax {
+            key: b"zset".to_vec(),
+            count: 1,
+        };
+        assert_eq!(cmd.execute(&backend), members(&[]));
+
+        buf.extend_from_slice(b"*3\r\n$7\r\nzpopmin\r\n$4\r\nzset\r\n$2\r\n-1\r\n");
+        let ret: Result<ZPopMin, _> = RespArray::decode(&mut buf)?.try_into();
+        assert!(matches!(ret, Err(CommandError::NotPositive)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrangebylex_invalid_range_item() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$11\r\nzrangebylex\r\n$4\r\nzset\r\n$1\r\na\r\n$1\r\n+\r\n");
+        let ret: Result<ZRangeByLex, _> = RespArray::decode(&mut buf)?.try_into();
+        assert!(matches!(ret, Err(CommandError::InvalidLexRange)));
+        Ok(())
+    }
+}
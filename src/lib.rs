@@ -3,7 +3,10 @@ mod cmd;
 mod resp;
 mod respv2;
 
+pub mod client;
 pub mod network;
+#[cfg(feature = "testing")]
+pub mod testing;
 
 pub use backend::*;
 pub use resp::*;
@@ -1,5 +1,5 @@
-use anyhow::Result;
-use simple_redis::{Backend, network};
+use anyhow::{Error, Result};
+use simple_redis::{Backend, BackendConfig, network};
 use tokio::net::TcpListener;
 use tracing::{info, warn};
 
@@ -12,7 +12,14 @@ async fn main() -> Result<()> {
 
     let listener = TcpListener::bind(addr).await?;
 
-    let backend = Backend::new();
+    // like redis-server, the only argument is the path of a config file
+    let backend = match std::env::args().nth(1) {
+        Some(path) => Backend::with_config(BackendConfig::from_file(path).map_err(Error::msg)?),
+        None => Backend::new(),
+    };
+    // SIGUSR1 takes a snapshot, the same as BGSAVE
+    #[cfg(unix)]
+    backend.bgsave_on_signal(tokio::signal::unix::SignalKind::user_defined1())?;
 
     loop {
         let (stream, raddr) = listener.accept().await?;
@@ -1,5 +1,5 @@
-use crate::resp::{BUF_CAP, CRLF_LEN, calc_total_length, extract_fixed_data, parse_length};
-use crate::{RespDecode, RespEncode, RespError, RespFrame};
+use crate::resp::{BUF_CAP, CRLF_LEN, MAX_PREALLOC, calc_total_length, parse_length};
+use crate::{BulkString, RespDecode, RespEncode, RespError, RespFrame};
 use bytes::{Buf, BytesMut};
 use std::ops::Deref;
 
@@ -10,6 +10,12 @@ impl RespArray {
     pub fn new(s: impl Into<Vec<RespFrame>>) -> Self {
         RespArray(s.into())
     }
+
+    /// Build an array of bulk strings straight from owned byte buffers, so multi-bulk
+    /// replies don't need an intermediate `Vec<RespFrame>` built by the caller.
+    pub fn from_iter_bulk(iter: impl Iterator<Item = Vec<u8>>) -> Self {
+        RespArray(iter.map(|v| BulkString::new(v).into()).collect())
+    }
 }
 
 impl Deref for RespArray {
@@ -22,9 +28,6 @@ impl Deref for RespArray {
 // - array: "*<number-of-elements>\r\n<element-1>...<element-n>"
 impl RespEncode for RespArray {
     fn encode(self) -> Vec<u8> {
-        if self.0.is_empty() {
-            return b"*-1\r\n".to_vec();
-        }
         let mut buf = Vec::with_capacity(BUF_CAP);
         buf.extend_from_slice(&format!("*{}\r\n", self.0.len()).into_bytes());
         for frame in self.0 {
@@ -37,9 +40,6 @@ impl RespEncode for RespArray {
 impl RespDecode for RespArray {
     const PREFIX: &'static str = "*";
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        if extract_fixed_data(buf, "*-1\r\n", "NullArray").is_ok() {
-            return Ok(RespArray::new(vec![]));
-        }
         let (end, len) = parse_length(buf, Self::PREFIX)?;
         let total_len = calc_total_length(buf, end, len, Self::PREFIX)?;
 
@@ -49,7 +49,7 @@ impl RespDecode for RespArray {
 
         buf.advance(end + CRLF_LEN);
 
-        let mut frames = Vec::with_capacity(len);
+        let mut frames = Vec::with_capacity(len.min(MAX_PREALLOC));
         for _ in 0..len {
             frames.push(RespFrame::decode(buf)?);
         }
@@ -73,17 +73,41 @@ impl From<Vec<RespFrame>> for RespArray {
 
 #[cfg(test)]
 mod tests {
-    use crate::{BulkString, RespArray, RespDecode, RespEncode, RespError, RespFrame};
+    use crate::resp::MAX_PREALLOC;
+    use crate::{
+        BulkString, RespArray, RespDecode, RespEncode, RespError, RespFrame, RespNullArray,
+    };
     use bytes::BytesMut;
 
     #[test]
-    fn test_null_array_decode() -> anyhow::Result<()> {
+    fn test_empty_array_decode() -> anyhow::Result<()> {
         let mut buf = BytesMut::new();
-        buf.extend_from_slice(b"*-1\r\n");
+        buf.extend_from_slice(b"*0\r\n");
 
         let frame = RespArray::decode(&mut buf)?;
         assert_eq!(frame, RespArray::new(vec![]));
 
+        buf.extend_from_slice(b"*-1\r\n");
+        assert!(RespArray::decode(&mut buf).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_and_null_array_round_trip() -> anyhow::Result<()> {
+        let empty: RespFrame = RespArray::new([]).into();
+        let null: RespFrame = RespNullArray.into();
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(&empty.clone().encode());
+        buf.extend_from_slice(&null.clone().encode());
+        assert_eq!(&buf[..], b"*0\r\n*-1\r\n");
+
+        assert_eq!(RespFrame::expect_length(&buf)?, 4);
+        assert_eq!(RespFrame::decode(&mut buf)?, empty);
+        assert_eq!(RespFrame::expect_length(&buf)?, 5);
+        assert_eq!(RespFrame::decode(&mut buf)?, null);
+        assert!(buf.is_empty());
         Ok(())
     }
 
@@ -104,6 +128,35 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_huge_array_header_decode() {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*1000000000\r\n$3\r\nset\r\n");
+        let ret = RespArray::decode(&mut buf);
+        assert_eq!(ret.unwrap_err(), RespError::NotComplete);
+        assert_eq!(buf.len(), 22);
+    }
+
+    #[test]
+    fn test_large_array_decode_reserves_capped_slots() -> anyhow::Result<()> {
+        // the whole array is there, so the length check passes and decode reaches the
+        // reservation: capped at MAX_PREALLOC slots, the rest come from growing the Vec
+        let len = MAX_PREALLOC + 1;
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(format!("*{}\r\n", len).as_bytes());
+        for _ in 0..len {
+            buf.extend_from_slice(b":1\r\n");
+        }
+
+        let frame = RespArray::decode(&mut buf)?;
+        assert_eq!(frame.len(), len);
+        assert!(frame.iter().all(|v| *v == RespFrame::Integer(1)));
+        assert!(buf.is_empty());
+        // an exact reservation of `len` would leave no spare capacity
+        assert!(frame.0.capacity() > len);
+        Ok(())
+    }
+
     #[test]
     fn test_array_encode() {
         let frame: RespFrame = RespArray::new(vec![
@@ -119,8 +172,20 @@ mod tests {
     }
 
     #[test]
-    fn test_null_array_encode() {
+    fn test_array_from_iter_bulk() {
+        let data = vec![b"hello".to_vec(), b"world".to_vec()];
+        let frame = RespArray::from_iter_bulk(data.into_iter());
+        let expected = RespArray::new(vec![
+            BulkString::new(b"hello".to_vec()).into(),
+            BulkString::new(b"world".to_vec()).into(),
+        ]);
+        assert_eq!(frame, expected);
+        assert_eq!(frame.encode(), expected.encode());
+    }
+
+    #[test]
+    fn test_empty_array_encode() {
         let frame: RespFrame = RespArray::new(vec![]).into();
-        assert_eq!(frame.encode(), b"*-1\r\n");
+        assert_eq!(frame.encode(), b"*0\r\n");
     }
 }
@@ -0,0 +1,122 @@
+use crate::resp::CRLF_LEN;
+use crate::resp::extract_simple_frame_data;
+use crate::{RespDecode, RespEncode, RespError};
+use bytes::BytesMut;
+use std::ops::Deref;
+
+/// A RESP3 big number, an integer outside the range of `i64`.
+///
+/// Nothing here does arithmetic on it, so it is kept as its decimal digits, validated on
+/// construction, with a leading `-` when negative.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
+pub struct RespBigNumber(pub(crate) String);
+
+impl Deref for RespBigNumber {
+    type Target = str;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl RespBigNumber {
+    /// Validates `s` as an optionally signed run of decimal digits, a `+` sign is dropped.
+    pub fn new(s: impl Into<String>) -> Result<Self, RespError> {
+        let s = s.into();
+        let digits = s.strip_prefix(['+', '-']).unwrap_or(&s);
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(RespError::InvalidFrame(format!(
+                "invalid big number: {}",
+                s
+            )));
+        }
+        Ok(match s.strip_prefix('+') {
+            Some(digits) => RespBigNumber(digits.to_string()),
+            None => RespBigNumber(s),
+        })
+    }
+}
+
+impl From<i64> for RespBigNumber {
+    fn from(n: i64) -> Self {
+        RespBigNumber(n.to_string())
+    }
+}
+
+impl From<i128> for RespBigNumber {
+    fn from(n: i128) -> Self {
+        RespBigNumber(n.to_string())
+    }
+}
+
+impl From<u128> for RespBigNumber {
+    fn from(n: u128) -> Self {
+        RespBigNumber(n.to_string())
+    }
+}
+
+// - big number: "([+|-]<number>\r\n"
+impl RespEncode for RespBigNumber {
+    fn encode(self) -> Vec<u8> {
+        format!("({}\r\n", self.0).into_bytes()
+    }
+}
+
+impl RespDecode for RespBigNumber {
+    const PREFIX: &'static str = "(";
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let end = extract_simple_frame_data(buf, Self::PREFIX)?;
+        let data = buf.split_to(end + CRLF_LEN);
+        let s = String::from_utf8_lossy(&data[Self::PREFIX.len()..end]);
+        RespBigNumber::new(s)
+    }
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        let end = extract_simple_frame_data(buf, Self::PREFIX)?;
+        Ok(end + CRLF_LEN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{RespBigNumber, RespDecode, RespEncode, RespError, RespFrame};
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_big_number_decode() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"(3492890328409238509324850943850943825024385\r\n");
+        let frame = RespBigNumber::decode(&mut buf)?;
+        assert_eq!(&*frame, "3492890328409238509324850943850943825024385");
+
+        buf.extend_from_slice(b"(+170141183460469231731687303715884105727\r\n");
+        let frame = RespBigNumber::decode(&mut buf)?;
+        assert_eq!(frame, RespBigNumber::from(i128::MAX));
+
+        buf.extend_from_slice(b"(-9223372036854775809\r\n");
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(frame, RespBigNumber::from(i64::MIN as i128 - 1).into());
+
+        buf.extend_from_slice(b"(12a\r\n");
+        assert!(matches!(
+            RespBigNumber::decode(&mut buf),
+            Err(RespError::InvalidFrame(_))
+        ));
+        assert!(RespBigNumber::new("-").is_err());
+
+        buf.extend_from_slice(b"(123");
+        let ret = RespBigNumber::decode(&mut buf);
+        assert_eq!(ret.unwrap_err(), RespError::NotComplete);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_big_number_encode() {
+        let frame: RespFrame = RespBigNumber::from(u128::MAX).into();
+        assert_eq!(
+            frame.encode(),
+            b"(340282366920938463463374607431768211455\r\n"
+        );
+        let frame: RespFrame = RespBigNumber::new("-18446744073709551616").unwrap().into();
+        assert_eq!(frame.encode(), b"(-18446744073709551616\r\n");
+    }
+}
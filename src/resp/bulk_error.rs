@@ -0,0 +1,96 @@
+use crate::resp::CRLF_LEN;
+use crate::resp::parse_length;
+use crate::{RespDecode, RespEncode, RespError};
+use bytes::{Buf, BytesMut};
+use std::ops::Deref;
+
+/// A RESP3 bulk error, an error whose message is length prefixed so unlike a
+/// [`SimpleError`](crate::SimpleError) it may span several lines.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
+pub struct BulkError(pub(crate) Vec<u8>);
+
+impl Deref for BulkError {
+    type Target = Vec<u8>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl BulkError {
+    pub fn new(s: impl Into<Vec<u8>>) -> Self {
+        BulkError(s.into())
+    }
+}
+
+impl From<&str> for BulkError {
+    fn from(s: &str) -> Self {
+        BulkError(s.as_bytes().to_vec())
+    }
+}
+
+// - bulk error: "!<length>\r\n<error>\r\n"
+impl RespEncode for BulkError {
+    fn encode(self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.len() + 16);
+        buf.extend_from_slice(&format!("!{}\r\n", self.len()).into_bytes());
+        buf.extend_from_slice(&self);
+        buf.extend_from_slice(b"\r\n");
+        buf
+    }
+}
+
+impl RespDecode for BulkError {
+    const PREFIX: &'static str = "!";
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let (end, len) = parse_length(buf, Self::PREFIX)?;
+        let remained = &buf[end + CRLF_LEN..];
+        if remained.len() < len + CRLF_LEN {
+            return Err(RespError::NotComplete);
+        }
+
+        buf.advance(end + CRLF_LEN);
+        let data = buf.split_to(len + CRLF_LEN);
+        Ok(BulkError::new(&data[..len]))
+    }
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        let (end, len) = parse_length(buf, Self::PREFIX)?;
+        Ok(end + CRLF_LEN + len + CRLF_LEN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BulkError, RespDecode, RespEncode, RespError, RespFrame};
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_bulk_error_encode() {
+        let frame: RespFrame = BulkError::from("SYNTAX invalid syntax").into();
+        assert_eq!(frame.encode(), b"!21\r\nSYNTAX invalid syntax\r\n");
+    }
+
+    #[test]
+    fn test_bulk_error_decode() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"!21\r\nSYNTAX invalid syntax\r\n");
+        let frame = BulkError::decode(&mut buf)?;
+        assert_eq!(frame, BulkError::from("SYNTAX invalid syntax"));
+
+        buf.extend_from_slice(b"!5\r\nERR a");
+        let ret = BulkError::decode(&mut buf);
+        assert_eq!(ret.unwrap_err(), RespError::NotComplete);
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_line_bulk_error_round_trip() -> anyhow::Result<()> {
+        let error = BulkError::from("ERR script failed\r\nat line 3\r\n\r\nstack traceback");
+        let frame: RespFrame = error.clone().into();
+        let mut buf = BytesMut::from(frame.encode().as_slice());
+        assert_eq!(RespFrame::expect_length(&buf)?, buf.len());
+
+        assert_eq!(RespFrame::decode(&mut buf)?, error.into());
+        assert!(buf.is_empty());
+        Ok(())
+    }
+}
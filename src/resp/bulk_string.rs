@@ -1,16 +1,16 @@
 use crate::resp::CRLF_LEN;
 use crate::resp::parse_length;
 use crate::{RespDecode, RespEncode, RespError};
-use bytes::{Buf, BytesMut};
+use bytes::{Buf, Bytes, BytesMut};
 use std::ops::Deref;
 
-use super::extract_fixed_data;
-
+// the payload is reference counted, a decoded bulk string shares the allocation of the
+// read buffer instead of copying out of it; anything kept past the request is detached
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
-pub struct BulkString(pub(crate) Vec<u8>);
+pub struct BulkString(pub(crate) Bytes);
 
 impl Deref for BulkString {
-    type Target = Vec<u8>;
+    type Target = [u8];
     fn deref(&self) -> &Self::Target {
         &self.0
     }
@@ -18,31 +18,49 @@ impl Deref for BulkString {
 
 impl BulkString {
     pub fn new(s: impl Into<Vec<u8>>) -> Self {
-        BulkString(s.into())
+        BulkString(Bytes::from(s.into()))
+    }
+
+    /// The payload as an owned vector, free when this is the only handle to it.
+    pub fn into_vec(self) -> Vec<u8> {
+        self.0.into()
+    }
+
+    /// A copy holding only its own payload. A decoded bulk string keeps the whole read
+    /// buffer it was sliced from alive, so a value stored for longer than the request must
+    /// not be one.
+    pub fn detach(self) -> Self {
+        BulkString(Bytes::copy_from_slice(&self.0))
+    }
+}
+
+impl From<Bytes> for BulkString {
+    fn from(s: Bytes) -> Self {
+        BulkString(s)
     }
 }
 
 impl From<&str> for BulkString {
     fn from(s: &str) -> Self {
-        BulkString(s.as_bytes().to_vec())
+        BulkString(Bytes::copy_from_slice(s.as_bytes()))
     }
 }
 
 impl From<String> for BulkString {
     fn from(s: String) -> Self {
-        BulkString(s.into_bytes())
+        BulkString(Bytes::from(s))
     }
 }
 
 impl From<&[u8]> for BulkString {
     fn from(s: &[u8]) -> Self {
-        BulkString(s.to_vec())
+        BulkString(Bytes::copy_from_slice(s))
     }
 }
 
 impl<const N: usize> From<&[u8; N]> for BulkString {
     fn from(s: &[u8; N]) -> Self {
-        BulkString(s.to_vec())
+        BulkString(Bytes::copy_from_slice(s))
     }
 }
 
@@ -55,9 +73,6 @@ impl AsRef<[u8]> for BulkString {
 // - bulk string: "$<length>\r\n<data>\r\n"
 impl RespEncode for BulkString {
     fn encode(self) -> Vec<u8> {
-        if self.0.is_empty() {
-            return b"$-1\r\n".to_vec();
-        }
         let mut buf = Vec::with_capacity(self.len() + 16);
         buf.extend_from_slice(&format!("${}\r\n", self.len()).into_bytes());
         buf.extend_from_slice(&self);
@@ -69,9 +84,6 @@ impl RespEncode for BulkString {
 impl RespDecode for BulkString {
     const PREFIX: &'static str = "$";
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        if extract_fixed_data(buf, "$-1\r\n", "BulkString").is_ok() {
-            return Ok(BulkString::new(vec![]));
-        }
         let (end, len) = parse_length(buf, Self::PREFIX)?;
         let remained = &buf[end + CRLF_LEN..];
         if remained.len() < len + CRLF_LEN {
@@ -80,8 +92,9 @@ impl RespDecode for BulkString {
 
         buf.advance(end + CRLF_LEN);
 
-        let data = buf.split_to(len + CRLF_LEN);
-        Ok(BulkString::new(data[..len].to_vec()))
+        let data = buf.split_to(len).freeze();
+        buf.advance(CRLF_LEN);
+        Ok(BulkString(data))
     }
     fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
         if buf.starts_with(b"$-1\r\n") {
@@ -94,7 +107,7 @@ impl RespDecode for BulkString {
 
 #[cfg(test)]
 mod tests {
-    use crate::{BulkString, RespDecode, RespEncode, RespError, RespFrame};
+    use crate::{BulkString, RespDecode, RespEncode, RespError, RespFrame, RespNullBulkString};
     use bytes::BytesMut;
 
     #[test]
@@ -117,13 +130,50 @@ mod tests {
     }
 
     #[test]
-    fn test_null_bulk_string_decode() -> anyhow::Result<()> {
+    fn test_large_bulk_string_decode_shares_buffer() -> anyhow::Result<()> {
+        let payload: Vec<u8> = (0..1024 * 1024).map(|i| (i % 251) as u8).collect();
         let mut buf = BytesMut::new();
-        buf.extend_from_slice(b"$-1\r\n");
+        buf.extend_from_slice(format!("${}\r\n", payload.len()).as_bytes());
+        let header_len = buf.len();
+        buf.extend_from_slice(&payload);
+        buf.extend_from_slice(b"\r\n");
+        let start = buf.as_ptr();
+
+        let frame = BulkString::decode(&mut buf)?;
+        assert_eq!(&frame[..], &payload[..]);
+        assert!(buf.is_empty());
+        // the payload still lives where it was read, nothing was copied
+        assert_eq!(frame.as_ptr(), start.wrapping_add(header_len));
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_bulk_string_decode() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"$0\r\n\r\n");
 
         let frame = BulkString::decode(&mut buf)?;
         assert_eq!(frame, BulkString::new(vec![]));
 
+        buf.extend_from_slice(b"$-1\r\n");
+        assert!(BulkString::decode(&mut buf).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_and_null_bulk_string_round_trip() -> anyhow::Result<()> {
+        let empty: RespFrame = BulkString::new(vec![]).into();
+        let null: RespFrame = RespNullBulkString.into();
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(&empty.clone().encode());
+        buf.extend_from_slice(&null.clone().encode());
+        assert_eq!(&buf[..], b"$0\r\n\r\n$-1\r\n");
+
+        assert_eq!(RespFrame::decode(&mut buf)?, empty);
+        assert_eq!(RespFrame::decode(&mut buf)?, null);
+        assert!(buf.is_empty());
         Ok(())
     }
 
@@ -134,8 +184,8 @@ mod tests {
     }
 
     #[test]
-    fn test_null_bulk_string_encode() {
+    fn test_empty_bulk_string_encode() {
         let frame: RespFrame = BulkString::new(vec![]).into();
-        assert_eq!(frame.encode(), b"$-1\r\n");
+        assert_eq!(frame.encode(), b"$0\r\n\r\n");
     }
 }
@@ -3,11 +3,28 @@ use crate::resp::extract_simple_frame_data;
 use crate::{RespDecode, RespEncode, RespError};
 use bytes::BytesMut;
 
+// RESP3 spells the values that aren't numbers `inf`, `-inf` and `nan`, while rust would
+// format them as `inf` and `NaN`, with a sign in front when asked for one
+pub(super) fn non_finite_token(d: f64) -> Option<&'static str> {
+    if d.is_nan() {
+        Some("nan")
+    } else if d == f64::INFINITY {
+        Some("inf")
+    } else if d == f64::NEG_INFINITY {
+        Some("-inf")
+    } else {
+        None
+    }
+}
+
 // - double: ",[<+|->]<integral>[.<fractional>][<E|e>[sign]<exponent>]\r\n"
+//   or ",inf\r\n", ",-inf\r\n" and ",nan\r\n"
 impl RespEncode for f64 {
     fn encode(self) -> Vec<u8> {
         let mut buf = Vec::with_capacity(32);
-        let ret = if self.abs() > 1e+8 || self.abs() < 1e-8 {
+        let ret = if let Some(token) = non_finite_token(self) {
+            format!(",{}\r\n", token)
+        } else if self.abs() > 1e+8 || self.abs() < 1e-8 {
             format!(",{:+e}\r\n", self)
         } else {
             let sign = if self < 0.0 { "" } else { "+" };
@@ -25,7 +42,12 @@ impl RespDecode for f64 {
         let end = extract_simple_frame_data(buf, Self::PREFIX)?;
         let data = buf.split_to(end + CRLF_LEN);
         let s = String::from_utf8_lossy(&data[Self::PREFIX.len()..end]);
-        Ok(s.parse()?)
+        match s.as_ref() {
+            "inf" => Ok(f64::INFINITY),
+            "-inf" => Ok(f64::NEG_INFINITY),
+            "nan" => Ok(f64::NAN),
+            s => Ok(s.parse()?),
+        }
     }
     fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
         let end = extract_simple_frame_data(buf, Self::PREFIX)?;
@@ -64,4 +86,22 @@ mod tests {
         let frame: RespFrame = (-1.23456e-9).into();
         assert_eq!(&frame.encode(), b",-1.23456e-9\r\n");
     }
+
+    #[test]
+    fn test_non_finite_double_round_trip() -> anyhow::Result<()> {
+        for (value, encoded) in [
+            (f64::INFINITY, b",inf\r\n".as_slice()),
+            (f64::NEG_INFINITY, b",-inf\r\n"),
+        ] {
+            assert_eq!(value.encode(), encoded);
+            let mut buf = BytesMut::from(encoded);
+            assert_eq!(f64::decode(&mut buf)?, value);
+        }
+
+        assert_eq!(f64::NAN.encode(), b",nan\r\n");
+        assert_eq!((-f64::NAN).encode(), b",nan\r\n");
+        let mut buf = BytesMut::from(b",nan\r\n".as_slice());
+        assert!(f64::decode(&mut buf)?.is_nan());
+        Ok(())
+    }
 }
@@ -1,8 +1,10 @@
+use crate::resp::{calc_total_length, parse_length};
 use crate::{
-    BulkString, RespArray, RespDecode, RespError, RespMap, RespNull, RespSet, SimpleError,
-    SimpleString,
+    BulkError, BulkString, RespArray, RespBigNumber, RespDecode, RespError, RespMap, RespNull,
+    RespNullArray, RespNullBulkString, RespPush, RespSet, SimpleError, SimpleString,
+    VerbatimString,
 };
-use bytes::BytesMut;
+use bytes::{Buf, BytesMut};
 use enum_dispatch::enum_dispatch;
 
 #[enum_dispatch(RespEncode)]
@@ -10,14 +12,54 @@ use enum_dispatch::enum_dispatch;
 pub enum RespFrame {
     SimpleString(SimpleString),
     Error(SimpleError),
+    BulkError(BulkError),
     Integer(i64),
     BulkString(BulkString),
+    NullBulkString(RespNullBulkString),
     Array(RespArray),
+    NullArray(RespNullArray),
     Null(RespNull),
     Boolean(bool),
     Double(f64),
+    BigNumber(RespBigNumber),
+    VerbatimString(VerbatimString),
     Map(RespMap),
     Set(RespSet),
+    Push(RespPush),
+}
+
+impl RespFrame {
+    /// Approximate number of payload bytes held by the frame, used for memory accounting.
+    pub fn byte_len(&self) -> usize {
+        match self {
+            RespFrame::SimpleString(s) => s.len(),
+            RespFrame::Error(e) => e.len(),
+            RespFrame::BulkError(e) => e.len(),
+            RespFrame::BulkString(s) => s.len(),
+            RespFrame::BigNumber(n) => n.len(),
+            RespFrame::VerbatimString(s) => s.data.len(),
+            RespFrame::Integer(_) | RespFrame::Double(_) => 8,
+            RespFrame::Boolean(_) => 1,
+            RespFrame::NullBulkString(_) | RespFrame::NullArray(_) | RespFrame::Null(_) => 0,
+            RespFrame::Array(a) => a.iter().map(RespFrame::byte_len).sum(),
+            RespFrame::Set(s) => s.iter().map(RespFrame::byte_len).sum(),
+            RespFrame::Push(p) => p.iter().map(RespFrame::byte_len).sum(),
+            RespFrame::Map(m) => m.iter().map(|(k, v)| k.len() + v.byte_len()).sum(),
+        }
+    }
+
+    /// Decode the first frame of `buf` and hand back the bytes following it untouched, e.g.
+    /// to forward them as is. The tail shares the allocation of `buf`, nothing is copied.
+    /// On an error the buffer comes back with the error, so after `NotComplete` the caller
+    /// can append the bytes still to come and try again.
+    pub fn decode_with_remainder(
+        mut buf: BytesMut,
+    ) -> Result<(Self, BytesMut), (RespError, BytesMut)> {
+        match Self::decode(&mut buf) {
+            Ok(frame) => Ok((frame, buf)),
+            Err(e) => Err((e, buf)),
+        }
+    }
 }
 
 impl From<&str> for RespFrame {
@@ -28,13 +70,13 @@ impl From<&str> for RespFrame {
 
 impl From<&[u8]> for RespFrame {
     fn from(s: &[u8]) -> Self {
-        BulkString(s.to_vec()).into()
+        BulkString::from(s).into()
     }
 }
 
 impl<const N: usize> From<&[u8; N]> for RespFrame {
     fn from(s: &[u8; N]) -> Self {
-        BulkString(s.to_vec()).into()
+        BulkString::from(s).into()
     }
 }
 
@@ -51,14 +93,30 @@ impl RespDecode for RespFrame {
                 let frame = SimpleError::decode(buf)?;
                 Ok(frame.into())
             }
+            Some(b'!') => {
+                let frame = BulkError::decode(buf)?;
+                Ok(frame.into())
+            }
             Some(b':') => {
                 let frame = i64::decode(buf)?;
                 Ok(frame.into())
             }
+            Some(b'$') if buf.starts_with(b"$-") => {
+                let frame = RespNullBulkString::decode(buf)?;
+                Ok(frame.into())
+            }
             Some(b'$') => {
                 let frame = BulkString::decode(buf)?;
                 Ok(frame.into())
             }
+            Some(b'*') if buf.starts_with(b"*-") => {
+                let frame = RespNullArray::decode(buf)?;
+                Ok(frame.into())
+            }
+            Some(b'*') => {
+                let frame = RespArray::decode(buf)?;
+                Ok(frame.into())
+            }
             Some(b'_') => {
                 let frame = RespNull::decode(buf)?;
                 Ok(frame.into())
@@ -71,6 +129,14 @@ impl RespDecode for RespFrame {
                 let frame = f64::decode(buf)?;
                 Ok(frame.into())
             }
+            Some(b'=') => {
+                let frame = VerbatimString::decode(buf)?;
+                Ok(frame.into())
+            }
+            Some(b'(') => {
+                let frame = RespBigNumber::decode(buf)?;
+                Ok(frame.into())
+            }
             Some(b'%') => {
                 let frame = RespMap::decode(buf)?;
                 Ok(frame.into())
@@ -79,6 +145,17 @@ impl RespDecode for RespFrame {
                 let frame = RespSet::decode(buf)?;
                 Ok(frame.into())
             }
+            Some(b'>') => {
+                let frame = RespPush::decode(buf)?;
+                Ok(frame.into())
+            }
+            Some(b'|') => {
+                // make sure the real reply is complete before dropping the attribute
+                let len = attribute_length(buf)?;
+                RespFrame::expect_length(&buf[len..])?;
+                buf.advance(len);
+                Self::decode(buf)
+            }
             None => Err(RespError::NotComplete),
             _ => Err(RespError::InvalidFrameType(format!(
                 "expect_length: unknown frame type: {:?}",
@@ -91,15 +168,94 @@ impl RespDecode for RespFrame {
         match iter.peek() {
             Some(b'*') => RespArray::expect_length(buf),
             Some(b'~') => RespSet::expect_length(buf),
+            Some(b'>') => RespPush::expect_length(buf),
             Some(b'%') => RespMap::expect_length(buf),
             Some(b'$') => BulkString::expect_length(buf),
             Some(b':') => i64::expect_length(buf),
             Some(b'+') => SimpleString::expect_length(buf),
             Some(b'-') => SimpleError::expect_length(buf),
+            Some(b'!') => BulkError::expect_length(buf),
             Some(b'#') => bool::expect_length(buf),
             Some(b',') => f64::expect_length(buf),
+            Some(b'(') => RespBigNumber::expect_length(buf),
+            Some(b'=') => VerbatimString::expect_length(buf),
             Some(b'_') => RespNull::expect_length(buf),
+            Some(b'|') => {
+                let len = attribute_length(buf)?;
+                Ok(len + RespFrame::expect_length(&buf[len..])?)
+            }
             _ => Err(RespError::NotComplete),
         }
     }
 }
+
+// - attribute: "|<number-of-entries>\r\n<key-1><value-1>...<key-n><value-n>"
+// attributes are out-of-band metadata for the frame that follows, we skip them
+fn attribute_length(buf: &[u8]) -> Result<usize, RespError> {
+    let (end, len) = parse_length(buf, "|")?;
+    calc_total_length(buf, end, len, "|")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BulkString, RespArray, RespDecode, RespError, RespFrame, SimpleString};
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_decode_with_remainder() -> anyhow::Result<()> {
+        let buf = BytesMut::from(&b"*1\r\n$4\r\nping\r\n+OK\r\n"[..]);
+        let (frame, rest) = RespFrame::decode_with_remainder(buf).map_err(|(e, _)| e)?;
+        assert_eq!(
+            frame,
+            RespArray::new([BulkString::new("ping").into()]).into()
+        );
+        assert_eq!(&rest[..], b"+OK\r\n");
+
+        let (frame, rest) = RespFrame::decode_with_remainder(rest).map_err(|(e, _)| e)?;
+        assert_eq!(frame, SimpleString::new("OK").into());
+        assert!(rest.is_empty());
+
+        // a partial frame isn't lost, it decodes once the rest of it arrives
+        let ret = RespFrame::decode_with_remainder(BytesMut::from(&b"*1\r\n$4\r\npi"[..]));
+        let (e, mut partial) = ret.unwrap_err();
+        assert_eq!(e, RespError::NotComplete);
+        assert_eq!(&partial[..], b"*1\r\n$4\r\npi");
+        partial.extend_from_slice(b"ng\r\n:1\r\n");
+        let (frame, rest) = RespFrame::decode_with_remainder(partial).map_err(|(e, _)| e)?;
+        assert_eq!(
+            frame,
+            RespArray::new([BulkString::new("ping").into()]).into()
+        );
+        assert_eq!(&rest[..], b":1\r\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_attribute_decode() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"|1\r\n+ttl\r\n:3600\r\n+OK\r\n");
+        assert_eq!(RespFrame::expect_length(&buf)?, buf.len());
+
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(frame, SimpleString::new("OK").into());
+        assert!(buf.is_empty());
+
+        buf.extend_from_slice(b"|1\r\n+ttl\r\n:3600\r\n");
+        let ret = RespFrame::decode(&mut buf);
+        assert_eq!(ret.unwrap_err(), RespError::NotComplete);
+
+        buf.extend_from_slice(b"+OK\r\n");
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(frame, SimpleString::new("OK").into());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pair_count_overflow() {
+        for header in [b"|9223372036854775808\r\n", b"%9223372036854775808\r\n"] {
+            let ret = RespFrame::expect_length(header);
+            assert!(matches!(ret, Err(RespError::InvalidFrameLength(_))));
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use bytes::BytesMut;
+
+use crate::{BulkString, RespArray, RespError, RespFrame};
+
+// the longest inline line accepted, the same 64kb as redis; a client can't make the server
+// buffer an endless line waiting for its newline
+const INLINE_MAX_LEN: usize = 64 * 1024;
+
+/// Decode an inline command, a plain line like `set foo "bar baz"` as typed into telnet.
+///
+/// Arguments are split on whitespace and may be quoted the same way redis-cli does:
+/// double quotes understand `\n`, `\r`, `\t`, `\b`, `\a`, `\\`, `\"` and `\xHH`, single
+/// quotes only `\'`. A blank line decodes to an empty array. A line longer than 64kb,
+/// terminated or not, fails with [`RespError::InlineTooBig`].
+pub fn decode_inline(buf: &mut BytesMut) -> Result<RespArray, RespError> {
+    let end = match buf
+        .iter()
+        .take(INLINE_MAX_LEN + 1)
+        .position(|&b| b == b'\n')
+    {
+        Some(end) => end,
+        None if buf.len() > INLINE_MAX_LEN => return Err(RespError::InlineTooBig),
+        None => return Err(RespError::NotComplete),
+    };
+    let line = buf.split_to(end + 1);
+    let line = line.strip_suffix(b"\n").unwrap_or(&line);
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    let args = split_args(line)?;
+    Ok(RespArray::new(
+        args.into_iter()
+            .map(|arg| BulkString::new(arg).into())
+            .collect::<Vec<RespFrame>>(),
+    ))
+}
+
+fn split_args(mut line: &[u8]) -> Result<Vec<Vec<u8>>, RespError> {
+    let mut args = Vec::new();
+    loop {
+        line = line.trim_ascii_start();
+        if line.is_empty() {
+            return Ok(args);
+        }
+        let (arg, rest) = match line[0] {
+            b'"' => double_quoted(&line[1..])?,
+            b'\'' => single_quoted(&line[1..])?,
+            _ => {
+                let end = line
+                    .iter()
+                    .position(u8::is_ascii_whitespace)
+                    .unwrap_or(line.len());
+                (line[..end].to_vec(), &line[end..])
+            }
+        };
+        args.push(arg);
+        line = rest;
+    }
+}
+
+// returns the unquoted argument and what follows the closing quote
+fn double_quoted(mut line: &[u8]) -> Result<(Vec<u8>, &[u8]), RespError> {
+    let mut arg = Vec::new();
+    loop {
+        match line {
+            [b'\\', b'x', h, l, rest @ ..] if h.is_ascii_hexdigit() && l.is_ascii_hexdigit() => {
+                arg.push(hex_value(*h) << 4 | hex_value(*l));
+                line = rest;
+            }
+            [b'\\', c, rest @ ..] => {
+                arg.push(match c {
+                    b'n' => b'\n',
+                    b'r' => b'\r',
+                    b't' => b'\t',
+                    b'b' => 0x08,
+                    b'a' => 0x07,
+                    c => *c,
+                });
+                line = rest;
+            }
+            [b'"', rest @ ..] => return Ok((arg, closing_quote(rest)?)),
+            [c, rest @ ..] => {
+                arg.push(*c);
+                line = rest;
+            }
+            [] => return Err(RespError::UnbalancedQuotes),
+        }
+    }
+}
+
+fn single_quoted(mut line: &[u8]) -> Result<(Vec<u8>, &[u8]), RespError> {
+    let mut arg = Vec::new();
+    loop {
+        match line {
+            [b'\\', b'\'', rest @ ..] => {
+                arg.push(b'\'');
+                line = rest;
+            }
+            [b'\'', rest @ ..] => return Ok((arg, closing_quote(rest)?)),
+            [c, rest @ ..] => {
+                arg.push(*c);
+                line = rest;
+            }
+            [] => return Err(RespError::UnbalancedQuotes),
+        }
+    }
+}
+
+// a closing quote has to end the argument, `"foo"bar` is as broken as a missing quote
+fn closing_quote(rest: &[u8]) -> Result<&[u8], RespError> {
+    match rest.first() {
+        None => Ok(rest),
+        Some(c) if c.is_ascii_whitespace() => Ok(rest),
+        Some(_) => Err(RespError::UnbalancedQuotes),
+    }
+}
+
+fn hex_value(c: u8) -> u8 {
+    match c {
+        b'0'..=b'9' => c - b'0',
+        b'a'..=b'f' => c - b'a' + 10,
+        _ => c - b'A' + 10,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(line: &[u8]) -> Result<Vec<Vec<u8>>, RespError> {
+        let array = decode_inline(&mut BytesMut::from(line))?;
+        Ok(array
+            .iter()
+            .map(|frame| match frame {
+                RespFrame::BulkString(s) => s.to_vec(),
+                _ => panic!("inline arguments should be bulk strings"),
+            })
+            .collect())
+    }
+
+    #[test]
+    fn test_decode_inline() -> anyhow::Result<()> {
+        assert_eq!(
+            decode(b"set foo bar\r\n")?,
+            [b"set".as_slice(), b"foo", b"bar"]
+        );
+        assert_eq!(
+            decode(b"  set \"hello world\" 'it\\'s'\n")?,
+            [b"set".as_slice(), b"hello world", b"it's"]
+        );
+        assert_eq!(
+            decode(b"echo \"\\x41\\tb\"\r\n")?,
+            [b"echo".as_slice(), b"A\tb"]
+        );
+        assert!(decode(b"\r\n")?.is_empty());
+        assert_eq!(decode(b"set foo"), Err(RespError::NotComplete));
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_inline_unbalanced_quotes() {
+        for line in [
+            b"set foo \"bar\r\n".as_slice(),
+            b"set foo 'bar\r\n",
+            b"set foo \"bar\"baz\r\n",
+        ] {
+            let ret = decode(line);
+            assert_eq!(ret, Err(RespError::UnbalancedQuotes));
+        }
+        assert_eq!(
+            RespError::UnbalancedQuotes.to_string(),
+            "Protocol error: unbalanced quotes in request"
+        );
+    }
+
+    #[test]
+    fn test_decode_inline_too_big() {
+        let mut line = vec![b'a'; INLINE_MAX_LEN];
+        assert_eq!(decode(&line), Err(RespError::NotComplete));
+        line.push(b'\n');
+        assert_eq!(decode(&line).map(|args| args.len()), Ok(1));
+
+        let line = vec![b'a'; INLINE_MAX_LEN + 1];
+        assert_eq!(decode(&line), Err(RespError::InlineTooBig));
+        let mut line = vec![b'a'; INLINE_MAX_LEN + 1];
+        line.extend_from_slice(b"\r\n");
+        assert_eq!(decode(&line), Err(RespError::InlineTooBig));
+        assert_eq!(
+            RespError::InlineTooBig.to_string(),
+            "Protocol error: too big inline request"
+        );
+    }
+}
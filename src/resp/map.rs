@@ -1,14 +1,26 @@
 use crate::{
-    RespDecode, RespEncode, RespError, RespFrame, SimpleString,
+    BulkString, RespDecode, RespEncode, RespError, RespFrame, SimpleString,
     resp::CRLF_LEN,
     resp::{BUF_CAP, calc_total_length, parse_length},
 };
 use bytes::{Buf, BytesMut};
+use std::cmp::Ordering;
 use std::collections::BTreeMap;
-use std::ops::{Deref, DerefMut};
+use std::ops::Deref;
 
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
-pub struct RespMap(pub(crate) BTreeMap<String, RespFrame>);
+/// A RESP3 map. Entries are kept sorted by key, which is the order `encode` writes them in,
+/// so the same entries always encode the same way.
+///
+/// The keys are also remembered in the order they were inserted, for
+/// [`RespMap::encode_insertion_order`]. That order is only about presentation, two maps
+/// with the same entries are equal whatever it is. Entries only change through
+/// [`RespMap::insert`] and [`RespMap::remove`], which keep both in step.
+#[derive(Debug, Clone)]
+pub struct RespMap(
+    BTreeMap<String, RespFrame>,
+    // the keys of the map in insertion order, each of them once
+    Vec<String>,
+);
 
 impl Deref for RespMap {
     type Target = BTreeMap<String, RespFrame>;
@@ -17,15 +29,66 @@ impl Deref for RespMap {
     }
 }
 
-impl DerefMut for RespMap {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+impl PartialEq for RespMap {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl PartialOrd for RespMap {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.0.partial_cmp(&other.0)
     }
 }
 
 impl RespMap {
     pub fn new() -> Self {
-        RespMap(BTreeMap::new())
+        RespMap(BTreeMap::new(), Vec::new())
+    }
+
+    /// Same as `BTreeMap::insert`, a new key is also appended to the insertion order while
+    /// replacing the value of a key keeps its position.
+    pub fn insert(&mut self, key: String, value: RespFrame) -> Option<RespFrame> {
+        if !self.0.contains_key(&key) {
+            self.1.push(key.clone());
+        }
+        self.0.insert(key, value)
+    }
+
+    /// Same as `BTreeMap::remove`, the key also leaves the insertion order so inserting it
+    /// again puts it last.
+    pub fn remove(&mut self, key: &str) -> Option<RespFrame> {
+        let value = self.0.remove(key)?;
+        self.1.retain(|k| k != key);
+        Some(value)
+    }
+
+    /// Encode with the entries in the order their keys were inserted, for clients that
+    /// care about it.
+    pub fn encode_insertion_order(self) -> Vec<u8> {
+        let RespMap(mut entries, order) = self;
+        let mut buf = Vec::with_capacity(BUF_CAP);
+        buf.extend_from_slice(&format!("%{}\r\n", entries.len()).into_bytes());
+        for key in order {
+            if let Some(value) = entries.remove(&key) {
+                buf.extend_from_slice(&encode_key(key));
+                buf.extend_from_slice(&value.encode());
+            }
+        }
+        buf
+    }
+
+    /// Encode as a RESP2 array of alternating keys and values, the way redis replies to a
+    /// client that never switched to RESP3. Keys become bulk strings, values are encoded
+    /// as they are.
+    pub fn encode_as_resp2(self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(BUF_CAP);
+        buf.extend_from_slice(&format!("*{}\r\n", self.len() * 2).into_bytes());
+        for (key, value) in self.0 {
+            buf.extend_from_slice(&BulkString::new(key).encode());
+            buf.extend_from_slice(&value.encode());
+        }
+        buf
     }
 }
 
@@ -36,13 +99,13 @@ impl Default for RespMap {
 }
 
 // - map: "%<number-of-entries>\r\n<key-1><value-1>...<key-n><value-n>"
-// we only support string key which encode to SimpleString
+// we only support string keys, entries are written sorted by key
 impl RespEncode for RespMap {
     fn encode(self) -> Vec<u8> {
         let mut buf = Vec::with_capacity(BUF_CAP);
         buf.extend_from_slice(&format!("%{}\r\n", self.len()).into_bytes());
         for (key, value) in self.0 {
-            buf.extend_from_slice(&SimpleString::new(key).encode());
+            buf.extend_from_slice(&encode_key(key));
             buf.extend_from_slice(&value.encode());
         }
         buf
@@ -63,9 +126,9 @@ impl RespDecode for RespMap {
 
         let mut frames = RespMap::new();
         for _ in 0..len {
-            let key = SimpleString::decode(buf)?;
+            let key = map_key(RespFrame::decode(buf)?)?;
             let value = RespFrame::decode(buf)?;
-            frames.insert(key.0, value);
+            frames.insert(key, value);
         }
 
         Ok(frames)
@@ -76,15 +139,38 @@ impl RespDecode for RespMap {
     }
 }
 
+// a key is written as a SimpleString, unless it holds a CR or LF that would end that
+// frame early, e.g. one that came in as a bulk string
+fn encode_key(key: String) -> Vec<u8> {
+    if key.contains(['\r', '\n']) {
+        BulkString::new(key).encode()
+    } else {
+        SimpleString::new(key).encode()
+    }
+}
+
+// keys are stored as strings, a key sent as a bulk string or an integer is converted
+pub(crate) fn map_key(frame: RespFrame) -> Result<String, RespError> {
+    match frame {
+        RespFrame::SimpleString(s) => Ok(s.0),
+        RespFrame::BulkString(s) => Ok(String::from_utf8_lossy(&s).into_owned()),
+        RespFrame::Integer(i) => Ok(i.to_string()),
+        frame => Err(RespError::InvalidFrame(format!(
+            "map key must be a string or an integer, got: {:?}",
+            frame
+        ))),
+    }
+}
+
 impl From<BTreeMap<String, RespFrame>> for RespMap {
     fn from(s: BTreeMap<String, RespFrame>) -> Self {
-        RespMap(s)
+        RespMap(s, Vec::new())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{BulkString, RespDecode, RespEncode, RespFrame, RespMap};
+    use crate::{BulkString, RespDecode, RespEncode, RespError, RespFrame, RespMap, SimpleString};
     use bytes::BytesMut;
 
     #[test]
@@ -104,6 +190,44 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_map_decode_keys_of_any_type() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"%1\r\n$3\r\nfoo\r\n:5\r\n");
+        assert_eq!(RespFrame::expect_length(&buf)?, buf.len());
+        let frame = RespMap::decode(&mut buf)?;
+        let mut map = RespMap::new();
+        map.insert("foo".to_string(), RespFrame::Integer(5));
+        assert_eq!(frame, map);
+
+        buf.extend_from_slice(b"%2\r\n:1\r\n+one\r\n+two\r\n:2\r\n");
+        let frame = RespMap::decode(&mut buf)?;
+        let mut map = RespMap::new();
+        map.insert("1".to_string(), SimpleString::new("one").into());
+        map.insert("two".to_string(), RespFrame::Integer(2));
+        assert_eq!(frame, map);
+
+        buf.extend_from_slice(b"%1\r\n*1\r\n:1\r\n:5\r\n");
+        assert!(matches!(
+            RespMap::decode(&mut buf),
+            Err(RespError::InvalidFrame(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_map_key_with_crlf_encodes_as_bulk_string() -> anyhow::Result<()> {
+        let input = b"%2\r\n$4\r\na\r\nb\r\n:1\r\n+c\r\n:2\r\n";
+        let frame = RespMap::decode(&mut BytesMut::from(&input[..]))?;
+        assert_eq!(frame.get("a\r\nb"), Some(&RespFrame::Integer(1)));
+
+        assert_eq!(frame.clone().encode(), input);
+        assert_eq!(frame.clone().encode_insertion_order(), input);
+        let decoded = RespMap::decode(&mut BytesMut::from(&frame.clone().encode()[..]))?;
+        assert_eq!(decoded, frame);
+        Ok(())
+    }
+
     #[test]
     fn test_map_encode() {
         let mut map = RespMap::new();
@@ -118,4 +242,65 @@ mod tests {
             b"%2\r\n+foo\r\n,-123456.789\r\n+hello\r\n$5\r\nworld\r\n"
         );
     }
+
+    #[test]
+    fn test_map_encode_insertion_order() -> anyhow::Result<()> {
+        let mut map = RespMap::new();
+        map.insert("zeta".to_string(), RespFrame::Integer(1));
+        map.insert("alpha".to_string(), RespFrame::Integer(2));
+        map.insert("mid".to_string(), RespFrame::Integer(3));
+        // replacing a value keeps the key where it was
+        map.insert("zeta".to_string(), RespFrame::Integer(4));
+
+        assert_eq!(
+            map.clone().encode(),
+            b"%3\r\n+alpha\r\n:2\r\n+mid\r\n:3\r\n+zeta\r\n:4\r\n"
+        );
+        assert_eq!(
+            map.clone().encode_insertion_order(),
+            b"%3\r\n+zeta\r\n:4\r\n+alpha\r\n:2\r\n+mid\r\n:3\r\n"
+        );
+
+        // a removed key is skipped, inserted again it comes last and only once
+        assert_eq!(map.remove("alpha"), Some(RespFrame::Integer(2)));
+        assert_eq!(map.remove("alpha"), None);
+        map.insert("beta".to_string(), RespFrame::Integer(5));
+        assert_eq!(
+            map.clone().encode_insertion_order(),
+            b"%3\r\n+zeta\r\n:4\r\n+mid\r\n:3\r\n+beta\r\n:5\r\n"
+        );
+        map.insert("alpha".to_string(), RespFrame::Integer(6));
+        map.remove("zeta");
+        map.insert("zeta".to_string(), RespFrame::Integer(7));
+        assert_eq!(
+            map.encode_insertion_order(),
+            b"%4\r\n+mid\r\n:3\r\n+beta\r\n:5\r\n+alpha\r\n:6\r\n+zeta\r\n:7\r\n"
+        );
+
+        // decoding keeps the order of the wire, equality ignores it
+        let input = b"%2\r\n+b\r\n:1\r\n+a\r\n:2\r\n";
+        let frame = RespMap::decode(&mut BytesMut::from(&input[..]))?;
+        let mut sorted = RespMap::new();
+        sorted.insert("a".to_string(), RespFrame::Integer(2));
+        sorted.insert("b".to_string(), RespFrame::Integer(1));
+        assert_eq!(frame, sorted);
+        assert_eq!(frame.encode_insertion_order(), input);
+        Ok(())
+    }
+
+    #[test]
+    fn test_map_encode_as_resp2() {
+        let mut map = RespMap::new();
+        map.insert("hello".to_string(), BulkString::new("world").into());
+        map.insert("count".to_string(), RespFrame::Integer(2));
+
+        assert_eq!(
+            map.clone().encode(),
+            b"%2\r\n+count\r\n:2\r\n+hello\r\n$5\r\nworld\r\n"
+        );
+        assert_eq!(
+            map.encode_as_resp2(),
+            b"*4\r\n$5\r\ncount\r\n:2\r\n$5\r\nhello\r\n$5\r\nworld\r\n"
+        );
+    }
 }
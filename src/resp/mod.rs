@@ -1,22 +1,48 @@
 mod array;
+mod big_number;
 mod bool;
+mod bulk_error;
 mod bulk_string;
 mod double;
 mod frame;
+mod inline;
 mod integer;
 mod map;
 mod null;
+mod null_array;
+mod null_bulk_string;
+mod push;
+mod reply;
 mod set;
 mod simple_error;
 mod simple_string;
+mod verbatim_string;
+mod visit;
+
+pub(crate) use self::map::map_key;
 
 use bytes::{Buf, BytesMut};
 use enum_dispatch::enum_dispatch;
 use thiserror::Error;
 
 pub use self::{
-    array::RespArray, bulk_string::BulkString, frame::RespFrame, map::RespMap, null::RespNull,
-    set::RespSet, simple_error::SimpleError, simple_string::SimpleString,
+    array::RespArray,
+    big_number::RespBigNumber,
+    bulk_error::BulkError,
+    bulk_string::BulkString,
+    frame::RespFrame,
+    inline::decode_inline,
+    map::RespMap,
+    null::RespNull,
+    null_array::RespNullArray,
+    null_bulk_string::RespNullBulkString,
+    push::RespPush,
+    reply::ReplyBuilder,
+    set::RespSet,
+    simple_error::SimpleError,
+    simple_string::SimpleString,
+    verbatim_string::VerbatimString,
+    visit::{ByteLenVisitor, FrameVisitor},
 };
 
 #[enum_dispatch]
@@ -39,6 +65,14 @@ pub enum RespError {
     InvalidFrameLength(isize),
     #[error("Frame is not complete")]
     NotComplete,
+    #[error("Protocol error: invalid multibulk length")]
+    InvalidMultibulkLength,
+    #[error("Protocol error: unbalanced quotes in request")]
+    UnbalancedQuotes,
+    #[error("Protocol error: too big inline request")]
+    InlineTooBig,
+    #[error("trailing partial frame after {0} complete frames")]
+    TrailingPartialFrame(usize),
 
     #[error("ParseIntError: {0}")]
     ParseIntError(#[from] std::num::ParseIntError),
@@ -48,7 +82,31 @@ pub enum RespError {
     ParseFloatError(#[from] std::num::ParseFloatError),
 }
 
+/// Decode a buffer holding several concatenated frames, e.g. captured traffic.
+///
+/// A buffer ending in the middle of a frame fails with [`RespError::TrailingPartialFrame`]
+/// carrying the number of frames that were complete.
+pub fn decode_all(buf: &[u8]) -> Result<Vec<RespFrame>, RespError> {
+    let mut frames = Vec::new();
+    let mut rest = buf;
+    while !rest.is_empty() {
+        let len = match RespFrame::expect_length(rest) {
+            Ok(len) if len <= rest.len() => len,
+            Ok(_) | Err(RespError::NotComplete) => {
+                return Err(RespError::TrailingPartialFrame(frames.len()));
+            }
+            Err(e) => return Err(e),
+        };
+        frames.push(RespFrame::decode(&mut BytesMut::from(&rest[..len]))?);
+        rest = &rest[len..];
+    }
+    Ok(frames)
+}
+
 const BUF_CAP: usize = 4096;
+// upper bound on the slots reserved from a declared aggregate length, the header
+// comes from the client so anything above this grows as elements actually decode
+const MAX_PREALLOC: usize = 1024;
 const CRLF: &[u8] = b"\r\n";
 const CRLF_LEN: usize = CRLF.len();
 
@@ -56,23 +114,19 @@ fn calc_total_length(buf: &[u8], end: usize, len: usize, prefix: &str) -> Result
     let mut total = end + CRLF_LEN;
     let mut data = &buf[total..];
     match prefix {
-        "*" | "~" => {
+        "*" | "~" | ">" => {
             for _ in 0..len {
-                let len = RespFrame::expect_length(data)?;
-                data = &data[len..];
-                total += len;
+                total += skip_frame(&mut data, RespFrame::expect_length)?;
             }
             Ok(total)
         }
-        "%" => {
+        "%" | "|" => {
+            // the count of pairs comes from the client, doubling it may overflow
+            let len = len
+                .checked_mul(2)
+                .ok_or(RespError::InvalidFrameLength(isize::MAX))?;
             for _ in 0..len {
-                let len = SimpleString::expect_length(data)?;
-                data = &data[len..];
-                total += len;
-
-                let len = RespFrame::expect_length(data)?;
-                data = &data[len..];
-                total += len;
+                total += skip_frame(&mut data, RespFrame::expect_length)?;
             }
             Ok(total)
         }
@@ -80,6 +134,19 @@ fn calc_total_length(buf: &[u8], end: usize, len: usize, prefix: &str) -> Result
     }
 }
 
+// the expected length of a bulk string is read off its header, so it can run past the buffer
+fn skip_frame(
+    data: &mut &[u8],
+    expect_length: fn(&[u8]) -> Result<usize, RespError>,
+) -> Result<usize, RespError> {
+    let len = expect_length(data)?;
+    if data.len() < len {
+        return Err(RespError::NotComplete);
+    }
+    *data = &data[len..];
+    Ok(len)
+}
+
 fn parse_length(buf: &[u8], prefix: &str) -> Result<(usize, usize), RespError> {
     let end = extract_simple_frame_data(buf, prefix)?;
     let s = String::from_utf8_lossy(&buf[prefix.len()..end]);
@@ -137,8 +204,8 @@ fn extract_fixed_data(
 
 #[cfg(test)]
 mod tests {
-    use crate::RespError;
-    use crate::resp::{calc_total_length, parse_length};
+    use crate::resp::{calc_total_length, decode_all, parse_length};
+    use crate::{RespArray, RespError, RespFrame, SimpleString};
 
     #[test]
     fn test_calc_total_length() -> anyhow::Result<()> {
@@ -154,4 +221,26 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_decode_all() -> anyhow::Result<()> {
+        let buf = b"+OK\r\n:42\r\n*2\r\n$3\r\nget\r\n$5\r\nhello\r\n";
+        let frames = decode_all(buf)?;
+        assert_eq!(
+            frames,
+            vec![
+                SimpleString::new("OK").into(),
+                RespFrame::Integer(42),
+                RespArray::new([b"get".into(), b"hello".into()]).into(),
+            ]
+        );
+
+        let mut buf = buf.to_vec();
+        buf.extend_from_slice(b"*2\r\n$3\r\nget\r\n$5\r\nhel");
+        assert_eq!(
+            decode_all(&buf).unwrap_err(),
+            RespError::TrailingPartialFrame(3)
+        );
+        Ok(())
+    }
 }
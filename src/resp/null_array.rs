@@ -0,0 +1,62 @@
+use crate::resp::extract_fixed_data;
+use crate::{RespDecode, RespEncode, RespError};
+use bytes::BytesMut;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
+pub struct RespNullArray;
+
+// - null array: "*-1\r\n"
+impl RespEncode for RespNullArray {
+    fn encode(self) -> Vec<u8> {
+        b"*-1\r\n".to_vec()
+    }
+}
+
+impl RespDecode for RespNullArray {
+    const PREFIX: &'static str = "*";
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        extract_fixed_data(buf, "*-1\r\n", "NullArray")?;
+        Ok(RespNullArray)
+    }
+    fn expect_length(_buf: &[u8]) -> Result<usize, RespError> {
+        Ok(5)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{RespArray, RespDecode, RespEncode, RespFrame, RespNullArray};
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_null_array_decode() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*-1\r\n");
+
+        let frame = RespNullArray::decode(&mut buf)?;
+        assert_eq!(frame, RespNullArray);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_null_array_encode() {
+        let frame: RespFrame = RespNullArray.into();
+        assert_eq!(frame.encode(), b"*-1\r\n");
+    }
+
+    #[test]
+    fn test_null_and_empty_array_round_trip() -> anyhow::Result<()> {
+        let null: RespFrame = RespNullArray.into();
+        let empty: RespFrame = RespArray::new(vec![]).into();
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(&null.clone().encode());
+        buf.extend_from_slice(&empty.clone().encode());
+        assert_eq!(&buf[..], b"*-1\r\n*0\r\n");
+
+        assert_eq!(RespFrame::decode(&mut buf)?, null);
+        assert_eq!(RespFrame::decode(&mut buf)?, empty);
+        Ok(())
+    }
+}
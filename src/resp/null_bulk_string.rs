@@ -0,0 +1,62 @@
+use crate::resp::extract_fixed_data;
+use crate::{RespDecode, RespEncode, RespError};
+use bytes::BytesMut;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
+pub struct RespNullBulkString;
+
+// - null bulk string: "$-1\r\n"
+impl RespEncode for RespNullBulkString {
+    fn encode(self) -> Vec<u8> {
+        b"$-1\r\n".to_vec()
+    }
+}
+
+impl RespDecode for RespNullBulkString {
+    const PREFIX: &'static str = "$";
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        extract_fixed_data(buf, "$-1\r\n", "NullBulkString")?;
+        Ok(RespNullBulkString)
+    }
+    fn expect_length(_buf: &[u8]) -> Result<usize, RespError> {
+        Ok(5)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BulkString, RespDecode, RespEncode, RespFrame, RespNullBulkString};
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_null_bulk_string_decode() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"$-1\r\n");
+
+        let frame = RespNullBulkString::decode(&mut buf)?;
+        assert_eq!(frame, RespNullBulkString);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_null_bulk_string_encode() {
+        let frame: RespFrame = RespNullBulkString.into();
+        assert_eq!(frame.encode(), b"$-1\r\n");
+    }
+
+    #[test]
+    fn test_null_and_empty_bulk_string_round_trip() -> anyhow::Result<()> {
+        let null: RespFrame = RespNullBulkString.into();
+        let empty: RespFrame = BulkString::new(vec![]).into();
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(&null.clone().encode());
+        buf.extend_from_slice(&empty.clone().encode());
+        assert_eq!(&buf[..], b"$-1\r\n$0\r\n\r\n");
+
+        assert_eq!(RespFrame::decode(&mut buf)?, null);
+        assert_eq!(RespFrame::decode(&mut buf)?, empty);
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use crate::resp::{BUF_CAP, CRLF_LEN, MAX_PREALLOC, calc_total_length, parse_length};
+use crate::{RespDecode, RespEncode, RespError, RespFrame};
+use bytes::{Buf, BytesMut};
+use std::ops::Deref;
+
+/// A RESP3 push frame, data the server sends without a request, e.g. a pub/sub message.
+/// It is laid out like an array, the first element telling the kind of push.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct RespPush(pub(crate) Vec<RespFrame>);
+
+impl Deref for RespPush {
+    type Target = Vec<RespFrame>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl RespPush {
+    pub fn new(s: impl Into<Vec<RespFrame>>) -> Self {
+        RespPush(s.into())
+    }
+}
+
+// - push: "><number-of-elements>\r\n<element-1>...<element-n>"
+impl RespEncode for RespPush {
+    fn encode(self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(BUF_CAP);
+        buf.extend_from_slice(&format!(">{}\r\n", self.len()).into_bytes());
+        for frame in self.0 {
+            buf.extend_from_slice(&frame.encode());
+        }
+        buf
+    }
+}
+
+impl RespDecode for RespPush {
+    const PREFIX: &'static str = ">";
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let (end, len) = parse_length(buf, Self::PREFIX)?;
+        let total_len = calc_total_length(buf, end, len, Self::PREFIX)?;
+
+        if buf.len() < total_len {
+            return Err(RespError::NotComplete);
+        }
+
+        buf.advance(end + CRLF_LEN);
+
+        let mut frames = Vec::with_capacity(len.min(MAX_PREALLOC));
+        for _ in 0..len {
+            frames.push(RespFrame::decode(buf)?);
+        }
+
+        Ok(RespPush::new(frames))
+    }
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        let (end, len) = parse_length(buf, Self::PREFIX)?;
+        calc_total_length(buf, end, len, Self::PREFIX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BulkString, RespDecode, RespEncode, RespError, RespFrame, RespPush};
+    use bytes::BytesMut;
+
+    fn message() -> RespPush {
+        RespPush::new([
+            BulkString::new("message").into(),
+            BulkString::new("news").into(),
+            BulkString::new("hello").into(),
+        ])
+    }
+
+    #[test]
+    fn test_push_encode() {
+        let frame: RespFrame = message().into();
+        assert_eq!(
+            frame.encode(),
+            b">3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$5\r\nhello\r\n"
+        );
+    }
+
+    #[test]
+    fn test_push_decode() -> anyhow::Result<()> {
+        let mut buf = BytesMut::from(message().encode().as_slice());
+        assert_eq!(RespFrame::expect_length(&buf)?, buf.len());
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(frame, message().into());
+        assert!(buf.is_empty());
+
+        buf.extend_from_slice(b">2\r\n$7\r\nmessage\r\n");
+        let ret = RespPush::decode(&mut buf);
+        assert_eq!(ret.unwrap_err(), RespError::NotComplete);
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use crate::{BulkString, RespArray, RespFrame, RespNullBulkString, SimpleString};
+
+/// Builds an array reply element by element, instead of nesting `.into()` calls.
+///
+/// ```
+/// use simple_redis::ReplyBuilder;
+///
+/// let reply = ReplyBuilder::new()
+///     .bulk("0")
+///     .array(ReplyBuilder::new().bulk("a").bulk("b"))
+///     .build();
+/// ```
+#[derive(Debug, Default)]
+pub struct ReplyBuilder(Vec<RespFrame>);
+
+impl ReplyBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(Vec::with_capacity(capacity))
+    }
+
+    pub fn bulk(self, value: impl Into<Vec<u8>>) -> Self {
+        self.frame(BulkString::new(value))
+    }
+
+    pub fn int(self, value: i64) -> Self {
+        self.frame(RespFrame::Integer(value))
+    }
+
+    pub fn simple(self, value: impl Into<String>) -> Self {
+        self.frame(SimpleString::new(value))
+    }
+
+    /// The RESP2 nil, a null bulk string.
+    pub fn nil(self) -> Self {
+        self.frame(RespNullBulkString)
+    }
+
+    pub fn array(self, children: ReplyBuilder) -> Self {
+        self.frame(children.build())
+    }
+
+    pub fn frame(mut self, frame: impl Into<RespFrame>) -> Self {
+        self.0.push(frame.into());
+        self
+    }
+
+    pub fn build(self) -> RespFrame {
+        RespArray::new(self.0).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reply_builder_nested_array() {
+        let reply = ReplyBuilder::new()
+            .simple("OK")
+            .int(42)
+            .bulk("hello")
+            .nil()
+            .array(ReplyBuilder::new().bulk("a").array(ReplyBuilder::new()))
+            .build();
+
+        let expected: RespFrame = RespArray::new(vec![
+            SimpleString::new("OK").into(),
+            RespFrame::Integer(42),
+            BulkString::new("hello").into(),
+            RespNullBulkString.into(),
+            RespArray::new(vec![
+                BulkString::new("a").into(),
+                RespArray::new(vec![]).into(),
+            ])
+            .into(),
+        ])
+        .into();
+        assert_eq!(reply, expected);
+    }
+}
@@ -0,0 +1,118 @@
+use crate::resp::CRLF_LEN;
+use crate::resp::parse_length;
+use crate::{RespDecode, RespEncode, RespError};
+use bytes::{Buf, BytesMut};
+
+/// A RESP3 verbatim string, text meant to be shown as is along with its format, `txt` for
+/// plain text or `mkd` for markdown.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
+pub struct VerbatimString {
+    pub format: [u8; 3],
+    pub data: Vec<u8>,
+}
+
+impl VerbatimString {
+    pub fn new(format: [u8; 3], data: impl Into<Vec<u8>>) -> Self {
+        VerbatimString {
+            format,
+            data: data.into(),
+        }
+    }
+
+    pub fn txt(data: impl Into<Vec<u8>>) -> Self {
+        Self::new(*b"txt", data)
+    }
+
+    pub fn mkd(data: impl Into<Vec<u8>>) -> Self {
+        Self::new(*b"mkd", data)
+    }
+
+    // splits the payload of the frame, which has to be the format then a colon
+    pub(crate) fn from_payload(payload: &[u8]) -> Result<Self, RespError> {
+        match payload {
+            [a, b, c, b':', data @ ..] => Ok(Self::new([*a, *b, *c], data)),
+            _ => Err(RespError::InvalidFrame(format!(
+                "verbatim string without a format prefix: {:?}",
+                payload
+            ))),
+        }
+    }
+}
+
+// - verbatim string: "=<length>\r\n<format>:<data>\r\n", the length counts the format too
+impl RespEncode for VerbatimString {
+    fn encode(self) -> Vec<u8> {
+        let len = self.data.len() + 4;
+        let mut buf = Vec::with_capacity(len + 16);
+        buf.extend_from_slice(&format!("={}\r\n", len).into_bytes());
+        buf.extend_from_slice(&self.format);
+        buf.push(b':');
+        buf.extend_from_slice(&self.data);
+        buf.extend_from_slice(b"\r\n");
+        buf
+    }
+}
+
+impl RespDecode for VerbatimString {
+    const PREFIX: &'static str = "=";
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let (end, len) = parse_length(buf, Self::PREFIX)?;
+        let remained = &buf[end + CRLF_LEN..];
+        if remained.len() < len + CRLF_LEN {
+            return Err(RespError::NotComplete);
+        }
+
+        buf.advance(end + CRLF_LEN);
+        let data = buf.split_to(len);
+        buf.advance(CRLF_LEN);
+        Self::from_payload(&data)
+    }
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        let (end, len) = parse_length(buf, Self::PREFIX)?;
+        Ok(end + CRLF_LEN + len + CRLF_LEN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{RespDecode, RespEncode, RespError, RespFrame, VerbatimString};
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_verbatim_string_decode() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"=15\r\ntxt:Some string\r\n");
+        let frame = VerbatimString::decode(&mut buf)?;
+        assert_eq!(frame, VerbatimString::txt("Some string"));
+
+        buf.extend_from_slice(b"=11\r\nmkd:# title\r\n");
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(frame, VerbatimString::mkd("# title").into());
+
+        buf.extend_from_slice(b"=4\r\ntxt:");
+        let ret = VerbatimString::decode(&mut buf);
+        assert_eq!(ret.unwrap_err(), RespError::NotComplete);
+        buf.extend_from_slice(b"\r\n");
+        let frame = VerbatimString::decode(&mut buf)?;
+        assert_eq!(frame, VerbatimString::txt(""));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_verbatim_string_without_colon_should_fail() {
+        for input in [b"=5\r\ntxt-a\r\n".as_slice(), b"=2\r\ntx\r\n"] {
+            let mut buf = BytesMut::from(input);
+            let ret = VerbatimString::decode(&mut buf);
+            assert!(matches!(ret, Err(RespError::InvalidFrame(_))));
+        }
+    }
+
+    #[test]
+    fn test_verbatim_string_encode() {
+        let frame: RespFrame = VerbatimString::txt("Some string").into();
+        assert_eq!(frame.encode(), b"=15\r\ntxt:Some string\r\n");
+        let frame: RespFrame = VerbatimString::mkd("# title").into();
+        assert_eq!(frame.encode(), b"=11\r\nmkd:# title\r\n");
+    }
+}
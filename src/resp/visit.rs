@@ -0,0 +1,270 @@
+use std::fmt::{self, Write};
+
+use super::double::non_finite_token;
+use crate::RespFrame;
+
+/// Callbacks for walking a frame tree by reference, see [`RespFrame::visit`].
+///
+/// Every method defaults to doing nothing, so a visitor only overrides what it cares
+/// about. Aggregates announce their length, then their children are visited, then the
+/// matching `end_*` method is called. Map keys are visited as simple strings, the same
+/// way they are encoded.
+pub trait FrameVisitor {
+    fn visit_simple_string(&mut self, _s: &str) {}
+    fn visit_error(&mut self, _e: &str) {}
+    fn visit_bulk_error(&mut self, _e: &[u8]) {}
+    fn visit_integer(&mut self, _i: i64) {}
+    fn visit_bulk_string(&mut self, _s: &[u8]) {}
+    fn visit_null_bulk_string(&mut self) {}
+    fn visit_null_array(&mut self) {}
+    fn visit_null(&mut self) {}
+    fn visit_boolean(&mut self, _b: bool) {}
+    fn visit_double(&mut self, _d: f64) {}
+    fn visit_big_number(&mut self, _n: &str) {}
+    fn visit_verbatim_string(&mut self, _format: &[u8; 3], _data: &[u8]) {}
+    fn visit_array(&mut self, _len: usize) {}
+    fn end_array(&mut self) {}
+    fn visit_map(&mut self, _len: usize) {}
+    fn end_map(&mut self) {}
+    fn visit_set(&mut self, _len: usize) {}
+    fn end_set(&mut self) {}
+    fn visit_push(&mut self, _len: usize) {}
+    fn end_push(&mut self) {}
+}
+
+impl RespFrame {
+    /// Walk the frame and everything nested in it without cloning or encoding.
+    pub fn visit<V: FrameVisitor>(&self, v: &mut V) {
+        match self {
+            RespFrame::SimpleString(s) => v.visit_simple_string(s),
+            RespFrame::Error(e) => v.visit_error(e),
+            RespFrame::BulkError(e) => v.visit_bulk_error(e),
+            RespFrame::Integer(i) => v.visit_integer(*i),
+            RespFrame::BulkString(s) => v.visit_bulk_string(s),
+            RespFrame::NullBulkString(_) => v.visit_null_bulk_string(),
+            RespFrame::NullArray(_) => v.visit_null_array(),
+            RespFrame::Null(_) => v.visit_null(),
+            RespFrame::Boolean(b) => v.visit_boolean(*b),
+            RespFrame::Double(d) => v.visit_double(*d),
+            RespFrame::BigNumber(n) => v.visit_big_number(n),
+            RespFrame::VerbatimString(s) => v.visit_verbatim_string(&s.format, &s.data),
+            RespFrame::Array(a) => {
+                v.visit_array(a.len());
+                a.iter().for_each(|frame| frame.visit(v));
+                v.end_array();
+            }
+            RespFrame::Map(m) => {
+                v.visit_map(m.len());
+                for (key, value) in m.iter() {
+                    v.visit_simple_string(key);
+                    value.visit(v);
+                }
+                v.end_map();
+            }
+            RespFrame::Set(s) => {
+                v.visit_set(s.len());
+                s.iter().for_each(|frame| frame.visit(v));
+                v.end_set();
+            }
+            RespFrame::Push(p) => {
+                v.visit_push(p.len());
+                p.iter().for_each(|frame| frame.visit(v));
+                v.end_push();
+            }
+        }
+    }
+}
+
+/// Computes the encoded size of a frame, i.e. `frame.clone().encode().len()` without
+/// building the buffer.
+#[derive(Debug, Default)]
+pub struct ByteLenVisitor(usize);
+
+impl ByteLenVisitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    // prefix, formatted payload and CRLF, the payload is counted rather than allocated
+    fn line(&mut self, payload: fmt::Arguments) {
+        self.0 += 1;
+        let _ = self.write_fmt(payload);
+        self.0 += 2;
+    }
+}
+
+impl Write for ByteLenVisitor {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
+}
+
+impl FrameVisitor for ByteLenVisitor {
+    fn visit_simple_string(&mut self, s: &str) {
+        self.0 += s.len() + 3;
+    }
+
+    fn visit_error(&mut self, e: &str) {
+        self.0 += e.len() + 3;
+    }
+
+    fn visit_bulk_error(&mut self, e: &[u8]) {
+        self.line(format_args!("{}", e.len()));
+        self.0 += e.len() + 2;
+    }
+
+    fn visit_integer(&mut self, i: i64) {
+        self.line(format_args!("{}", i));
+    }
+
+    fn visit_bulk_string(&mut self, s: &[u8]) {
+        self.line(format_args!("{}", s.len()));
+        self.0 += s.len() + 2;
+    }
+
+    fn visit_null_bulk_string(&mut self) {
+        self.0 += 5;
+    }
+
+    fn visit_null_array(&mut self) {
+        self.0 += 5;
+    }
+
+    fn visit_null(&mut self) {
+        self.0 += 3;
+    }
+
+    fn visit_boolean(&mut self, _b: bool) {
+        self.0 += 4;
+    }
+
+    // mirrors the f64 encoder
+    fn visit_double(&mut self, d: f64) {
+        if let Some(token) = non_finite_token(d) {
+            self.line(format_args!("{}", token));
+        } else if d.abs() > 1e+8 || d.abs() < 1e-8 {
+            self.line(format_args!("{:+e}", d));
+        } else {
+            let sign = if d < 0.0 { "" } else { "+" };
+            self.line(format_args!("{}{}", sign, d));
+        }
+    }
+
+    fn visit_big_number(&mut self, n: &str) {
+        self.0 += n.len() + 3;
+    }
+
+    fn visit_verbatim_string(&mut self, _format: &[u8; 3], data: &[u8]) {
+        self.line(format_args!("{}", data.len() + 4));
+        self.0 += data.len() + 6;
+    }
+
+    fn visit_array(&mut self, len: usize) {
+        self.line(format_args!("{}", len));
+    }
+
+    fn visit_map(&mut self, len: usize) {
+        self.line(format_args!("{}", len));
+    }
+
+    fn visit_set(&mut self, len: usize) {
+        self.line(format_args!("{}", len));
+    }
+
+    fn visit_push(&mut self, len: usize) {
+        self.line(format_args!("{}", len));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        BulkError, BulkString, RespArray, RespBigNumber, RespEncode, RespMap, RespNull,
+        RespNullBulkString, RespPush, RespSet, SimpleError, SimpleString, VerbatimString,
+    };
+
+    #[derive(Default)]
+    struct BulkCounter {
+        bulks: usize,
+        depth: usize,
+        max_depth: usize,
+    }
+
+    impl FrameVisitor for BulkCounter {
+        fn visit_bulk_string(&mut self, _s: &[u8]) {
+            self.bulks += 1;
+        }
+
+        fn visit_array(&mut self, _len: usize) {
+            self.depth += 1;
+            self.max_depth = self.max_depth.max(self.depth);
+        }
+
+        fn end_array(&mut self) {
+            self.depth -= 1;
+        }
+    }
+
+    #[test]
+    fn test_visitor_counts_nested_bulk_strings() {
+        let frame: RespFrame = RespArray::new([
+            BulkString::new("a").into(),
+            RespArray::new([
+                BulkString::new("b").into(),
+                RespFrame::Integer(1),
+                RespArray::new([BulkString::new("c").into()]).into(),
+            ])
+            .into(),
+            SimpleString::new("d").into(),
+        ])
+        .into();
+
+        let mut counter = BulkCounter::default();
+        frame.visit(&mut counter);
+        assert_eq!(counter.bulks, 3);
+        assert_eq!(counter.max_depth, 3);
+        assert_eq!(counter.depth, 0);
+    }
+
+    #[test]
+    fn test_byte_len_visitor_matches_encoding() {
+        let mut map = RespMap::new();
+        map.insert("k".to_string(), RespFrame::Double(-1.5));
+        map.insert("big".to_string(), RespFrame::Double(1.23456e9));
+        let frames: Vec<RespFrame> = vec![
+            SimpleString::new("OK").into(),
+            SimpleError::new("ERR nope").into(),
+            BulkError::from("ERR two\r\nlines").into(),
+            RespFrame::Integer(-12345),
+            BulkString::new("hello").into(),
+            RespNullBulkString.into(),
+            RespNull.into(),
+            RespFrame::Boolean(false),
+            RespFrame::Double(0.0),
+            RespFrame::Double(f64::NEG_INFINITY),
+            RespFrame::Double(f64::NAN),
+            RespBigNumber::from(i128::MIN).into(),
+            VerbatimString::txt("Some string").into(),
+            map.into(),
+            RespSet::new([RespFrame::Integer(1), BulkString::new("").into()]).into(),
+            RespArray::new([RespArray::new([]).into(), RespFrame::Double(3.25)]).into(),
+            RespPush::new([BulkString::new("message").into(), RespFrame::Integer(7)]).into(),
+        ];
+
+        for frame in frames {
+            let mut len = ByteLenVisitor::new();
+            frame.visit(&mut len);
+            assert_eq!(len.len(), frame.clone().encode().len(), "{:?}", frame);
+        }
+    }
+}
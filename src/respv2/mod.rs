@@ -14,8 +14,8 @@ pub trait RespDecodeV2: Sized {
 impl RespDecodeV2 for RespFrame {
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
         let len = Self::expect_length(buf)?;
-        let data = buf.split_to(len);
-        parse::parse_frame(&mut data.as_ref()).map_err(|e| RespError::InvalidFrame(e.to_string()))
+        let data = buf.split_to(len).freeze();
+        parse::parse_frame_bytes(&data).map_err(|e| RespError::InvalidFrame(e.to_string()))
     }
 
     fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
@@ -26,8 +26,32 @@ impl RespDecodeV2 for RespFrame {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        BulkError, BulkString, RespArray, RespBigNumber, RespEncode, RespNullArray,
+        RespNullBulkString, RespPush, VerbatimString,
+    };
     use std::collections::BTreeMap;
 
+    #[test]
+    fn respv2_large_bulk_string_should_share_buffer() {
+        let payload = vec![b'x'; 1024 * 1024];
+        let mut buf = BytesMut::from(format!("*1\r\n${}\r\n", payload.len()).as_bytes());
+        let header_len = buf.len();
+        buf.extend_from_slice(&payload);
+        buf.extend_from_slice(b"\r\n");
+        let start = buf.as_ptr();
+
+        let frame = RespFrame::decode(&mut buf).unwrap();
+        let RespFrame::Array(array) = frame else {
+            panic!("expected an array");
+        };
+        let RespFrame::BulkString(s) = &array[0] else {
+            panic!("expected a bulk string");
+        };
+        assert_eq!(&s[..], &payload[..]);
+        assert_eq!(s.as_ptr(), start.wrapping_add(header_len));
+    }
+
     #[test]
     fn respv2_simple_string_length_should_work() {
         let buf = b"+OK\r\n";
@@ -77,6 +101,85 @@ mod tests {
         assert_eq!(frame, RespFrame::Integer(1000));
     }
 
+    #[test]
+    fn respv2_big_number_should_work() {
+        let input = b"(-3492890328409238509324850943850943825024385\r\n";
+        assert_eq!(RespFrame::expect_length(input).unwrap(), input.len());
+
+        let mut buf = BytesMut::from(&input[..]);
+        let frame = RespFrame::decode(&mut buf).unwrap();
+        let expected = RespBigNumber::new("-3492890328409238509324850943850943825024385").unwrap();
+        assert_eq!(frame, expected.into());
+
+        let mut buf = BytesMut::from("*2\r\n(+18446744073709551616\r\n:1\r\n");
+        let frame = RespFrame::decode(&mut buf).unwrap();
+        let big = RespBigNumber::from(u64::MAX as u128 + 1);
+        assert_eq!(
+            frame,
+            RespArray::new([big.into(), RespFrame::Integer(1)]).into()
+        );
+
+        let mut buf = BytesMut::from("(12a\r\n");
+        assert!(RespFrame::decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn respv2_verbatim_string_should_work() {
+        let input = b"=15\r\ntxt:Some string\r\n";
+        assert_eq!(RespFrame::expect_length(input).unwrap(), input.len());
+        let mut buf = BytesMut::from(&input[..]);
+        let frame = RespFrame::decode(&mut buf).unwrap();
+        assert_eq!(frame, VerbatimString::txt("Some string").into());
+
+        let mut buf = BytesMut::from("=11\r\nmkd:# title\r\n");
+        let frame = RespFrame::decode(&mut buf).unwrap();
+        assert_eq!(frame, VerbatimString::mkd("# title").into());
+
+        let mut buf = BytesMut::from("=11\r\nmkd;# title\r\n");
+        assert!(RespFrame::decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn respv2_push_should_work() {
+        let input = b">3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n:1\r\n";
+        assert_eq!(RespFrame::expect_length(input).unwrap(), input.len());
+        let mut buf = BytesMut::from(&input[..]);
+        let frame = RespFrame::decode(&mut buf).unwrap();
+        let expected = RespPush::new([
+            BulkString::new("message").into(),
+            BulkString::new("news").into(),
+            RespFrame::Integer(1),
+        ]);
+        assert_eq!(frame, expected.into());
+    }
+
+    #[test]
+    fn respv2_bulk_error_should_work() {
+        let input = b"!28\r\nERR script failed\r\nat line 3\r\n";
+        assert_eq!(RespFrame::expect_length(input).unwrap(), input.len());
+        let mut buf = BytesMut::from(&input[..]);
+        let frame = RespFrame::decode(&mut buf).unwrap();
+        assert_eq!(
+            frame,
+            BulkError::from("ERR script failed\r\nat line 3").into()
+        );
+    }
+
+    #[test]
+    fn respv2_non_finite_double_should_work() {
+        for (input, expected) in [
+            (",inf\r\n", f64::INFINITY),
+            (",-inf\r\n", f64::NEG_INFINITY),
+        ] {
+            let mut buf = BytesMut::from(input);
+            let frame = RespFrame::decode(&mut buf).unwrap();
+            assert_eq!(frame, RespFrame::Double(expected));
+        }
+        let mut buf = BytesMut::from(",nan\r\n");
+        let frame = RespFrame::decode(&mut buf).unwrap();
+        assert!(matches!(frame, RespFrame::Double(d) if d.is_nan()));
+    }
+
     #[test]
     fn respv2_bulk_string_length_should_work() {
         let buf = b"$6\r\nfoobar\r\n";
@@ -98,6 +201,26 @@ mod tests {
         assert_eq!(len, buf.len());
     }
 
+    #[test]
+    fn respv2_null_and_empty_bulk_string_should_work() {
+        let mut buf = BytesMut::from("$-1\r\n$0\r\n\r\n");
+        assert_eq!(RespFrame::expect_length(&buf).unwrap(), 5);
+        let frame = RespFrame::decode(&mut buf).unwrap();
+        assert_eq!(frame, RespFrame::NullBulkString(RespNullBulkString));
+        assert_eq!(RespFrame::expect_length(&buf).unwrap(), 6);
+        let frame = RespFrame::decode(&mut buf).unwrap();
+        assert_eq!(frame, RespFrame::BulkString("".into()));
+    }
+
+    #[test]
+    fn respv2_null_and_empty_array_should_work() {
+        let mut buf = BytesMut::from("*-1\r\n*0\r\n");
+        let frame = RespFrame::decode(&mut buf).unwrap();
+        assert_eq!(frame, RespFrame::NullArray(RespNullArray));
+        let frame = RespFrame::decode(&mut buf).unwrap();
+        assert_eq!(frame, RespFrame::Array(vec![].into()));
+    }
+
     #[test]
     fn respv2_array_length_should_work() {
         let buf = b"*2\r\n+OK\r\n-ERR\r\n";
@@ -145,4 +268,49 @@ mod tests {
                 .collect();
         assert_eq!(frame, RespFrame::Map(items.into()));
     }
+
+    #[test]
+    fn respv2_map_keys_of_any_type_should_work() {
+        let input = b"%2\r\n$3\r\nfoo\r\n:5\r\n:1\r\n+one\r\n";
+        assert_eq!(RespFrame::expect_length(input).unwrap(), input.len());
+        let mut buf = BytesMut::from(&input[..]);
+        let frame = RespFrame::decode(&mut buf).unwrap();
+        let items: BTreeMap<String, RespFrame> = [
+            ("foo".to_string(), RespFrame::Integer(5)),
+            ("1".to_string(), RespFrame::SimpleString("one".into())),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(frame, RespFrame::Map(items.into()));
+
+        let mut buf = BytesMut::from("%1\r\n#t\r\n:5\r\n");
+        assert!(RespFrame::decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn respv2_map_should_keep_insertion_order() {
+        let input = b"%2\r\n+zeta\r\n:1\r\n+alpha\r\n:2\r\n";
+        let mut buf = BytesMut::from(&input[..]);
+        let RespFrame::Map(map) = RespFrame::decode(&mut buf).unwrap() else {
+            panic!("expected a map");
+        };
+        assert_eq!(map.clone().encode_insertion_order(), input);
+        assert_eq!(map.encode(), b"%2\r\n+alpha\r\n:2\r\n+zeta\r\n:1\r\n");
+    }
+
+    #[test]
+    fn respv2_attribute_should_work() {
+        let mut buf = BytesMut::from("|1\r\n+ttl\r\n:3600\r\n+OK\r\n");
+        let frame = RespFrame::decode(&mut buf).unwrap();
+        assert_eq!(frame, RespFrame::SimpleString("OK".into()));
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn respv2_pair_count_overflow_should_fail() {
+        for header in [b"|4611686018427387904\r\n", b"%4611686018427387904\r\n"] {
+            let ret = RespFrame::expect_length(header);
+            assert!(matches!(ret, Err(RespError::InvalidFrameLength(_))));
+        }
+    }
 }
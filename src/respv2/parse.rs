@@ -1,21 +1,29 @@
 use std::num::NonZeroUsize;
 
+use bytes::Bytes;
 use winnow::{
     Parser,
     ascii::{digit1, float},
-    combinator::{alt, dispatch, fail, opt, preceded, terminated},
-    error::{ContextError, Needed, ParserError},
+    combinator::{alt, dispatch, fail, opt, terminated},
+    error::{ContextError, FromExternalError, Needed, ParserError},
+    stream::Stateful,
     token::{any, take, take_till, take_until},
 };
 
+use crate::resp::map_key;
 use crate::{
-    BulkString, RespArray, RespError, RespFrame, RespMap, RespNull, RespSet, SimpleError,
-    SimpleString,
+    BulkError, BulkString, RespArray, RespBigNumber, RespError, RespFrame, RespMap, RespNull,
+    RespNullArray, RespNullBulkString, RespPush, RespSet, SimpleError, SimpleString,
+    VerbatimString,
 };
 use winnow::Result;
 
 const CRLF: &[u8] = b"\r\n";
 
+// the input of the aggregate parsers, carrying the buffer being parsed when there is one so
+// bulk strings can share it instead of copying their payload out
+type Input<'i> = Stateful<&'i [u8], Option<&'i Bytes>>;
+
 pub fn parse_frame_length(input: &[u8]) -> Result<usize, RespError> {
     let target = &mut (&*input);
     let ret = advance(target);
@@ -26,7 +34,10 @@ pub fn parse_frame_length(input: &[u8]) -> Result<usize, RespError> {
             let end = (*target).as_ptr() as usize;
             Ok(end - start)
         }
-        Err(_) => Err(RespError::NotComplete),
+        Err(e) => match e.cause().and_then(|e| e.downcast_ref::<RespError>()) {
+            Some(RespError::InvalidFrameLength(len)) => Err(RespError::InvalidFrameLength(*len)),
+            _ => Err(RespError::NotComplete),
+        },
     }
 }
 
@@ -37,12 +48,17 @@ fn advance(input: &mut &[u8]) -> Result<()> {
         b'-' => simple_advance,
         b':' => simple_advance,
         b'$' => bulk_string_advance,
+        b'!' => bulk_string_advance,
+        b'=' => bulk_string_advance,
         b'*' => array_advance,
         b'_' => simple_advance,
         b'#' => simple_advance,
         b',' => simple_advance,
+        b'(' => simple_advance,
         b'%' => map_advance,
         b'~' => set_advance,
+        b'>' => array_advance,
+        b'|' => attribute_advance,
         _v=>fail::<_,_,_>,
     }
     .parse_next(input)
@@ -50,7 +66,7 @@ fn advance(input: &mut &[u8]) -> Result<()> {
 
 fn bulk_string_advance(input: &mut &[u8]) -> Result<()> {
     let len = integer.parse_next(input)?;
-    if len == -1 || len == 0 {
+    if len == -1 {
         return Ok(());
     } else if len < 0 {
         return Err(err_cut("bulk string length must be non-negative"));
@@ -81,10 +97,7 @@ fn map_advance(input: &mut &[u8]) -> Result<()> {
     if len == -1 || len == 0 {
         return Ok(());
     }
-    for _ in 0..len {
-        terminated(take_till(0.., CRLF), CRLF)
-            .value(())
-            .parse_next(input)?;
+    for _ in 0..pairs(input, len)? {
         advance(input)?;
     }
     Ok(())
@@ -101,21 +114,55 @@ fn set_advance(input: &mut &[u8]) -> Result<()> {
     Ok(())
 }
 
+fn attribute_advance(input: &mut &[u8]) -> Result<()> {
+    let len = integer.parse_next(input)?;
+    for _ in 0..pairs(input, len)? {
+        advance(input)?;
+    }
+    // the attribute is followed by the real reply
+    advance(input)
+}
+
 pub fn parse_frame(input: &mut &[u8]) -> Result<RespFrame> {
-    dispatch! {any;
-        b'+' => simple_string.map(RespFrame::SimpleString),
-        b'-' => simple_error.map(RespFrame::Error),
-        b':' => integer.map(RespFrame::Integer),
-        b'$' => bulk_string.map(RespFrame::BulkString),
-        b'*' => array.map(RespFrame::Array),
-        b'_' => null.map(RespFrame::Null),
-        b'#' => boolean.map(RespFrame::Boolean),
-        b',' => double.map(RespFrame::Double),
-        b'%' => map.map(RespFrame::Map),
-        b'~' => set.map(RespFrame::Set),
-        _ => fail::<_,_,_>,
+    let mut stateful = Input {
+        input: *input,
+        state: None,
+    };
+    let frame = frame(&mut stateful)?;
+    *input = stateful.input;
+    Ok(frame)
+}
+
+// parses the single frame `buf` holds, bulk strings point into `buf` without a copy
+pub(crate) fn parse_frame_bytes(buf: &Bytes) -> Result<RespFrame> {
+    frame(&mut Input {
+        input: buf,
+        state: Some(buf),
+    })
+}
+
+fn frame(input: &mut Input) -> Result<RespFrame> {
+    let slice = &mut input.input;
+    match any.parse_next(slice)? {
+        b'+' => simple_string.map(RespFrame::SimpleString).parse_next(slice),
+        b'-' => simple_error.map(RespFrame::Error).parse_next(slice),
+        b':' => integer.map(RespFrame::Integer).parse_next(slice),
+        b'$' => bulk_string(input),
+        b'!' => bulk_error.map(RespFrame::BulkError).parse_next(slice),
+        b'=' => verbatim_string
+            .map(RespFrame::VerbatimString)
+            .parse_next(slice),
+        b'*' => array(input),
+        b'_' => null.map(RespFrame::Null).parse_next(slice),
+        b'#' => boolean.map(RespFrame::Boolean).parse_next(slice),
+        b',' => double.map(RespFrame::Double).parse_next(slice),
+        b'(' => big_number.map(RespFrame::BigNumber).parse_next(slice),
+        b'%' => map(input).map(RespFrame::Map),
+        b'~' => set(input).map(RespFrame::Set),
+        b'>' => push(input).map(RespFrame::Push),
+        b'|' => attribute(input),
+        _ => fail.parse_next(slice),
     }
-    .parse_next(input)
 }
 
 fn simple_string(input: &mut &[u8]) -> Result<SimpleString> {
@@ -133,27 +180,53 @@ fn integer(input: &mut &[u8]) -> Result<i64> {
     Ok(sign * digits)
 }
 
-fn bulk_string(input: &mut &[u8]) -> Result<BulkString> {
-    let len = terminated(digit1.parse_to::<i64>(), CRLF).parse_next(input)?;
+fn bulk_string(input: &mut Input) -> Result<RespFrame> {
+    let len = integer.parse_next(&mut input.input)?;
     if len == -1 {
-        return Ok(BulkString::new(vec![]));
+        return Ok(RespNullBulkString.into());
+    } else if len < 0 {
+        return Err(err_cut("bulk string length must be non-negative"));
+    }
+
+    let data = terminated(take(len as usize), CRLF).parse_next(&mut input.input)?;
+    Ok(match input.state {
+        Some(buf) => BulkString::from(buf.slice_ref(data)),
+        None => BulkString::from(data),
     }
+    .into())
+}
 
+fn bulk_error(input: &mut &[u8]) -> Result<BulkError> {
+    let len = integer.parse_next(input)?;
+    if len < 0 {
+        return Err(err_cut("bulk error length must be non-negative"));
+    }
     let data = terminated(take(len as usize), CRLF).parse_next(input)?;
-    Ok(BulkString::new(data))
+    Ok(BulkError::new(data))
 }
 
-fn array(input: &mut &[u8]) -> Result<RespArray> {
-    let len = terminated(digit1.parse_to::<i64>(), CRLF).parse_next(input)?;
+fn verbatim_string(input: &mut &[u8]) -> Result<VerbatimString> {
+    let len = integer.parse_next(input)?;
+    if len < 0 {
+        return Err(err_cut("verbatim string length must be non-negative"));
+    }
+    let data = terminated(take(len as usize), CRLF).parse_next(input)?;
+    VerbatimString::from_payload(data).map_err(|e| err_cut(e.to_string()))
+}
+
+fn array(input: &mut Input) -> Result<RespFrame> {
+    let len = integer.parse_next(&mut input.input)?;
     if len == -1 {
-        return Ok(RespArray::new(vec![]));
+        return Ok(RespNullArray.into());
+    } else if len < 0 {
+        return Err(err_cut("array length must be non-negative"));
     }
 
     let mut items = Vec::new();
     for _ in 0..len {
-        items.push(parse_frame(input)?);
+        items.push(frame(input)?);
     }
-    Ok(RespArray::new(items))
+    Ok(RespArray::new(items).into())
 }
 
 fn null(input: &mut &[u8]) -> Result<RespNull> {
@@ -170,29 +243,68 @@ fn double(input: &mut &[u8]) -> Result<f64> {
     terminated(float, CRLF).parse_next(input)
 }
 
-fn map(input: &mut &[u8]) -> Result<RespMap> {
-    let len = terminated(digit1.parse_to::<i64>(), CRLF).parse_next(input)?;
+fn big_number(input: &mut &[u8]) -> Result<RespBigNumber> {
+    let sign = opt(alt(('+', '-'))).parse_next(input)?;
+    let digits = terminated(digit1, CRLF).parse_next(input)?;
+    let digits = String::from_utf8_lossy(digits);
+    Ok(RespBigNumber(match sign {
+        Some('-') => format!("-{}", digits),
+        _ => digits.into_owned(),
+    }))
+}
+
+fn map(input: &mut Input) -> Result<RespMap> {
+    let len = terminated(digit1.parse_to::<i64>(), CRLF).parse_next(&mut input.input)?;
     let mut map = RespMap::new();
 
     for _ in 0..len {
-        let key = preceded('+', parse_string).parse_next(input)?;
-        let value = parse_frame(input)?;
+        let key = map_key(frame(input)?).map_err(|e| err_cut(e.to_string()))?;
+        let value = frame(input)?;
         map.insert(key, value);
     }
     Ok(map)
 }
 
-fn set(input: &mut &[u8]) -> Result<RespSet> {
-    let len = terminated(digit1.parse_to::<i64>(), CRLF).parse_next(input)?;
+fn set(input: &mut Input) -> Result<RespSet> {
+    let len = terminated(digit1.parse_to::<i64>(), CRLF).parse_next(&mut input.input)?;
     let len = len / 2;
     let mut items = Vec::new();
     for _ in 0..len {
-        let item = parse_frame(input)?;
+        let item = frame(input)?;
         items.push(item);
     }
     Ok(RespSet::new(items))
 }
 
+fn push(input: &mut Input) -> Result<RespPush> {
+    let len = integer.parse_next(&mut input.input)?;
+    if len < 0 {
+        return Err(err_cut("push length must be non-negative"));
+    }
+    let mut items = Vec::new();
+    for _ in 0..len {
+        items.push(frame(input)?);
+    }
+    Ok(RespPush::new(items))
+}
+
+// attributes carry out-of-band metadata, we drop them and return the frame that follows
+fn attribute(input: &mut Input) -> Result<RespFrame> {
+    let len = terminated(digit1.parse_to::<i64>(), CRLF).parse_next(&mut input.input)?;
+    for _ in 0..pairs(&input.input, len)? {
+        frame(input)?;
+    }
+    frame(input)
+}
+
+// frames making up `len` key/value pairs, the count comes from the client so doubling it
+// may overflow; the error carries InvalidFrameLength for parse_frame_length to report
+fn pairs(input: &&[u8], len: i64) -> Result<i64> {
+    len.checked_mul(2).ok_or_else(|| {
+        ContextError::from_external_error(input, RespError::InvalidFrameLength(isize::MAX))
+    })
+}
+
 fn parse_string(input: &mut &[u8]) -> Result<String> {
     terminated(take_till(0.., CRLF), CRLF)
         .map(|s: &[u8]| String::from_utf8_lossy(s).into_owned())
@@ -234,6 +346,14 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_attribute_len() {
+        let input = b"|1\r\n+ttl\r\n:3600\r\n+OK\r\n";
+        let input = &input[..];
+        let len = parse_frame_length(input).unwrap();
+        assert_eq!(input.len(), len);
+    }
+
     #[test]
     fn test_set_len() {
         let input = b"~2\r\n$4\r\nping\r\n$4\r\npong\r\n";
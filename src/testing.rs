@@ -0,0 +1,185 @@
+//! Helpers for tests that check persistence, enabled by the `testing` feature.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    time::{Duration, Instant},
+};
+
+use crate::{Backend, RespFrame};
+
+/// Save `backend` to a temporary snapshot, load it into a fresh backend and assert both
+/// hold the same strings, hashes, sets, sorted sets, lists, key TTLs and hash field TTLs.
+///
+/// TTLs go through the wall clock on the way, so deadlines only have to agree within a
+/// few milliseconds.
+pub fn assert_snapshot_roundtrip(backend: &Backend) {
+    let dir = tempfile::tempdir().expect("failed to create a temporary directory");
+    let path = dir.path().join("dump.rdb");
+    backend
+        .save_snapshot(&path)
+        .expect("failed to save snapshot");
+    let restored = Backend::new();
+    restored
+        .load_snapshot(&path)
+        .expect("failed to load snapshot");
+
+    assert_eq!(strings(backend), strings(&restored), "strings differ");
+    assert_eq!(hashes(backend), hashes(&restored), "hashes differ");
+    assert_eq!(sets(backend), sets(&restored), "sets differ");
+    assert_eq!(zsets(backend), zsets(&restored), "sorted sets differ");
+    assert_eq!(lists(backend), lists(&restored), "lists differ");
+
+    assert_ttls_close(key_ttls(backend), key_ttls(&restored), "key TTLs differ");
+    assert_ttls_close(
+        hash_ttls(backend),
+        hash_ttls(&restored),
+        "hash field TTLs differ",
+    );
+}
+
+fn assert_ttls_close<K: Ord + Debug>(
+    expected: BTreeMap<K, Instant>,
+    actual: BTreeMap<K, Instant>,
+    msg: &str,
+) {
+    assert_eq!(
+        expected.keys().collect::<Vec<_>>(),
+        actual.keys().collect::<Vec<_>>(),
+        "{}",
+        msg
+    );
+    for (key, deadline) in expected {
+        let restored = actual[&key];
+        let drift = deadline
+            .checked_duration_since(restored)
+            .unwrap_or_else(|| restored.duration_since(deadline));
+        assert!(
+            drift <= Duration::from_millis(10),
+            "TTL of {:?} drifted by {:?}",
+            key,
+            drift
+        );
+    }
+}
+
+fn strings(backend: &Backend) -> BTreeMap<Vec<u8>, RespFrame> {
+    backend
+        .map
+        .iter()
+        .map(|v| (v.key().clone(), v.value().clone()))
+        .collect()
+}
+
+fn hashes(backend: &Backend) -> BTreeMap<Vec<u8>, BTreeMap<String, RespFrame>> {
+    backend
+        .hmap
+        .iter()
+        .map(|v| {
+            let fields = v
+                .value()
+                .iter()
+                .map(|f| (f.key().clone(), f.value().clone()))
+                .collect();
+            (v.key().clone(), fields)
+        })
+        .collect()
+}
+
+fn sets(backend: &Backend) -> BTreeMap<Vec<u8>, Vec<String>> {
+    backend
+        .set
+        .iter()
+        .map(|v| {
+            let mut members = v
+                .value()
+                .iter()
+                .map(|m| m.key().clone())
+                .collect::<Vec<_>>();
+            members.sort();
+            (v.key().clone(), members)
+        })
+        .collect()
+}
+
+fn zsets(backend: &Backend) -> BTreeMap<Vec<u8>, Vec<(String, f64)>> {
+    backend
+        .zset
+        .iter()
+        .map(|v| {
+            let members = v.value().iter().map(|(m, s)| (m.to_string(), s)).collect();
+            (v.key().clone(), members)
+        })
+        .collect()
+}
+
+fn lists(backend: &Backend) -> BTreeMap<Vec<u8>, Vec<Vec<u8>>> {
+    backend
+        .list
+        .iter()
+        .map(|v| (v.key().clone(), v.value().iter().cloned().collect()))
+        .collect()
+}
+
+fn key_ttls(backend: &Backend) -> BTreeMap<Vec<u8>, Instant> {
+    backend
+        .expires
+        .iter()
+        .map(|v| (v.key().clone(), *v.value()))
+        .collect()
+}
+
+fn hash_ttls(backend: &Backend) -> BTreeMap<(Vec<u8>, String), Instant> {
+    backend
+        .hexpires
+        .iter()
+        .flat_map(|v| {
+            let key = v.key().clone();
+            v.value()
+                .iter()
+                .map(|f| ((key.clone(), f.key().clone()), *f.value()))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{BulkString, ListEnd};
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let backend = Backend::new();
+        backend.set(b"hello".to_vec(), BulkString::from("world").into());
+        backend.set(b"\xff\xfe".to_vec(), RespFrame::Integer(42));
+        backend.hset(
+            b"map".to_vec(),
+            "a".to_string(),
+            BulkString::from("1").into(),
+        );
+        backend.hset(
+            b"map".to_vec(),
+            "b".to_string(),
+            BulkString::from("2").into(),
+        );
+        backend.hexpire(b"map", "a", Duration::from_secs(100));
+        backend.add_member(b"set".to_vec(), "x".to_string());
+        backend.add_member(b"set".to_vec(), "y".to_string());
+        backend.zadd(
+            b"zset".to_vec(),
+            vec![(1.5, "one".to_string()), (-2.0, "two".to_string())],
+        );
+        backend.push(
+            b"list".to_vec(),
+            vec![b"a".to_vec(), b"b".to_vec()],
+            ListEnd::Right,
+        );
+        backend.set(b"hello".to_vec(), BulkString::from("again").into());
+        backend.expire(b"list", 100);
+
+        assert_snapshot_roundtrip(&backend);
+    }
+}